@@ -1,19 +1,38 @@
-use crate::cli::{Cli, Commands, RuleAction};
-use crate::config::Config;
-use crate::discovery::FileDiscovery;
+use crate::cli::{Cli, Commands, ConfigAction, OutputFormat, RuleAction};
+use crate::config::{Config, ConfigTrace, Date, OnBaseBranch, Suppression};
+use crate::discovery::{COMMON_EXCLUDES, FileDiscovery};
 use crate::error::{Result, SizelintError};
-use crate::git::{GitError, GitRepo};
-use crate::output::{OutputFormatter, print_error, print_progress, print_success};
-use crate::rules::{ConfigurableRule, RuleEngine};
+use crate::git::{CommitWindow, DiffFilter, GitError, GitLayout, GitRepo, MAX_SIZE_ATTRIBUTE};
+use crate::output::{
+    ExitReason, ExitStatus, JSON_SCHEMA_VERSIONS, OutputFormatter, defer_progress,
+    finish_deferred_progress, locale_supports_utf8, print_error, print_progress, print_size_table,
+    print_success, print_warning, set_ascii, symbols,
+};
+use crate::rules::{ConfigurableRule, PartialResults, RuleEngine, SizeRevisions, Violation};
 use colored::*;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::process::ExitCode;
-use tracing::{Level, debug, span};
+use tracing::{Level, debug, span, warn};
+
+/// Exit code when a check is interrupted and only partial results were shown.
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+// Rules listed by `--profile`
+const PROFILE_TOP_RULES: usize = 5;
+
+// Commits sized and checked together in a history walk, bounding how many
+// blobs are held in memory at once
+const HISTORY_BATCH_COMMITS: usize = 256;
 
 pub struct App {
     cli: Cli,
     config: Config,
+    // The config file in use, if any
+    config_path: Option<PathBuf>,
+    // `--git-dir` and `--work-tree`, passed to every git command
+    git_layout: GitLayout,
+    partial: PartialResults,
 }
 
 impl App {
@@ -21,28 +40,65 @@ impl App {
         let _span = span!(Level::DEBUG, "App::new").entered();
         debug!("Creating new App instance");
 
-        let config = Self::load_config(&cli)?;
+        if let Some(version) = cli.get_json_schema_version()
+            && !JSON_SCHEMA_VERSIONS.contains(&version)
+        {
+            let supported: Vec<String> = JSON_SCHEMA_VERSIONS.iter().map(u32::to_string).collect();
+            return Err(SizelintError::config_invalid(
+                "json_schema_version".to_string(),
+                version.to_string(),
+                format!("one of {}", supported.join(", ")),
+            ));
+        }
+
+        if cli.get_quiet_on_success() {
+            defer_progress();
+        }
+        let absolute = |path: &Option<PathBuf>| -> Result<Option<PathBuf>> {
+            path.as_deref()
+                .map(|p| {
+                    std::path::absolute(p)
+                        .map_err(|e| SizelintError::CurrentDirectory { source: e })
+                })
+                .transpose()
+        };
+        let git_layout = GitLayout {
+            git_dir: absolute(&cli.git_dir)?,
+            work_tree: absolute(&cli.work_tree)?,
+        };
+
+        let ascii = cli.ascii || !locale_supports_utf8();
+        set_ascii(ascii);
+        let (config, config_path) = Self::load_config(&cli)?;
+        set_ascii(ascii || config.sizelint.ascii);
 
         debug!("App initialized successfully");
-        Ok(Self { cli, config })
+        Ok(Self {
+            cli,
+            config,
+            config_path,
+            git_layout,
+            partial: PartialResults::default(),
+        })
     }
 
-    fn load_config(cli: &Cli) -> Result<Config> {
+    fn load_config(cli: &Cli) -> Result<(Config, Option<PathBuf>)> {
         let _span = span!(Level::DEBUG, "load_config").entered();
 
         // Priority order: 1) subcommand config, 2) global config, 3) auto-discover, 4) default
-        let config = if let Some(config_path) = cli.get_check_config() {
+        // An explicit config is not merged with the built-in defaults
+        let (config_path, with_defaults) = if let Some(config_path) = cli.get_subcommand_config() {
             debug!(
                 "Loading config from subcommand-specified path: {}",
                 config_path.display()
             );
-            Config::load_from_file(&config_path)?
+            (Some(config_path), false)
         } else if let Some(config_path) = &cli.config {
             debug!(
                 "Loading config from global config path: {}",
                 config_path.display()
             );
-            Config::load_from_file(config_path)?
+            (Some(config_path.clone()), false)
         } else {
             let current_dir = std::env::current_dir()
                 .map_err(|e| SizelintError::CurrentDirectory { source: e })?;
@@ -52,19 +108,105 @@ impl App {
                 current_dir.display()
             );
 
-            if let Some(config_path) = Config::find_config_file(&current_dir) {
+            let config_path = Config::find_config_file(&current_dir);
+            if let Some(config_path) = &config_path {
                 debug!("Found config file: {}", config_path.display());
                 print_progress(&format!("Found config file: {}", config_path.display()));
-                Config::load_with_defaults(config_path)?
             } else {
                 debug!("No config file found, using defaults");
                 print_progress("No config file found, using defaults");
-                Config::default()
             }
+            (config_path, true)
         };
 
+        // The per-user config sits beneath the chosen config file. Both are
+        // loaded before failing, so that errors in each are reported together
+        let mut errors = Vec::new();
+        let mut collect = |e: SizelintError| match e {
+            SizelintError::ConfigErrors { errors: inner } => errors.extend(inner),
+            e => errors.push(e),
+        };
+        let user_config = Self::load_user_config(cli).unwrap_or_else(|e| {
+            collect(e);
+            None
+        });
+        let file_config =
+            config_path
+                .as_deref()
+                .and_then(|path| match Config::load_table(path, cli.lenient) {
+                    Ok(table) => Some((path.to_path_buf(), table)),
+                    Err(e) => {
+                        collect(e);
+                        None
+                    }
+                });
+        match errors.len() {
+            0 => {}
+            1 => return Err(errors.remove(0)),
+            _ => return Err(SizelintError::config_errors(errors)),
+        }
+
+        let mut layers: Vec<(PathBuf, toml::Table)> =
+            user_config.into_iter().chain(file_config).collect();
+        // Percentages resolve against the quota of all layers together
+        Config::resolve_quota_sizes(layers.iter_mut().map(|(_, table)| table))?;
+        let mut config = if with_defaults {
+            Config::default()
+        } else {
+            Config::from_table(toml::Table::new())?
+        };
+        for (_, table) in &layers {
+            config.merge_table(table.clone())?;
+        }
+        let ci = crate::config::ci_enabled(std::env::var("CI").ok().as_deref());
+        config.apply_ci_overrides(ci)?;
+
+        if let Some(key) = &cli.trace_config {
+            // The same layers, in the same order, as merged above
+            let mut trace = if with_defaults {
+                ConfigTrace::with_defaults()
+            } else {
+                ConfigTrace::new()
+            };
+            for (path, table) in &layers {
+                trace.record(path.display().to_string(), table.clone());
+            }
+            if ci && !config.sizelint.ci_overrides.is_empty() {
+                trace.record(
+                    "ci_overrides".to_string(),
+                    config.sizelint.ci_overrides.clone(),
+                );
+            }
+            let trace = trace.trace(key, &config)?;
+            if cli.get_format() == OutputFormat::Json {
+                eprintln!("{}", serde_json::to_string(&trace)?);
+            } else {
+                eprintln!("{trace}");
+            }
+        }
+
         debug!("Config loaded successfully");
-        Ok(config)
+        Ok((config, config_path))
+    }
+
+    /// The per-user config and its path, unless disabled with
+    /// `--no-global-config` or absent.
+    fn load_user_config(cli: &Cli) -> Result<Option<(PathBuf, toml::Table)>> {
+        if cli.no_global_config {
+            return Ok(None);
+        }
+        let xdg_config_home = std::env::var("XDG_CONFIG_HOME")
+            .or_else(|_| std::env::var("APPDATA"))
+            .ok();
+        let home = std::env::var("HOME").ok();
+        match crate::config::global_config_path(xdg_config_home.as_deref(), home.as_deref()) {
+            Some(path) if path.is_file() => {
+                debug!("Loading per-user config: {}", path.display());
+                let table = Config::load_table(&path, cli.lenient)?;
+                Ok(Some((path, table)))
+            }
+            _ => Ok(None),
+        }
     }
 
     pub fn run(&self) -> Result<ExitCode> {
@@ -74,7 +216,10 @@ impl App {
                 force,
                 stdout,
                 edit,
-            } => self.run_init(force, stdout, edit),
+                force_edit,
+                preset,
+            } => self.run_init(force, stdout, edit, force_edit, preset.as_deref()),
+            Commands::Config { action } => self.run_config(action),
             Commands::Rules { action } => self.run_rules(action),
             Commands::Completions { shell } => {
                 Cli::generate_completion(&shell).map_err(|e| {
@@ -82,26 +227,102 @@ impl App {
                 })?;
                 Ok(ExitCode::SUCCESS)
             }
+            Commands::Info { json } => self.run_info(json),
         }
     }
 
     fn run_check(&self, paths: Vec<PathBuf>) -> Result<ExitCode> {
+        let Some(jobs) = self.cli.get_jobs() else {
+            return self.check(paths);
+        };
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build()
+            .map_err(|e| {
+                SizelintError::config_invalid("jobs".to_string(), jobs.to_string(), e.to_string())
+            })?;
+        pool.install(|| self.check(paths))
+    }
+
+    fn check(&self, paths: Vec<PathBuf>) -> Result<ExitCode> {
         let start = std::time::Instant::now();
         let check_root = self.check_root(&paths)?;
 
-        let git_range = match self.active_git_range() {
-            Some(raw) => match GitRepo::discover(&check_root) {
-                Ok(repo) => Some(repo.expand_git_range(&raw)?),
-                Err(_) => Some(raw),
-            },
-            None => None,
-        };
+        if self.cli.get_dir_sizes() {
+            let repo = self.git_repo(&check_root)?;
+            print_size_table(&repo.tree_sizes_by_dir()?)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+
+        if self.cli.get_gitdir_size() {
+            return self.run_gitdir_size(&check_root, start);
+        }
 
-        let files = if paths.is_empty() {
-            self.discover_files_at(&check_root, git_range.as_deref())?
+        if let Some(max_size) = self.cli.get_history_max() {
+            return self.run_history_max(&check_root, &max_size, start);
+        }
+
+        if let Some(rev) = self.cli.get_ref() {
+            return self.run_check_ref(&check_root, &rev, start);
+        }
+
+        if let Some(archive) = self.cli.get_in_archive() {
+            return self.run_check_archive(&archive, start);
+        }
+
+        let commits = self.cli.get_commits();
+        if !commits.is_empty() {
+            return self.run_check_commits(&check_root, &commits, start);
+        }
+
+        // (as given, expanded) for each active range
+        let raw_ranges = self.active_git_ranges();
+        if !raw_ranges.is_empty() && !self.repo_has_commits(&check_root)? {
+            print_warning(&format!(
+                "Repository has no commits yet, so {} has no changes to check",
+                raw_ranges.join(", ")
+            ));
+            if !self.cli.get_quiet_on_success() {
+                print_success("No files to check");
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+        let git_ranges: Vec<(String, String)> = match self.git_repo(&check_root) {
+            Ok(repo) => raw_ranges
+                .into_iter()
+                .map(|raw| Ok((raw.clone(), self.expand_git_range(&repo, &raw)?)))
+                .collect::<Result<_>>()?,
+            Err(_) => raw_ranges
+                .into_iter()
+                .map(|raw| (raw.clone(), raw))
+                .collect(),
+        };
+        let label_ranges = git_ranges.len() > 1;
+        let mut origins = HashMap::new();
+
+        let mut missing = Vec::new();
+        let files = if let Some(list) = self.cli.get_from_manifest() {
+            let (present, absent): (Vec<_>, Vec<_>) = crate::manifest::read_path_list(&list)?
+                .into_iter()
+                .partition(|p| p.exists());
+            missing = absent;
+            self.file_discovery(&check_root)?
+                .discover_specific_paths(&present)?
+        } else if paths.is_empty() {
+            self.discover_in_ranges(&check_root, &git_ranges, &mut origins)?
+        } else if !git_ranges.is_empty() || self.uses_git_diff(&check_root) {
+            // Git modes check the changed files that lie within the given paths
+            let scopes: Vec<PathBuf> = paths
+                .iter()
+                .filter_map(|p| std::fs::canonicalize(p).ok())
+                .collect();
+            self.discover_in_ranges(&check_root, &git_ranges, &mut origins)?
+                .into_iter()
+                .filter(|file| scopes.iter().any(|scope| file.starts_with(scope)))
+                .collect()
         } else {
             // Explicit files pass through; directories use the same
-            // git-aware discovery as the no-paths case.
+            // discovery as the no-paths case.
             let mut files = Vec::new();
             let mut dirs = Vec::new();
             for path in paths {
@@ -112,23 +333,81 @@ impl App {
                 }
             }
             for dir in &dirs {
-                files.extend(self.discover_files_at(dir, git_range.as_deref())?);
+                files.extend(self.discover_in_ranges(dir, &git_ranges, &mut origins)?);
             }
             files
         };
 
-        if files.is_empty() && git_range.is_none() {
-            print_success("No files to check");
+        let files = match self.cli.get_modified_since() {
+            Some(when) => {
+                let cutoff =
+                    crate::discovery::parse_modified_since(&when, std::time::SystemTime::now())?;
+                let total = files.len();
+                let recent = crate::discovery::modified_since(files, cutoff);
+                print_progress(&format!(
+                    "Skipping {} of {total} files not modified since {when}",
+                    total - recent.len()
+                ));
+                recent
+            }
+            None => files,
+        };
+
+        if self.cli.get_by_extension() {
+            let sizes = crate::fs::sizes_by_extension(&files);
+            match self.cli.get_format() {
+                OutputFormat::Json => self.formatter()?.print_json(&sizes)?,
+                _ => print_size_table(&sizes)?,
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+
+        if let Some(dump_path) = self.cli.get_dump_sizes() {
+            crate::fs::write_atomic(&dump_path, crate::fs::sizes_csv(&files, &check_root))?;
+            print_progress(&format!(
+                "Wrote sizes of {} files to {}",
+                files.len(),
+                dump_path.display()
+            ));
+        }
+
+        if files.is_empty() && git_ranges.is_empty() && missing.is_empty() {
+            if !self.cli.get_quiet_on_success() {
+                print_success("No files to check");
+            }
             return Ok(ExitCode::SUCCESS);
         }
 
+        let files = match self.cli.get_sample() {
+            Some(n) => {
+                let seed = self.cli.get_seed().unwrap_or_else(random_seed);
+                let total = files.len();
+                let sampled = sample_files(files, n, seed);
+                print_warning(&format!(
+                    "Checking a random sample of {} of {total} files (--seed {seed}); \
+                     unsampled files may still have violations",
+                    sampled.len()
+                ));
+                sampled
+            }
+            None => files,
+        };
+
         let file_count = files.len();
         if file_count > 0 {
             print_progress(&format!("Found {} files to check", file_count));
         }
 
         debug!("Setting up rules...");
-        let rule_engine = self.create_rule_engine()?;
+        let rule_engine = self
+            .create_rule_engine()?
+            .with_root(&check_root)
+            .with_size_overrides(self.size_overrides(&check_root, &files)?)
+            .with_tracked_files(self.tracked_files(&check_root, &files)?)
+            .with_max_violations(self.cli.get_max_violations())
+            .with_fail_fast(self.cli.get_fast_fail())
+            .with_pass_records(self.cli.get_verbose_skips().is_some())
+            .with_profiling(self.cli.get_profile());
 
         debug!("Running checks...");
         let mut violations = if file_count > 0 {
@@ -136,41 +415,177 @@ impl App {
         } else {
             vec![]
         };
+        if label_ranges {
+            for v in &mut violations {
+                v.range = origins.get(&v.path).cloned();
+            }
+        }
+        violations.extend(crate::manifest::missing_file_violations(&missing));
+        // With --fast-fail, the first error already decides the exit code
+        let failed_fast = self.cli.get_fast_fail() && rule_engine.stopped_early();
+
+        if let Some(manifest_path) = self.cli.get_manifest() {
+            let manifest =
+                crate::manifest::build_manifest(&files, &check_root, self.cli.get_manifest_hash())?;
+            let mut json = serde_json::to_string_pretty(&manifest)?;
+            json.push('\n');
+            crate::fs::write_atomic(&manifest_path, json)?;
+            print_progress(&format!(
+                "Wrote manifest of {} files to {}",
+                manifest.len(),
+                manifest_path.display()
+            ));
+        }
 
-        // Phase 2: walk git history for oversized blobs
-        if let Some(range) = git_range
-            && !self.cli.get_no_history()
-        {
-            let discovery = FileDiscovery::new(&check_root, &self.config.sizelint.excludes)?;
-            let history_blobs = discovery.discover_history_blobs(&range)?;
-            if !history_blobs.is_empty() {
+        // Kept apart from the per-path dedup, which would drop them in favour
+        // of a size violation on the same path
+        let mut revision_violations = Vec::new();
+        for (raw, range) in &git_ranges {
+            if failed_fast {
+                break;
+            }
+            let mut range_violations = Vec::new();
+
+            // Growth check: compare sizes against the base of the range
+            if let Some(max_growth) = &self.config.sizelint.max_growth
+                && file_count > 0
+            {
+                let range_files: Vec<PathBuf> = files
+                    .iter()
+                    .filter(|f| origins.get(*f).is_none_or(|origin| origin == raw))
+                    .cloned()
+                    .collect();
+                let repo = self.git_repo(&check_root)?;
+                let base = repo.range_base(range)?;
+                let base_sizes = repo.blob_sizes_at(&base, &range_files)?;
+                range_violations.extend(crate::rules::check_growth(
+                    &range_files,
+                    &base_sizes,
+                    max_growth.bytes(),
+                )?);
+            }
+
+            // Aggregate check: total tracked size at HEAD against the base
+            if let Some(max_tree_growth) = &self.config.sizelint.max_tree_growth {
+                let repo = self.git_repo(&check_root)?;
+                let base_total = repo.tree_size(&repo.range_base(range)?)?;
+                let head_total = repo.tree_size("HEAD")?;
+                let (sign, delta) = if head_total >= base_total {
+                    ("+", head_total - base_total)
+                } else {
+                    ("-", base_total - head_total)
+                };
                 print_progress(&format!(
-                    "Scanning {} blob(s) from git history",
-                    history_blobs.len()
+                    "Tree size {sign}{} since {raw}",
+                    crate::rules::format_size(delta)
+                ));
+                range_violations.extend(crate::rules::check_tree_growth(
+                    base_total,
+                    head_total,
+                    max_tree_growth.bytes(),
                 ));
-                let blob_violations = rule_engine.check_history_blobs(&history_blobs)?;
-                violations.extend(blob_violations);
             }
+
+            // Phase 2: walk git history for oversized blobs
+            if !self.cli.get_no_history() {
+                let discovery = self.file_discovery(&check_root)?;
+                let mut revisions = self
+                    .config
+                    .sizelint
+                    .max_size_revisions
+                    .map(|_| SizeRevisions::new(self.config.sizelint.revision_min_size()));
+                let (history_violations, blob_count) = check_history_in_batches(
+                    &discovery,
+                    Some(range),
+                    &rule_engine,
+                    HISTORY_BATCH_COMMITS,
+                    revisions.as_mut(),
+                )?;
+                if blob_count > 0 {
+                    print_progress(&format!("Scanned {blob_count} blob(s) from git history"));
+                }
+                range_violations.extend(history_violations);
+                if let (Some(revisions), Some(max_revisions)) =
+                    (revisions, self.config.sizelint.max_size_revisions)
+                {
+                    let mut churn = revisions.check(max_revisions);
+                    if label_ranges {
+                        for v in &mut churn {
+                            v.range = Some(raw.clone());
+                        }
+                    }
+                    revision_violations.extend(churn);
+                }
+            }
+
+            if label_ranges {
+                for v in &mut range_violations {
+                    v.range = Some(raw.clone());
+                }
+            }
+            violations.extend(range_violations);
         }
 
         // Deduplicate across phases: keep only the largest violation per path.
         // Phase 1 entries come first, so equal sort_keys preserve Phase 1.
-        let mut best: std::collections::HashMap<std::path::PathBuf, crate::rules::Violation> =
-            std::collections::HashMap::new();
-        for v in violations {
-            best.entry(v.path.clone())
-                .and_modify(|existing| {
-                    if v.sort_key > existing.sort_key {
-                        *existing = v.clone();
-                    }
-                })
-                .or_insert(v);
+        let mut violations = largest_per_path(violations);
+        violations.extend(revision_violations);
+
+        let (kept, expired) = apply_suppressions(
+            violations,
+            &self.config.sizelint.suppress,
+            &check_root,
+            crate::config::today(),
+        )?;
+        violations = kept;
+        for s in expired {
+            print_warning(&format!(
+                "Suppression of '{}' for {} expired on {}",
+                s.rule, s.path_glob, s.until
+            ));
+        }
+
+        if self.cli.get_baseline_auto() {
+            violations = self.apply_auto_baseline(violations, &check_root)?;
+        }
+
+        let mut stopped_early = rule_engine.stopped_early();
+        if let Some(max) = self.cli.get_max_violations()
+            && violations.len() > max
+        {
+            violations.truncate(max);
+            stopped_early = true;
+        }
+        if stopped_early {
+            print_warning(&format!(
+                "Stopped after {} violations; more may exist",
+                violations.len()
+            ));
         }
-        let violations: Vec<_> = best.into_values().collect();
+
+        if self.cli.get_profile() {
+            print_progress("Slowest rules:");
+            for (name, elapsed) in rule_engine.slowest_rules(PROFILE_TOP_RULES) {
+                print_progress(&format!("  {name}: {elapsed:.1?}"));
+            }
+        }
+
+        if self.config.sizelint.warn_unmatched_rules {
+            for name in rule_engine.unmatched_rules() {
+                warn!("Rule '{name}' did not match any checked file");
+            }
+        }
+
         let suggestions = rule_engine.suggestions();
         let descriptions = rule_engine.descriptions();
-
-        self.output_results(
+        let passed = self
+            .cli
+            .get_verbose_skips()
+            .map(|limit| rule_engine.largest_passed(limit))
+            .unwrap_or_default();
+
+        self.output_results_with(
+            self.formatter()?.with_passed_files(passed),
             &violations,
             file_count,
             start.elapsed(),
@@ -179,6 +594,192 @@ impl App {
         )
     }
 
+    /// Without a baseline under `root`, record `violations` in a new one and
+    /// report none; otherwise report only the violations it does not list.
+    fn apply_auto_baseline(
+        &self,
+        violations: Vec<Violation>,
+        root: &std::path::Path,
+    ) -> Result<Vec<Violation>> {
+        let path = root.join(crate::baseline::DEFAULT_BASELINE);
+        if path.exists() {
+            let baseline = crate::baseline::read_baseline(&path)?;
+            return Ok(crate::baseline::new_violations(violations, &baseline, root));
+        }
+
+        let entries = crate::baseline::build_baseline(&violations, root);
+        crate::baseline::write_baseline(&path, &entries)?;
+        if entries.is_empty() {
+            print_progress(&format!("Wrote empty baseline to {}", path.display()));
+        } else {
+            print_warning(&format!(
+                "Added {} existing violation(s) to new baseline {}; \
+                 they will not fail future runs",
+                entries.len(),
+                path.display()
+            ));
+        }
+        Ok(vec![])
+    }
+
+    /// Report the on-disk size of `.git`, failing above `max_gitdir_size`.
+    fn run_gitdir_size(
+        &self,
+        root: &std::path::Path,
+        start: std::time::Instant,
+    ) -> Result<ExitCode> {
+        let git_dir = self.git_repo(root)?.git_dir()?;
+        let size = crate::fs::dir_size(&git_dir)?;
+        print_progress(&format!(
+            "Git directory {} is {}",
+            git_dir.display(),
+            crate::rules::format_size(size)
+        ));
+
+        let violations = match &self.config.sizelint.max_gitdir_size {
+            Some(max) => crate::rules::check_gitdir_size(&git_dir, size, max.bytes())
+                .into_iter()
+                .collect(),
+            None => vec![],
+        };
+        self.output_results(
+            &violations,
+            0,
+            start.elapsed(),
+            &HashMap::new(),
+            &HashMap::new(),
+        )
+    }
+
+    /// Whether the repository at `root` has a commit at HEAD; true outside
+    /// a repository, where git errors are reported by the git modes themselves.
+    fn repo_has_commits(&self, root: &std::path::Path) -> Result<bool> {
+        match self.git_repo(root) {
+            Ok(repo) => Ok(repo.has_commits()?),
+            Err(_) => Ok(true),
+        }
+    }
+
+    /// Check every blob reachable from HEAD against a single size cap.
+    fn run_history_max(
+        &self,
+        root: &std::path::Path,
+        max_size: &str,
+        start: std::time::Instant,
+    ) -> Result<ExitCode> {
+        let rule_engine =
+            RuleEngine::history_max(max_size)?.with_partial_results(self.partial.clone());
+        let discovery = self.file_discovery(root)?;
+        self.print_discovery("history", root, &discovery);
+        let (violations, blob_count) = if self.repo_has_commits(root)? {
+            check_history_in_batches(&discovery, None, &rule_engine, HISTORY_BATCH_COMMITS, None)?
+        } else {
+            print_warning("Repository has no commits yet, so there is no history to check");
+            (Vec::new(), 0)
+        };
+        print_progress(&format!("Scanned {blob_count} blob(s) from git history"));
+
+        self.output_results(
+            &violations,
+            blob_count,
+            start.elapsed(),
+            &rule_engine.suggestions(),
+            &rule_engine.descriptions(),
+        )
+    }
+
+    /// Check the blobs of each of `commits`, keeping the largest violation
+    /// per path.
+    fn run_check_commits(
+        &self,
+        root: &std::path::Path,
+        commits: &[String],
+        start: std::time::Instant,
+    ) -> Result<ExitCode> {
+        let rule_engine = self.create_rule_engine()?.with_root(root);
+        let blobs = self.file_discovery(root)?.discover_commit_blobs(commits)?;
+        print_progress(&format!(
+            "Scanning {} blob(s) from {} commit{}",
+            blobs.len(),
+            commits.len(),
+            if commits.len() == 1 { "" } else { "s" }
+        ));
+
+        let violations = largest_per_path(rule_engine.check_history_blobs(&blobs)?);
+        self.output_results(
+            &violations,
+            blobs.len(),
+            start.elapsed(),
+            &rule_engine.suggestions(),
+            &rule_engine.descriptions(),
+        )
+    }
+
+    /// Check the blob sizes in the tree at `rev`, reporting repo-relative paths.
+    fn run_check_ref(
+        &self,
+        root: &std::path::Path,
+        rev: &str,
+        start: std::time::Instant,
+    ) -> Result<ExitCode> {
+        let repo = self.git_repo(root)?;
+        let rule_engine = self.create_rule_engine()?.with_root(repo.root());
+        let files: Vec<(PathBuf, u64)> = self
+            .file_discovery(repo.root())?
+            .discover_tree_blobs(rev)?
+            .into_iter()
+            .map(|blob| (PathBuf::from(blob.path), blob.size))
+            .collect();
+        print_progress(&format!("Found {} files at {rev} to check", files.len()));
+
+        let mut violations = rule_engine.check_sized_files(&files)?;
+        for v in &mut violations {
+            if let Ok(rel) = v.path.strip_prefix(repo.root()) {
+                v.path = rel.to_path_buf();
+            }
+        }
+
+        self.output_results(
+            &violations,
+            files.len(),
+            start.elapsed(),
+            &rule_engine.suggestions(),
+            &rule_engine.descriptions(),
+        )
+    }
+
+    /// Check the members of `archive` by their uncompressed sizes, reported
+    /// as `archive!member`.
+    fn run_check_archive(
+        &self,
+        archive: &std::path::Path,
+        start: std::time::Instant,
+    ) -> Result<ExitCode> {
+        let rule_engine = self.create_rule_engine()?;
+        let members: Vec<(PathBuf, u64)> = crate::archive::members(archive)?
+            .into_iter()
+            .map(|(name, size)| (PathBuf::from(name), size))
+            .collect();
+        print_progress(&format!(
+            "Found {} members in {} to check",
+            members.len(),
+            archive.display()
+        ));
+
+        let mut violations = rule_engine.check_sized_files(&members)?;
+        for v in &mut violations {
+            v.path = PathBuf::from(format!("{}!{}", archive.display(), v.path.display()));
+        }
+
+        self.output_results(
+            &violations,
+            members.len(),
+            start.elapsed(),
+            &rule_engine.suggestions(),
+            &rule_engine.descriptions(),
+        )
+    }
+
     /// Root directory for git operations.
     ///
     /// When explicit paths are given, discovers the git repo for each and
@@ -186,11 +787,14 @@ impl App {
     /// repos and `--git` is active.
     fn check_root(&self, paths: &[PathBuf]) -> Result<PathBuf> {
         if paths.is_empty() {
+            if let Some(work_tree) = &self.cli.work_tree {
+                return Ok(work_tree.clone());
+            }
             return std::env::current_dir()
                 .map_err(|e| SizelintError::CurrentDirectory { source: e });
         }
 
-        let git_active = self.active_git_range().is_some();
+        let git_active = !self.active_git_ranges().is_empty();
         let mut roots = HashSet::new();
         let mut first_root = None;
 
@@ -201,7 +805,7 @@ impl App {
                 p.parent().unwrap_or(p).to_path_buf()
             };
 
-            if let Ok(repo) = GitRepo::discover(&dir) {
+            if let Ok(repo) = self.git_repo(&dir) {
                 let root = repo.root().to_path_buf();
                 if first_root.is_none() {
                     first_root = Some(root.clone());
@@ -234,9 +838,175 @@ impl App {
             })
     }
 
-    /// Returns the active git range if --git or config git is in effect.
-    fn active_git_range(&self) -> Option<String> {
-        self.cli.get_git().or(self.config.sizelint.git.clone())
+    /// Which of `files` are tracked by git, when a rule has `require_tracked`.
+    ///
+    /// `None` outside a repository, so those rules become no-ops there.
+    fn tracked_files(
+        &self,
+        root: &std::path::Path,
+        files: &[PathBuf],
+    ) -> Result<Option<HashSet<PathBuf>>> {
+        let required = self.config.rules.as_ref().is_some_and(|rules| {
+            rules
+                .get_enabled_rules()
+                .iter()
+                .any(|(_, rule)| rule.require_tracked)
+        });
+        if !required || files.is_empty() {
+            return Ok(None);
+        }
+        let Ok(repo) = self.git_repo(root) else {
+            return Ok(None);
+        };
+
+        let absolute: Vec<PathBuf> = files
+            .iter()
+            .map(|f| std::path::absolute(f).unwrap_or_else(|_| f.clone()))
+            .collect();
+        let tracked: HashSet<PathBuf> = repo.filter_tracked(&absolute)?.into_iter().collect();
+        Ok(Some(
+            files
+                .iter()
+                .zip(&absolute)
+                .filter(|(_, abs)| tracked.contains(*abs))
+                .map(|(file, _)| file.clone())
+                .collect(),
+        ))
+    }
+
+    /// Per-file maximum sizes from `.gitattributes` and sidecar files, as
+    /// enabled; a sidecar limit wins over the attribute.
+    fn size_overrides(
+        &self,
+        root: &std::path::Path,
+        files: &[PathBuf],
+    ) -> Result<HashMap<PathBuf, u64>> {
+        let mut limits = self.gitattribute_limits(root, files)?;
+        if self.config.sizelint.use_sidecar_limits {
+            limits.extend(crate::fs::sidecar_limits(files)?);
+        }
+        Ok(limits)
+    }
+
+    /// Per-file maximum sizes from the `sizelint-max` gitattribute, if enabled.
+    fn gitattribute_limits(
+        &self,
+        root: &std::path::Path,
+        files: &[PathBuf],
+    ) -> Result<HashMap<PathBuf, u64>> {
+        if !self.config.sizelint.use_gitattributes || files.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let repo = self.git_repo(root)?;
+        repo.check_attr(MAX_SIZE_ATTRIBUTE, files)?
+            .into_iter()
+            .map(|(path, value)| Ok((path, crate::rules::parse_size_string(&value)?)))
+            .collect()
+    }
+
+    /// Expand `raw` for diffing, applying `on_base_branch` when HEAD is
+    /// already at the base ref and the range would be empty.
+    fn expand_git_range(&self, repo: &GitRepo, raw: &str) -> Result<String> {
+        if !repo.base_is_head(raw)? {
+            return Ok(repo.expand_git_range(raw)?);
+        }
+        match self.config.sizelint.on_base_branch {
+            OnBaseBranch::Empty => Ok(repo.expand_git_range(raw)?),
+            OnBaseBranch::LastCommit => {
+                print_progress(&format!(
+                    "HEAD is already at {raw}; checking the last commit instead"
+                ));
+                Ok("HEAD~1..HEAD".to_string())
+            }
+            OnBaseBranch::Error => Err(GitError::BaseIsHead {
+                git_ref: raw.to_string(),
+            }
+            .into()),
+        }
+    }
+
+    /// Returns the active git ranges from --git, or config git if set.
+    /// Config git yields to an explicit `--staged` or `--working-tree`.
+    fn active_git_ranges(&self) -> Vec<String> {
+        let cli_ranges = self.cli.get_git();
+        if !cli_ranges.is_empty() || self.cli.get_staged() || self.cli.get_working_tree() {
+            return cli_ranges;
+        }
+        self.config.sizelint.git.iter().cloned().collect()
+    }
+
+    /// Whether discovery under `root` lists staged or working tree changes
+    /// instead of walking the directory.
+    fn uses_git_diff(&self, root: &std::path::Path) -> bool {
+        self.cli.get_staged()
+            || self.cli.get_working_tree()
+            || ((self.config.sizelint.check_staged || self.config.sizelint.check_working_tree)
+                && self.git_repo(root).is_ok())
+    }
+
+    /// Files under `root` changed in any of `ranges`, or all files when
+    /// there are none. Records the first range each file was found in.
+    fn discover_in_ranges(
+        &self,
+        root: &std::path::Path,
+        ranges: &[(String, String)],
+        origins: &mut HashMap<PathBuf, String>,
+    ) -> Result<Vec<PathBuf>> {
+        if ranges.is_empty() {
+            return self.discover_files_at(root, None);
+        }
+        let mut files = Vec::new();
+        for (raw, range) in ranges {
+            for file in self.discover_files_at(root, Some(range))? {
+                if !origins.contains_key(&file) {
+                    origins.insert(file.clone(), raw.clone());
+                    files.push(file);
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    /// The repository containing `start_path`, located through
+    /// `--git-dir` and `--work-tree` when given.
+    fn git_repo<P: AsRef<std::path::Path>>(&self, start_path: P) -> Result<GitRepo> {
+        Ok(GitRepo::discover_with_layout(
+            start_path,
+            self.git_layout.clone(),
+        )?)
+    }
+
+    fn file_discovery(&self, root: &std::path::Path) -> Result<FileDiscovery> {
+        let diff_filter = if self.cli.get_added_only() {
+            DiffFilter::Added
+        } else {
+            DiffFilter::Changed
+        };
+        let mut excludes = self.config.sizelint.excludes.clone();
+        if self.config.sizelint.exclude_common {
+            excludes.extend(COMMON_EXCLUDES.iter().map(|p| p.to_string()));
+        }
+        if !self.config.sizelint.check_self
+            && let Some(config_path) = &self.config_path
+            && let (Ok(config_path), Ok(root)) = (
+                std::fs::canonicalize(config_path),
+                std::fs::canonicalize(root),
+            )
+            && let Ok(rel) = config_path.strip_prefix(root)
+        {
+            excludes.push(globset::escape(&rel.to_string_lossy()));
+        }
+        Ok(FileDiscovery::new(root, &excludes)?
+            .with_git_layout(self.git_layout.clone())
+            .with_includes(&self.config.sizelint.includes)?
+            .with_diff_filter(diff_filter)
+            .with_commit_window(CommitWindow {
+                since: self.cli.get_since(),
+                until: self.cli.get_until(),
+            })
+            .with_untracked(self.cli.get_include_untracked())
+            .with_git_jobs(self.cli.get_git_jobs()))
     }
 
     fn discover_files_at(
@@ -244,21 +1014,30 @@ impl App {
         root: &std::path::Path,
         git_range: Option<&str>,
     ) -> Result<Vec<PathBuf>> {
-        let discovery = FileDiscovery::new(root, &self.config.sizelint.excludes)?;
+        let discovery = self.file_discovery(root)?;
 
         debug!("Discovering files...");
 
+        // CLI modes win over config ones; --git over config check_staged
+        let cli_range = git_range.filter(|_| !self.cli.get_git().is_empty());
         if self.cli.get_staged()
-            || (self.config.sizelint.check_staged && discovery.is_in_git_repo())
+            || (cli_range.is_none()
+                && self.config.sizelint.check_staged
+                && discovery.is_in_git_repo())
         {
+            self.print_discovery("staged", root, &discovery);
             print_progress("Checking staged files (git diff --staged)");
             discovery.discover_staged_files()
         } else if self.cli.get_working_tree()
-            || (self.config.sizelint.check_working_tree && discovery.is_in_git_repo())
+            || (cli_range.is_none()
+                && self.config.sizelint.check_working_tree
+                && discovery.is_in_git_repo())
         {
+            self.print_discovery("working-tree", root, &discovery);
             print_progress("Checking working tree files (git diff)");
             discovery.discover_working_tree_files()
         } else if let Some(range) = git_range {
+            self.print_discovery("diff", root, &discovery);
             let commit_count = discovery
                 .git_repo()
                 .map(|r| r.count_commits_in_range(range).unwrap_or(0))
@@ -269,11 +1048,27 @@ impl App {
             ));
             discovery.discover_git_diff_files(range)
         } else {
+            self.print_discovery("walk", root, &discovery);
             print_progress("Checking all files (directory walk)");
             discovery.discover_files(self.config.sizelint.respect_gitignore)
         }
     }
 
+    /// With `--print-discovery`, reports the discovery decision on stderr.
+    fn print_discovery(&self, mode: &str, root: &std::path::Path, discovery: &FileDiscovery) {
+        if self.cli.get_print_discovery() {
+            eprintln!(
+                "mode={mode} root={} git={}",
+                root.display(),
+                if discovery.is_in_git_repo() {
+                    "yes"
+                } else {
+                    "no"
+                }
+            );
+        }
+    }
+
     fn output_results(
         &self,
         violations: &[crate::rules::Violation],
@@ -282,31 +1077,112 @@ impl App {
         suggestions: &std::collections::HashMap<&str, &str>,
         descriptions: &std::collections::HashMap<&str, &str>,
     ) -> Result<ExitCode> {
-        let cwd =
-            std::env::current_dir().map_err(|e| SizelintError::CurrentDirectory { source: e })?;
-        let formatter = OutputFormatter::new(self.cli.get_format(), self.cli.get_quiet(), cwd);
+        self.output_results_with(
+            self.formatter()?,
+            violations,
+            file_count,
+            elapsed,
+            suggestions,
+            descriptions,
+        )
+    }
+
+    fn output_results_with(
+        &self,
+        formatter: OutputFormatter,
+        violations: &[crate::rules::Violation],
+        file_count: usize,
+        elapsed: std::time::Duration,
+        suggestions: &std::collections::HashMap<&str, &str>,
+        descriptions: &std::collections::HashMap<&str, &str>,
+    ) -> Result<ExitCode> {
+        finish_deferred_progress(!violations.is_empty());
+        let status = self.exit_status(violations);
+        let formatter = formatter.with_exit_status(status);
         formatter.output_results(violations, file_count, elapsed, suggestions, descriptions)?;
 
-        if !violations.is_empty() {
-            let has_errors = violations
-                .iter()
-                .any(|v| matches!(v.severity, crate::rules::Severity::Error));
+        if status.reason == ExitReason::Errors
+            && !self.cli.get_quiet()
+            && let Some(line) = formatter.largest_violation_line(violations)
+        {
+            eprintln!("{line}");
+        }
 
-            let fail_on_warn = self.cli.get_fail_on_warn() || self.config.sizelint.fail_on_warn;
-            let has_warnings = violations
-                .iter()
-                .any(|v| matches!(v.severity, crate::rules::Severity::Warning));
+        Ok(ExitCode::from(status.code))
+    }
 
-            if has_errors || (fail_on_warn && has_warnings) {
-                return Ok(ExitCode::FAILURE);
-            }
+    /// Fail on any error, and on warnings under `fail_on_warn` or from a
+    /// rule with `warn_is_error`.
+    fn exit_status(&self, violations: &[crate::rules::Violation]) -> ExitStatus {
+        let has_errors = violations
+            .iter()
+            .any(|v| matches!(v.severity, crate::rules::Severity::Error));
+
+        let fail_on_warn = self.cli.get_fail_on_warn() || self.config.sizelint.fail_on_warn;
+        let has_failing_warnings = violations.iter().any(|v| {
+            matches!(v.severity, crate::rules::Severity::Warning)
+                && (fail_on_warn || self.config.warn_is_error(&v.rule_name))
+        });
+
+        let (code, reason) = if has_errors {
+            (1, ExitReason::Errors)
+        } else if has_failing_warnings {
+            (1, ExitReason::Warnings)
+        } else {
+            (0, ExitReason::Clean)
+        };
+        ExitStatus {
+            code,
+            reason,
+            fail_on_warn,
         }
+    }
 
-        Ok(ExitCode::SUCCESS)
+    /// Print the violations found before an interruption, e.g. from a
+    /// Ctrl-C handler while a long history walk is still running.
+    pub fn print_partial_results(&self) -> Result<()> {
+        let violations = largest_per_path(self.partial.snapshot());
+        print_warning(&format!(
+            "Interrupted; showing {} violation(s) found so far",
+            violations.len()
+        ));
+        self.formatter()?.output_results(
+            &violations,
+            0,
+            std::time::Duration::ZERO,
+            &HashMap::new(),
+            &HashMap::new(),
+        )
     }
 
-    fn run_init(&self, force: bool, stdout: bool, edit: bool) -> Result<ExitCode> {
-        let default_config = Config::create_default_config();
+    fn formatter(&self) -> Result<OutputFormatter> {
+        let cwd =
+            std::env::current_dir().map_err(|e| SizelintError::CurrentDirectory { source: e })?;
+        Ok(
+            OutputFormatter::new(self.cli.get_format(), self.cli.get_quiet(), cwd)
+                .with_schema_version(self.cli.get_json_schema_version().unwrap_or(1))
+                .with_trailing_newline(self.config.sizelint.output_trailing_newline)
+                .with_explain(self.cli.get_explain())
+                .with_sibling_context(self.cli.get_diff_context())
+                .with_metadata(self.cli.get_with_metadata())
+                .with_quiet_on_success(self.cli.get_quiet_on_success())
+                .with_only_violations(self.cli.get_only_violations())
+                .with_dual_units(self.cli.get_dual_units()),
+        )
+    }
+
+    fn run_init(
+        &self,
+        force: bool,
+        stdout: bool,
+        edit: bool,
+        force_edit: bool,
+        preset: Option<&str>,
+    ) -> Result<ExitCode> {
+        let default_config = match preset {
+            Some(name) => Config::create_preset_config(name)?,
+            None => Config::create_default_config(),
+        };
 
         if stdout {
             println!("{default_config}");
@@ -318,7 +1194,7 @@ impl App {
         if config_file.exists() && !force {
             if edit {
                 print_progress(&format!("Opening existing {}", config_file.display()));
-                self.open_editor(&config_file)?;
+                self.open_editor(&config_file, force_edit)?;
                 return Ok(ExitCode::SUCCESS);
             } else {
                 print_error(
@@ -328,14 +1204,12 @@ impl App {
             }
         }
 
-        std::fs::write(&config_file, default_config).map_err(|e| {
-            SizelintError::filesystem("write config file".to_string(), config_file.clone(), e)
-        })?;
+        crate::fs::write_atomic(&config_file, default_config)?;
 
         print_success(&format!("Created {}", config_file.display()));
 
         if edit {
-            self.open_editor(&config_file)?;
+            self.open_editor(&config_file, force_edit)?;
         } else {
             println!(
                 "You can now customize the configuration and run 'sizelint check' to start linting."
@@ -345,9 +1219,22 @@ impl App {
         Ok(ExitCode::SUCCESS)
     }
 
-    fn open_editor(&self, file_path: &PathBuf) -> Result<()> {
+    fn open_editor(&self, file_path: &PathBuf, force_edit: bool) -> Result<()> {
+        use std::io::IsTerminal;
         use std::process::Command;
 
+        if let Some(reason) = editor_skip_reason(
+            std::io::stdin().is_terminal(),
+            std::env::var("SIZELINT_NO_EDITOR").ok().as_deref(),
+            force_edit,
+        ) {
+            print_warning(&format!(
+                "Not opening {} in an editor: {reason}",
+                file_path.display()
+            ));
+            return Ok(());
+        }
+
         let editor = std::env::var("VISUAL")
             .or_else(|_| std::env::var("EDITOR"))
             .unwrap_or_else(|_| "vi".to_string());
@@ -372,12 +1259,48 @@ impl App {
         Ok(())
     }
 
+    fn run_config(&self, action: ConfigAction) -> Result<ExitCode> {
+        match action {
+            ConfigAction::Validate { .. } => {
+                let errors = self.config.validate();
+                if errors.is_empty() {
+                    print_success("Configuration is valid");
+                    return Ok(ExitCode::SUCCESS);
+                }
+                eprintln!(
+                    "{:?}",
+                    miette::Report::new(SizelintError::config_errors(errors))
+                );
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
+
+    fn run_info(&self, json: bool) -> Result<ExitCode> {
+        let info = Cli::tool_info();
+        if json || self.cli.get_format() == OutputFormat::Json {
+            self.formatter()?.print_json(&info)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+
+        println!("sizelint {}", info.version);
+        println!("Output formats: {}", info.output_formats.join(", "));
+        println!("Shells: {}", info.shells.join(", "));
+        println!("Config files: {}", info.config_filenames.join(", "));
+        Ok(ExitCode::SUCCESS)
+    }
+
     fn run_rules(&self, action: RuleAction) -> Result<ExitCode> {
         match action {
-            RuleAction::List => {
+            RuleAction::List { .. } => {
                 let rule_engine = self.create_rule_engine()?;
                 let rule_info = rule_engine.get_all_rule_info(&self.config);
 
+                if self.cli.get_format() == OutputFormat::Json {
+                    self.formatter()?.print_json(&rule_info)?;
+                    return Ok(ExitCode::SUCCESS);
+                }
+
                 if rule_info.is_empty() {
                     println!("No rules configured or available.");
                     return Ok(ExitCode::SUCCESS);
@@ -388,9 +1311,9 @@ impl App {
 
                 for info in &rule_info {
                     let status = if info.enabled {
-                        "✓ enabled".green()
+                        format!("{} enabled", symbols().ok).green()
                     } else {
-                        "✗ disabled".red()
+                        format!("{} disabled", symbols().error).red()
                     };
 
                     println!("  {} - {} [{}]", info.name.bold(), info.description, status);
@@ -416,11 +1339,11 @@ impl App {
                     } else {
                         details.push("priority=default".to_string());
                     }
-                    if let Some(max_str) = &info.max_size_str {
-                        details.push(format!("max={max_str}"));
+                    if let Some(max_size) = &info.max_size {
+                        details.push(format!("max={max_size}"));
                     }
-                    if let Some(warn_str) = &info.warn_size_str {
-                        details.push(format!("warn={warn_str}"));
+                    if let Some(warn_size) = &info.warn_size {
+                        details.push(format!("warn={warn_size}"));
                     }
                     if !info.includes.is_empty() {
                         details.push(format!("includes={}", info.includes.len()));
@@ -439,7 +1362,7 @@ impl App {
                         details.push("error_on_match=true".to_string());
                     }
 
-                    println!("  ✓ {}: {}", info.name, details.join(", "));
+                    println!("  {} {}: {}", symbols().ok, info.name, details.join(", "));
                 }
 
                 if disabled_count > 0 {
@@ -452,11 +1375,11 @@ impl App {
                         } else {
                             details.push("priority=default".to_string());
                         }
-                        if let Some(max_str) = &info.max_size_str {
-                            details.push(format!("max={max_str}"));
+                        if let Some(max_size) = &info.max_size {
+                            details.push(format!("max={max_size}"));
                         }
-                        if let Some(warn_str) = &info.warn_size_str {
-                            details.push(format!("warn={warn_str}"));
+                        if let Some(warn_size) = &info.warn_size {
+                            details.push(format!("warn={warn_size}"));
                         }
                         if !info.includes.is_empty() {
                             details.push(format!("includes={}", info.includes.len()));
@@ -475,25 +1398,35 @@ impl App {
                             details.push("error_on_match=true".to_string());
                         }
 
-                        println!("  ✗ {}: {}", info.name, details.join(", "));
+                        println!(
+                            "  {} {}: {}",
+                            symbols().error,
+                            info.name,
+                            details.join(", ")
+                        );
                     }
                 }
             }
-            RuleAction::Describe { rule } => {
+            RuleAction::Describe { rule, .. } => {
                 let rule_engine = self.create_rule_engine()?;
                 let rule_info = rule_engine.get_all_rule_info(&self.config);
+                let found = rule_info.iter().find(|r| r.name == rule);
 
-                if let Some(info) = rule_info.iter().find(|r| r.name == rule) {
+                if let Some(info) = found
+                    && self.cli.get_format() == OutputFormat::Json
+                {
+                    self.formatter()?.print_json(info)?;
+                } else if let Some(info) = found {
                     println!("{}", format!("Rule: {}", info.name).bold().blue());
-                    println!("{}", "━".repeat(50).blue());
+                    println!("{}", symbols().rule.repeat(50).blue());
                     println!();
                     println!("Description: {}", info.description);
                     println!(
                         "Status: {}",
                         if info.enabled {
-                            "✓ enabled".green()
+                            format!("{} enabled", symbols().ok).green()
                         } else {
-                            "✗ disabled".red()
+                            format!("{} disabled", symbols().error).red()
                         }
                     );
 
@@ -515,21 +1448,11 @@ impl App {
                     } else {
                         println!("  Priority: default (lowest)");
                     }
-                    if let Some(max_str) = &info.max_size_str {
-                        let bytes_info = if let Some(bytes) = info.max_size {
-                            format!(" ({bytes} bytes)")
-                        } else {
-                            String::new()
-                        };
-                        println!("  Max size: {max_str}{bytes_info}");
+                    if let Some(max_size) = &info.max_size {
+                        println!("  Max size: {max_size} ({} bytes)", max_size.bytes());
                     }
-                    if let Some(warn_str) = &info.warn_size_str {
-                        let bytes_info = if let Some(bytes) = info.warn_size {
-                            format!(" ({bytes} bytes)")
-                        } else {
-                            String::new()
-                        };
-                        println!("  Warning size: {warn_str}{bytes_info}");
+                    if let Some(warn_size) = &info.warn_size {
+                        println!("  Warning size: {warn_size} ({} bytes)", warn_size.bytes());
                     }
                     if !info.includes.is_empty() {
                         println!("  Includes: {:?}", info.includes);
@@ -556,22 +1479,116 @@ impl App {
                     return Ok(ExitCode::FAILURE);
                 }
             }
+            RuleAction::Add {
+                name,
+                max,
+                warn,
+                include,
+                exclude,
+                priority,
+                description,
+            } => {
+                let definition = crate::config::RuleDefinition {
+                    enabled: true,
+                    description: description.clone().unwrap_or_default(),
+                    priority: priority.unwrap_or(100),
+                    max_size: max.as_deref().map(str::parse).transpose()?,
+                    warn_size: warn.as_deref().map(str::parse).transpose()?,
+                    includes: include.clone(),
+                    excludes: exclude.clone(),
+                    ..Default::default()
+                };
+                // Reject bad globs before touching the file
+                ConfigurableRule::new(name.clone(), definition)?;
+
+                let mut rule = toml::Table::new();
+                if let Some(description) = description {
+                    rule.insert("description".to_string(), description.into());
+                }
+                if let Some(priority) = priority {
+                    rule.insert("priority".to_string(), i64::from(priority).into());
+                }
+                if let Some(max) = max {
+                    rule.insert("max_size".to_string(), max.into());
+                }
+                if let Some(warn) = warn {
+                    rule.insert("warn_size".to_string(), warn.into());
+                }
+                if !include.is_empty() {
+                    rule.insert("includes".to_string(), include.into());
+                }
+                if !exclude.is_empty() {
+                    rule.insert("excludes".to_string(), exclude.into());
+                }
+                return self.append_rule(&name, rule);
+            }
         }
 
         Ok(ExitCode::SUCCESS)
     }
 
-    fn create_rule_engine(&self) -> Result<RuleEngine> {
-        let mut engine = RuleEngine::new();
+    /// Append `[rules.<name>]` with the keys of `rule` to the active config
+    /// file, leaving its existing content and comments untouched.
+    fn append_rule(&self, name: &str, rule: toml::Table) -> Result<ExitCode> {
+        let Some(config_path) = &self.config_path else {
+            print_error("No configuration file found. Run 'sizelint init' to create one.");
+            return Ok(ExitCode::FAILURE);
+        };
+        if self
+            .config
+            .rules
+            .as_ref()
+            .is_some_and(|rules| rules.rules.contains_key(name))
+        {
+            print_error(&format!(
+                "Rule '{name}' already exists in {}",
+                config_path.display()
+            ));
+            return Ok(ExitCode::FAILURE);
+        }
+
+        let mut rules = toml::Table::new();
+        rules.insert(name.to_string(), rule.into());
+        let mut table = toml::Table::new();
+        table.insert("rules".to_string(), rules.into());
 
-        // Always add a default rule that catches all files not matched by specific rules
-        self.add_default_rule(&mut engine)?;
+        let mut content = std::fs::read_to_string(config_path)
+            .map_err(|e| SizelintError::config_read(config_path.clone(), e))?;
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        content.push_str(&toml::to_string(&table).map_err(|e| {
+            SizelintError::config_invalid("rules".to_string(), name.to_string(), e.to_string())
+        })?);
+        crate::fs::write_atomic(config_path, content)?;
+
+        print_success(&format!("Added rule '{name}' to {}", config_path.display()));
+        Ok(ExitCode::SUCCESS)
+    }
+
+    fn create_rule_engine(&self) -> Result<RuleEngine> {
+        let mut engine = RuleEngine::new()
+            .with_rule_overlap_warnings(self.config.sizelint.warn_on_rule_overlap)
+            .with_binary_extensions(self.config.sizelint.binary_extensions.clone())
+            .with_normalize_crlf(
+                self.cli.get_normalize_line_endings() || self.config.sizelint.normalize_crlf,
+            )
+            .with_partial_results(self.partial.clone())
+            .with_symlink_report(self.config.sizelint.symlink_report);
+
+        // Always add a default rule that catches all files not matched by
+        // specific rules, unless a `--group` leaves it out
+        let groups = self.cli.get_groups();
+        self.add_default_rule(&mut engine, &groups)?;
 
         // Add any specific rules from configuration
         if let Some(rules_config) = &self.config.rules {
             let enabled_rules = rules_config.get_enabled_rules();
             for (rule_name, rule_def) in enabled_rules {
-                if rule_name == "default" {
+                if rule_name == "default" || !rule_def.in_groups(&groups) {
                     continue;
                 }
                 let mut rule_definition = rule_def.clone();
@@ -583,7 +1600,9 @@ impl App {
                     rule_definition.warn_size = self.config.sizelint.warn_file_size.clone();
                 }
 
-                let rule = ConfigurableRule::new(rule_name.clone(), rule_definition)?;
+                let rule = ConfigurableRule::new(rule_name.clone(), rule_definition)?
+                    .with_boundary(self.config.sizelint.boundary)
+                    .with_error_grace(self.config.sizelint.error_grace_percent);
                 engine.add_rule(rule);
             }
         }
@@ -591,7 +1610,7 @@ impl App {
         Ok(engine)
     }
 
-    fn add_default_rule(&self, engine: &mut RuleEngine) -> Result<()> {
+    fn add_default_rule(&self, engine: &mut RuleEngine, groups: &[String]) -> Result<()> {
         let config_def = self
             .config
             .rules
@@ -606,14 +1625,301 @@ impl App {
             priority: 1000,
             max_size: self.config.sizelint.max_file_size.clone(),
             warn_size: self.config.sizelint.warn_file_size.clone(),
-            includes: vec![],
-            excludes: vec![],
+            // Global patterns are relative to the checked root, as in discovery
+            includes: self.config.sizelint.includes.clone(),
+            excludes: self.config.sizelint.default_rule_excludes.clone(),
+            match_mode: crate::config::MatchMode::Path,
             suggestion: config_def.and_then(|d| d.suggestion.clone()),
+            tags: config_def.map(|d| d.tags.clone()).unwrap_or_default(),
             ..Default::default()
         };
+        if !default_rule.in_groups(groups) {
+            return Ok(());
+        }
 
-        let rule = ConfigurableRule::new("default".to_string(), default_rule)?;
+        let rule = ConfigurableRule::new("default".to_string(), default_rule)?
+            .with_boundary(self.config.sizelint.boundary)
+            .with_error_grace(self.config.sizelint.error_grace_percent);
         engine.add_rule(rule);
         Ok(())
     }
 }
+
+/// Why `init --edit` should not launch an editor, if it should not, so that
+/// it cannot hang a CI job. `SIZELINT_NO_EDITOR` wins even over `--force-edit`.
+fn editor_skip_reason(
+    stdin_is_terminal: bool,
+    no_editor: Option<&str>,
+    force_edit: bool,
+) -> Option<&'static str> {
+    if no_editor.is_some_and(|v| !v.is_empty() && v != "0") {
+        Some("SIZELINT_NO_EDITOR is set")
+    } else if !stdin_is_terminal && !force_edit {
+        Some("stdin is not a terminal (use --force-edit to open it anyway)")
+    } else {
+        None
+    }
+}
+
+/// Pick `n` of `files` at random (all of them if there are fewer), the same
+/// ones for the same `seed` regardless of discovery order. Sorted by path.
+fn sample_files(mut files: Vec<PathBuf>, n: usize, seed: u64) -> Vec<PathBuf> {
+    files.sort();
+    if n >= files.len() {
+        return files;
+    }
+
+    // splitmix64, plenty for picking files
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    };
+    // Partial Fisher-Yates: the first n slots end up a uniform sample
+    for i in 0..n {
+        let j = i + (next() % (files.len() - i) as u64) as usize;
+        files.swap(i, j);
+    }
+    files.truncate(n);
+    files.sort();
+    files
+}
+
+fn random_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// Check the blobs of `range`, or of all history without one,
+/// `batch_commits` commits at a time, keeping the largest violation per
+/// path across batches. Returns the violations and the number of blobs.
+fn check_history_in_batches(
+    discovery: &FileDiscovery,
+    range: Option<&str>,
+    rule_engine: &RuleEngine,
+    batch_commits: usize,
+    mut revisions: Option<&mut SizeRevisions>,
+) -> Result<(Vec<Violation>, usize)> {
+    let mut violations = Vec::new();
+    let mut blob_count = 0;
+    discovery.discover_history_blob_batches(range, batch_commits, |blobs| {
+        blob_count += blobs.len();
+        if let Some(revisions) = revisions.as_deref_mut() {
+            revisions.record(&blobs);
+        }
+        let batch = rule_engine.check_history_blobs(&blobs)?;
+        // Earlier batches come first, so equal sizes keep the blob walked first
+        violations = largest_per_path(violations.drain(..).chain(batch).collect());
+        Ok(())
+    })?;
+    Ok((violations, blob_count))
+}
+
+/// Keep only the largest violation per path; earlier entries win ties.
+fn largest_per_path(violations: Vec<Violation>) -> Vec<Violation> {
+    let mut index: HashMap<PathBuf, usize> = HashMap::new();
+    let mut best: Vec<Violation> = Vec::new();
+    for v in violations {
+        match index.get(&v.path) {
+            Some(&i) if v.sort_key > best[i].sort_key => best[i] = v,
+            Some(_) => {}
+            None => {
+                index.insert(v.path.clone(), best.len());
+                best.push(v);
+            }
+        }
+    }
+    best
+}
+
+/// Drop violations hidden by a suppression that is still active on `today`.
+/// Returns the remaining violations and the suppressions that have expired.
+fn apply_suppressions<'a>(
+    violations: Vec<Violation>,
+    suppressions: &'a [Suppression],
+    root: &std::path::Path,
+    today: Date,
+) -> Result<(Vec<Violation>, Vec<&'a Suppression>)> {
+    let mut active = Vec::new();
+    let mut expired = Vec::new();
+    for suppression in suppressions {
+        if suppression.is_expired(today)? {
+            expired.push(suppression);
+        } else {
+            let glob = globset::Glob::new(&suppression.path_glob)
+                .map_err(|e| {
+                    SizelintError::config_invalid_pattern(suppression.path_glob.clone(), e)
+                })?
+                .compile_matcher();
+            active.push((suppression, glob));
+        }
+    }
+
+    let kept = violations
+        .into_iter()
+        .filter(|v| {
+            let rel = v.path.strip_prefix(root).unwrap_or(&v.path);
+            !active
+                .iter()
+                .any(|(s, glob)| s.rule == v.rule_name && glob.is_match(rel))
+        })
+        .collect();
+    Ok((kept, expired))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Severity;
+
+    #[test]
+    fn test_batched_history_matches_single_pass() {
+        let tmp = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(args)
+                .current_dir(tmp.path())
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {args:?}");
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.email", "test@test.com"]);
+        git(&["config", "user.name", "Test"]);
+        // model.bin peaks in the middle of history; data.bin ties twice
+        for (i, (model, data)) in [(2048, 1500), (8192, 1500), (4096, 1500), (512, 3000)]
+            .into_iter()
+            .enumerate()
+        {
+            std::fs::write(tmp.path().join("model.bin"), vec![i as u8; model]).unwrap();
+            std::fs::write(tmp.path().join("data.bin"), vec![i as u8; data]).unwrap();
+            git(&["add", "."]);
+            git(&["commit", "-q", "-m", &format!("commit {i}")]);
+        }
+
+        let discovery = FileDiscovery::new(tmp.path(), &[]).unwrap();
+        let mut engine = RuleEngine::new();
+        engine.add_rule(
+            ConfigurableRule::new(
+                "default".to_string(),
+                crate::config::RuleDefinition {
+                    enabled: true,
+                    max_size: Some("1KB".parse().unwrap()),
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+        );
+        let summarize = |violations: Vec<Violation>| {
+            let mut rows: Vec<_> = violations
+                .into_iter()
+                .map(|v| (v.path, v.sort_key, v.commit))
+                .collect();
+            rows.sort();
+            rows
+        };
+
+        let single_pass = engine
+            .check_history_blobs(&discovery.discover_all_history_blobs().unwrap())
+            .unwrap();
+        let (batched, blob_count) =
+            check_history_in_batches(&discovery, None, &engine, 1, None).unwrap();
+        let (whole, _) =
+            check_history_in_batches(&discovery, None, &engine, usize::MAX, None).unwrap();
+
+        assert_eq!(blob_count, 8);
+        assert_eq!(summarize(batched.clone()), summarize(single_pass));
+        assert_eq!(summarize(batched), summarize(whole));
+    }
+
+    #[test]
+    fn test_exit_status_reports_failing_warnings() {
+        use clap::Parser;
+
+        let warning = Violation::new(
+            PathBuf::from("big.bin"),
+            "default".to_string(),
+            "File exceeds warning threshold 1.0 KB".to_string(),
+            Severity::Warning,
+        );
+        let exit_status = |args: &[&str]| {
+            let app = App::new(Cli::parse_from(args)).unwrap();
+            serde_json::to_value(app.exit_status(std::slice::from_ref(&warning))).unwrap()
+        };
+
+        assert_eq!(
+            exit_status(&["sizelint", "--no-global-config", "check", "--fail-on-warn"]),
+            serde_json::json!({"code": 1, "reason": "warnings"})
+        );
+        assert_eq!(
+            exit_status(&["sizelint", "--no-global-config", "check"]),
+            serde_json::json!({"code": 0, "reason": "clean"})
+        );
+    }
+
+    #[test]
+    fn test_editor_skipped_when_not_interactive() {
+        assert!(editor_skip_reason(false, None, false).is_some());
+        assert_eq!(editor_skip_reason(false, None, true), None);
+        assert_eq!(editor_skip_reason(true, None, false), None);
+        assert_eq!(editor_skip_reason(true, Some("0"), false), None);
+        assert_eq!(
+            editor_skip_reason(true, Some("1"), true),
+            Some("SIZELINT_NO_EDITOR is set")
+        );
+    }
+
+    #[test]
+    fn test_sample_files_is_deterministic() {
+        let files: Vec<PathBuf> = (0..100)
+            .map(|i| PathBuf::from(format!("file{i}.bin")))
+            .collect();
+        let mut shuffled = files.clone();
+        shuffled.reverse();
+
+        let sample = sample_files(files.clone(), 10, 42);
+        assert_eq!(sample.len(), 10);
+        assert_eq!(sample, sample_files(shuffled, 10, 42));
+        assert_ne!(sample, sample_files(files.clone(), 10, 43));
+
+        assert_eq!(
+            sample_files(files[..5].to_vec(), 10, 42),
+            files[..5].to_vec()
+        );
+    }
+
+    #[test]
+    fn test_suppressions_expire() {
+        let root = PathBuf::from("/repo");
+        let violation = || {
+            vec![Violation::new(
+                root.join("assets/video.mp4"),
+                "default".to_string(),
+                "File exceeds maximum allowed size 2.0 MB".to_string(),
+                Severity::Error,
+            )]
+        };
+        let suppressions = vec![Suppression {
+            rule: "default".to_string(),
+            path_glob: "assets/*.mp4".to_string(),
+            until: "2025-12-31".to_string(),
+            reason: "moving to LFS".to_string(),
+        }];
+
+        let (kept, expired) =
+            apply_suppressions(violation(), &suppressions, &root, (2025, 12, 31)).unwrap();
+        assert!(kept.is_empty());
+        assert!(expired.is_empty());
+
+        let (kept, expired) =
+            apply_suppressions(violation(), &suppressions, &root, (2026, 1, 1)).unwrap();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(expired.len(), 1);
+    }
+}
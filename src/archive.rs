@@ -0,0 +1,181 @@
+use crate::error::{Result, SizelintError};
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Tar,
+    TarGz,
+}
+
+impl ArchiveKind {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+/// Path and uncompressed size of each file in the zip or tar archive at
+/// `path`, read from its index or headers without extracting anything.
+pub fn members(path: &Path) -> Result<Vec<(String, u64)>> {
+    let is_zip = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+    let kind = ArchiveKind::from_path(path);
+    if !is_zip && kind.is_none() {
+        return Err(SizelintError::config_invalid(
+            "in_archive".to_string(),
+            path.display().to_string(),
+            "a .zip, .tar, .tar.gz or .tgz archive".to_string(),
+        ));
+    }
+
+    let file = File::open(path).map_err(|e| {
+        SizelintError::filesystem("open archive".to_string(), path.to_path_buf(), e)
+    })?;
+
+    match kind {
+        None => zip_members(file),
+        Some(ArchiveKind::Tar) => tar_members(file),
+        Some(ArchiveKind::TarGz) => tar_members(GzDecoder::new(file)),
+    }
+    .map_err(|e| SizelintError::archive_read(path.to_path_buf(), e))
+}
+
+fn zip_members(file: File) -> std::io::Result<Vec<(String, u64)>> {
+    let mut archive = zip::ZipArchive::new(file)?;
+    let mut members = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let member = archive.by_index_raw(i)?;
+        if member.is_file() {
+            members.push((member.name()?.to_string(), member.size()));
+        }
+    }
+    Ok(members)
+}
+
+fn tar_members<R: Read>(reader: R) -> std::io::Result<Vec<(String, u64)>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut members = Vec::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if entry.header().entry_type().is_file() {
+            let name = entry.path()?.to_string_lossy().to_string();
+            members.push((name, entry.header().size()?));
+        }
+    }
+    Ok(members)
+}
+
+/// Total size of all members once extracted, or `None` if `path` is not a
+/// supported archive or not a regular file.
+pub fn extracted_size(path: &Path) -> Result<Option<u64>> {
+    let Some(kind) = ArchiveKind::from_path(path) else {
+        return Ok(None);
+    };
+    if !crate::fs::is_regular_file(path) {
+        return Ok(None);
+    }
+
+    let file = File::open(path).map_err(|e| {
+        SizelintError::filesystem("open archive".to_string(), path.to_path_buf(), e)
+    })?;
+
+    let total = match kind {
+        ArchiveKind::Tar => sum_members(file),
+        ArchiveKind::TarGz => sum_members(GzDecoder::new(file)),
+    }
+    .map_err(|e| SizelintError::archive_read(path.to_path_buf(), e))?;
+
+    Ok(Some(total))
+}
+
+fn sum_members<R: Read>(reader: R) -> std::io::Result<u64> {
+    let mut archive = tar::Archive::new(reader);
+    let mut total: u64 = 0;
+    for entry in archive.entries()? {
+        total = total.saturating_add(entry?.header().size()?);
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+
+    fn write_tar_gz(path: &Path, members: &[(&str, usize)]) {
+        let encoder = GzEncoder::new(File::create(path).unwrap(), Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (name, size) in members {
+            let data = vec![0u8; *size];
+            let mut header = tar::Header::new_gnu();
+            header.set_size(*size as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, data.as_slice())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn test_extracted_size_of_tar_gz() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("fixture.tar.gz");
+        write_tar_gz(&path, &[("a.bin", 10_000), ("dir/b.bin", 2_500)]);
+
+        assert_eq!(extracted_size(&path).unwrap(), Some(12_500));
+        // Zeros compress well, so the archive itself is much smaller
+        assert!(std::fs::metadata(&path).unwrap().len() < 12_500);
+    }
+
+    #[test]
+    fn test_extracted_size_ignores_non_archives() {
+        assert_eq!(ArchiveKind::from_path(Path::new("data.zip")), None);
+        assert_eq!(
+            ArchiveKind::from_path(Path::new("a/fixture.TGZ")),
+            Some(ArchiveKind::TarGz)
+        );
+        assert_eq!(extracted_size(Path::new("README.md")).unwrap(), None);
+    }
+
+    #[test]
+    fn test_members_of_tar_gz() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("release.tgz");
+        write_tar_gz(&path, &[("a.bin", 10_000), ("dir/b.bin", 2_500)]);
+
+        assert_eq!(
+            members(&path).unwrap(),
+            vec![
+                ("a.bin".to_string(), 10_000),
+                ("dir/b.bin".to_string(), 2_500)
+            ]
+        );
+        assert!(matches!(
+            members(Path::new("README.md")),
+            Err(SizelintError::ConfigInvalid { .. })
+        ));
+    }
+
+    #[test]
+    fn test_corrupt_archive_errors() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("broken.tar.gz");
+        std::fs::write(&path, b"definitely not gzip").unwrap();
+
+        let err = extracted_size(&path).unwrap_err();
+        assert!(matches!(err, SizelintError::ArchiveRead { .. }));
+    }
+}
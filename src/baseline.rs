@@ -0,0 +1,65 @@
+use crate::error::{Result, SizelintError};
+use crate::rules::Violation;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Where `--baseline-auto` keeps its baseline, relative to the check root.
+pub const DEFAULT_BASELINE: &str = ".sizelint-baseline.json";
+
+/// A known violation: the file it was found in and the rule it broke.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub path: String,
+    pub rule: String,
+}
+
+/// One entry per distinct violation, sorted. Paths are recorded relative to
+/// `base` so the baseline can be committed.
+pub fn build_baseline(violations: &[Violation], base: &Path) -> Vec<BaselineEntry> {
+    let mut entries: Vec<_> = violations
+        .iter()
+        .map(|v| entry_for(v, base))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    entries.sort_by(|a, b| (&a.path, &a.rule).cmp(&(&b.path, &b.rule)));
+    entries
+}
+
+pub fn read_baseline(path: &Path) -> Result<Vec<BaselineEntry>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| SizelintError::filesystem("read".to_string(), path.to_path_buf(), e))?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+pub fn write_baseline(path: &Path, entries: &[BaselineEntry]) -> Result<()> {
+    let mut json = serde_json::to_string_pretty(entries)?;
+    json.push('\n');
+    crate::fs::write_atomic(path, json)
+}
+
+/// The violations in `violations` that are not recorded in `baseline`.
+pub fn new_violations(
+    violations: Vec<Violation>,
+    baseline: &[BaselineEntry],
+    base: &Path,
+) -> Vec<Violation> {
+    let known: HashSet<&BaselineEntry> = baseline.iter().collect();
+    violations
+        .into_iter()
+        .filter(|v| !known.contains(&entry_for(v, base)))
+        .collect()
+}
+
+fn entry_for(violation: &Violation, base: &Path) -> BaselineEntry {
+    BaselineEntry {
+        path: violation
+            .path
+            .strip_prefix(base)
+            .unwrap_or(&violation.path)
+            .display()
+            .to_string(),
+        rule: violation.rule_name.clone(),
+    }
+}
@@ -1,5 +1,6 @@
 use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use clap_complete::{Shell, generate};
+use serde::Serialize;
 use std::io;
 use std::path::PathBuf;
 
@@ -26,11 +27,45 @@ pub struct Cli {
     #[arg(short, long, value_name = "FILE")]
     pub config: Option<PathBuf>,
 
+    /// Ignore the per-user config in $XDG_CONFIG_HOME/sizelint/config.toml
+    #[arg(long)]
+    pub no_global_config: bool,
+
+    /// Ignore unknown config keys instead of failing, e.g. keys of a newer sizelint
+    #[arg(long)]
+    pub lenient: bool,
+
+    /// Print which config layers set a dotted config KEY, and its final value
+    #[arg(long, value_name = "KEY")]
+    pub trace_config: Option<String>,
+
     /// Enable debug output (or set SIZELINT_LOG for fine-grained control)
     #[arg(long)]
     pub debug: bool,
+
+    /// Increase log verbosity (-v for debug, -vv for a per-file rule trace)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Output format for all subcommands (subcommand --format takes precedence)
+    #[arg(short = 'f', long)]
+    pub format: Option<OutputFormat>,
+
+    /// Use ASCII instead of Unicode symbols (e.g. `[OK]` for `✓`)
+    #[arg(long)]
+    pub ascii: bool,
+
+    /// Git directory for all git commands, e.g. of a bare repository (sets GIT_DIR)
+    #[arg(long, value_name = "PATH")]
+    pub git_dir: Option<PathBuf>,
+
+    /// Work tree for all git commands (sets GIT_WORK_TREE)
+    #[arg(long, value_name = "PATH")]
+    pub work_tree: Option<PathBuf>,
 }
 
+// Parsed once per run, so the size of `Check` does not matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug, Clone)]
 pub enum Commands {
     /// Check files for size violations
@@ -43,9 +78,9 @@ pub enum Commands {
         #[arg(short, long, value_name = "FILE")]
         config: Option<PathBuf>,
 
-        /// Output format
-        #[arg(short = 'f', long, default_value = "human")]
-        format: OutputFormat,
+        /// Output format [default: human]
+        #[arg(short = 'f', long)]
+        format: Option<OutputFormat>,
 
         /// Check only staged files (git diff --staged)
         #[arg(long)]
@@ -55,21 +90,190 @@ pub enum Commands {
         #[arg(long, conflicts_with = "git")]
         working_tree: bool,
 
-        /// Check files changed in a git revision range (e.g. "main", "main..HEAD", "main...feature")
+        /// Also check untracked files in working tree mode
+        #[arg(long)]
+        include_untracked: bool,
+
+        /// Check files changed in a git revision range (e.g. "main", "main..HEAD", "main...feature");
+        /// repeat to check several ranges
         #[arg(long, value_name = "RANGE", conflicts_with_all = ["staged", "working_tree"])]
-        git: Option<String>,
+        git: Vec<String>,
+
+        /// Check blob sizes in the tree at REF without checking it out
+        #[arg(
+            long = "ref",
+            value_name = "REF",
+            conflicts_with_all = ["staged", "working_tree", "git"]
+        )]
+        git_ref: Option<String>,
+
+        /// Check exactly the files listed in FILE (one path per line, or a JSON list), failing on missing ones
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with_all = ["staged", "working_tree", "git", "git_ref"]
+        )]
+        from_manifest: Option<PathBuf>,
+
+        /// Check the members of a zip or tar archive instead of files on disk, without extracting it
+        #[arg(
+            long,
+            value_name = "ARCHIVE",
+            conflicts_with_all = ["staged", "working_tree", "git", "git_ref", "from_manifest"]
+        )]
+        in_archive: Option<PathBuf>,
+
+        /// Check the blobs added or modified by exactly these commits (comma-separated)
+        #[arg(
+            long,
+            value_name = "SHA",
+            value_delimiter = ',',
+            conflicts_with_all = ["staged", "working_tree", "git", "git_ref", "from_manifest", "in_archive"]
+        )]
+        commits: Vec<String>,
+
+        /// Only run rules tagged with one of these groups (comma-separated)
+        #[arg(long, value_name = "NAME", value_delimiter = ',')]
+        group: Vec<String>,
+
+        /// Only scan history commits made on or after DATE (any date git accepts)
+        #[arg(long, value_name = "DATE")]
+        since: Option<String>,
+
+        /// Only scan history commits made on or before DATE (any date git accepts)
+        #[arg(long, value_name = "DATE")]
+        until: Option<String>,
 
         /// Skip git history scanning for deleted blobs (only check files at HEAD)
         #[arg(long, requires = "git")]
         no_history: bool,
 
+        /// Only check files added in the diff, ignoring modifications
+        #[arg(long)]
+        added_only: bool,
+
+        /// Print the total size of tracked files per top-level directory and exit
+        #[arg(long)]
+        dir_sizes: bool,
+
+        /// Print the total size of the discovered files per extension and exit
+        #[arg(long)]
+        by_extension: bool,
+
+        /// Print the chosen discovery mode, root and whether a git repo was found to stderr
+        #[arg(long)]
+        print_discovery: bool,
+
+        /// Report the on-disk size of the .git directory and exit
+        #[arg(long)]
+        gitdir_size: bool,
+
         /// Quiet mode (only show violations)
         #[arg(short, long)]
         quiet: bool,
 
+        /// Print nothing, not even progress, unless there are violations
+        #[arg(long)]
+        quiet_on_success: bool,
+
+        /// Print only the violations, with no summary and nothing on success
+        #[arg(long)]
+        only_violations: bool,
+
+        /// Show sizes in human output in both binary and decimal units, e.g. "10.0 MiB (10.5 MB)"
+        #[arg(long)]
+        dual_units: bool,
+
+        /// Check only N randomly chosen files of those discovered
+        #[arg(long, value_name = "N")]
+        sample: Option<usize>,
+
+        /// Seed for --sample, to pick the same files again
+        #[arg(long, value_name = "SEED", requires = "sample")]
+        seed: Option<u64>,
+
+        /// Check at most N files at once, and run at most N git processes in history scans
+        #[arg(short = 'j', long, value_name = "N")]
+        jobs: Option<std::num::NonZeroUsize>,
+
+        /// Run at most N git processes in history scans, overriding --jobs (1 disables parallel git)
+        #[arg(long, value_name = "N")]
+        git_jobs: Option<std::num::NonZeroUsize>,
+
+        /// Run git processes in parallel in history scans; false runs one at a time
+        #[arg(long, value_name = "BOOL", default_value_t = true, action = clap::ArgAction::Set)]
+        parallel_git: bool,
+
+        /// Check only files modified within a duration (e.g. "1h") or since a date
+        #[arg(long, value_name = "WHEN")]
+        modified_since: Option<String>,
+
         /// Treat warnings as errors
         #[arg(long)]
         fail_on_warn: bool,
+
+        /// Measure text files as if CRLF line endings were LF
+        #[arg(long)]
+        normalize_line_endings: bool,
+
+        /// Stop checking after N violations have been found
+        #[arg(long, value_name = "N")]
+        max_violations: Option<usize>,
+
+        /// Exit at the first error, skipping the rest of the scan
+        #[arg(long)]
+        fast_fail: bool,
+
+        /// Append a remediation tip to each violation
+        #[arg(long)]
+        explain: bool,
+
+        /// List the sizes of up to 3 other files next to each violation
+        #[arg(long)]
+        diff_context: bool,
+
+        /// Add a unique run id and a timestamp to JSON output
+        #[arg(long)]
+        with_metadata: bool,
+
+        /// Layout of JSON output: 1 (flat) or 2 (adds by_rule and passed) [default: 1]
+        #[arg(long, value_name = "N")]
+        json_schema_version: Option<u32>,
+
+        /// List the N largest files that passed, with their size and rule [default: 10]
+        #[arg(
+            long,
+            value_name = "N",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "10"
+        )]
+        verbose_skips: Option<usize>,
+
+        /// Write the path and size of every checked file to FILE as JSON
+        #[arg(long, value_name = "FILE")]
+        manifest: Option<PathBuf>,
+
+        /// Include a SHA-256 content hash for each file in the manifest
+        #[arg(long, requires = "manifest")]
+        manifest_hash: bool,
+
+        /// Write a path,bytes CSV row for every discovered file to FILE
+        #[arg(long, value_name = "FILE")]
+        dump_sizes: Option<PathBuf>,
+
+        /// Record current violations in .sizelint-baseline.json on the first
+        /// run, then fail only on violations not recorded there
+        #[arg(long)]
+        baseline_auto: bool,
+
+        /// Report the rules that took longest to check files
+        #[arg(long)]
+        profile: bool,
+
+        /// Fail if any blob reachable from HEAD ever exceeded SIZE
+        #[arg(long, value_name = "SIZE")]
+        history_max: Option<String>,
     },
 
     /// Initialize sizelint configuration
@@ -84,6 +288,18 @@ pub enum Commands {
         /// Open configuration file in editor after creation
         #[arg(long)]
         edit: bool,
+        /// Open the editor even when stdin is not a terminal
+        #[arg(long, requires = "edit")]
+        force_edit: bool,
+        /// Start from a curated preset (web, data-science, mobile)
+        #[arg(long, value_name = "NAME")]
+        preset: Option<String>,
+    },
+
+    /// Configuration management
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
     },
 
     /// Rule management
@@ -98,24 +314,86 @@ pub enum Commands {
         /// Shell to generate completions for
         shell: String,
     },
+
+    /// Show the version, output formats, shells and config file names
+    Info {
+        /// Print the information as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[derive(Subcommand, Debug, Clone)]
 pub enum RuleAction {
     /// List available rules
     #[command(alias = "l")]
-    List,
+    List {
+        /// Output format
+        #[arg(short = 'f', long)]
+        format: Option<OutputFormat>,
+    },
     /// Show rule documentation
     #[command(alias = "d")]
-    Describe { rule: String },
+    Describe {
+        rule: String,
+        /// Output format
+        #[arg(short = 'f', long)]
+        format: Option<OutputFormat>,
+    },
+    /// Append a rule to the configuration file
+    Add {
+        /// Name of the new rule
+        name: String,
+        /// Maximum file size before error (e.g. "5MB")
+        #[arg(long, value_name = "SIZE")]
+        max: Option<String>,
+        /// File size warning threshold
+        #[arg(long, value_name = "SIZE")]
+        warn: Option<String>,
+        /// Glob of files the rule applies to (repeatable)
+        #[arg(long, value_name = "GLOB")]
+        include: Vec<String>,
+        /// Glob of files the rule skips (repeatable)
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+        /// Priority against other matching rules; higher wins
+        #[arg(long)]
+        priority: Option<i32>,
+        /// Description shown by `rules list`
+        #[arg(long)]
+        description: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigAction {
+    /// Load the configuration and check it for errors, without checking files
+    Validate {
+        /// Configuration file path
+        #[arg(short, long, value_name = "FILE")]
+        config: Option<PathBuf>,
+    },
 }
 
-#[derive(ValueEnum, Debug, Clone)]
+/// Output of `sizelint info`.
+#[derive(Debug, Serialize)]
+pub struct ToolInfo {
+    pub version: &'static str,
+    pub output_formats: Vec<String>,
+    pub shells: Vec<&'static str>,
+    pub config_filenames: Vec<&'static str>,
+}
+
+#[derive(ValueEnum, Debug, Clone, PartialEq)]
 pub enum OutputFormat {
     /// Human-readable output
     Human,
     /// JSON output
     Json,
+    /// One uncolored `path:severity:rule:bytes:message` line per violation
+    Compact,
+    /// Checkstyle XML, with the violations grouped by file
+    Checkstyle,
 }
 
 impl Cli {
@@ -123,11 +401,18 @@ impl Cli {
         self.command.clone()
     }
 
+    /// Subcommand `--format` wins over the global `--format`; human otherwise.
     pub fn get_format(&self) -> OutputFormat {
-        match &self.command {
+        let subcommand_format = match &self.command {
             Commands::Check { format, .. } => format.clone(),
-            _ => OutputFormat::Human,
-        }
+            Commands::Rules {
+                action: RuleAction::List { format } | RuleAction::Describe { format, .. },
+            } => format.clone(),
+            _ => None,
+        };
+        subcommand_format
+            .or_else(|| self.format.clone())
+            .unwrap_or(OutputFormat::Human)
     }
 
     pub fn get_quiet(&self) -> bool {
@@ -151,9 +436,30 @@ impl Cli {
         }
     }
 
-    pub fn get_git(&self) -> Option<String> {
+    pub fn get_git(&self) -> Vec<String> {
         match &self.command {
             Commands::Check { git, .. } => git.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn get_from_manifest(&self) -> Option<PathBuf> {
+        match &self.command {
+            Commands::Check { from_manifest, .. } => from_manifest.clone(),
+            _ => None,
+        }
+    }
+
+    pub fn get_since(&self) -> Option<String> {
+        match &self.command {
+            Commands::Check { since, .. } => since.clone(),
+            _ => None,
+        }
+    }
+
+    pub fn get_until(&self) -> Option<String> {
+        match &self.command {
+            Commands::Check { until, .. } => until.clone(),
             _ => None,
         }
     }
@@ -165,6 +471,36 @@ impl Cli {
         }
     }
 
+    pub fn get_added_only(&self) -> bool {
+        match &self.command {
+            Commands::Check { added_only, .. } => *added_only,
+            _ => false,
+        }
+    }
+
+    pub fn get_gitdir_size(&self) -> bool {
+        match &self.command {
+            Commands::Check { gitdir_size, .. } => *gitdir_size,
+            _ => false,
+        }
+    }
+
+    pub fn get_quiet_on_success(&self) -> bool {
+        match &self.command {
+            Commands::Check {
+                quiet_on_success, ..
+            } => *quiet_on_success,
+            _ => false,
+        }
+    }
+
+    pub fn get_dir_sizes(&self) -> bool {
+        match &self.command {
+            Commands::Check { dir_sizes, .. } => *dir_sizes,
+            _ => false,
+        }
+    }
+
     pub fn get_fail_on_warn(&self) -> bool {
         match &self.command {
             Commands::Check { fail_on_warn, .. } => *fail_on_warn,
@@ -172,9 +508,226 @@ impl Cli {
         }
     }
 
-    pub fn get_check_config(&self) -> Option<PathBuf> {
+    pub fn get_normalize_line_endings(&self) -> bool {
         match &self.command {
-            Commands::Check { config, .. } => config.clone(),
+            Commands::Check {
+                normalize_line_endings,
+                ..
+            } => *normalize_line_endings,
+            _ => false,
+        }
+    }
+
+    pub fn get_max_violations(&self) -> Option<usize> {
+        match &self.command {
+            Commands::Check { max_violations, .. } => *max_violations,
+            _ => None,
+        }
+    }
+
+    pub fn get_sample(&self) -> Option<usize> {
+        match &self.command {
+            Commands::Check { sample, .. } => *sample,
+            _ => None,
+        }
+    }
+
+    pub fn get_modified_since(&self) -> Option<String> {
+        match &self.command {
+            Commands::Check { modified_since, .. } => modified_since.clone(),
+            _ => None,
+        }
+    }
+
+    pub fn get_jobs(&self) -> Option<usize> {
+        match &self.command {
+            Commands::Check { jobs, .. } => jobs.map(usize::from),
+            _ => None,
+        }
+    }
+
+    /// Git processes for history scans: one with `--parallel-git false`,
+    /// else `--git-jobs`, else `--jobs`.
+    pub fn get_git_jobs(&self) -> Option<usize> {
+        match &self.command {
+            Commands::Check {
+                parallel_git: false,
+                ..
+            } => Some(1),
+            Commands::Check { git_jobs, .. } => git_jobs.map(usize::from).or(self.get_jobs()),
+            _ => None,
+        }
+    }
+
+    pub fn get_seed(&self) -> Option<u64> {
+        match &self.command {
+            Commands::Check { seed, .. } => *seed,
+            _ => None,
+        }
+    }
+
+    pub fn get_fast_fail(&self) -> bool {
+        match &self.command {
+            Commands::Check { fast_fail, .. } => *fast_fail,
+            _ => false,
+        }
+    }
+
+    pub fn get_history_max(&self) -> Option<String> {
+        match &self.command {
+            Commands::Check { history_max, .. } => history_max.clone(),
+            _ => None,
+        }
+    }
+
+    pub fn get_ref(&self) -> Option<String> {
+        match &self.command {
+            Commands::Check { git_ref, .. } => git_ref.clone(),
+            _ => None,
+        }
+    }
+
+    pub fn get_in_archive(&self) -> Option<PathBuf> {
+        match &self.command {
+            Commands::Check { in_archive, .. } => in_archive.clone(),
+            _ => None,
+        }
+    }
+
+    pub fn get_groups(&self) -> Vec<String> {
+        match &self.command {
+            Commands::Check { group, .. } => group.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn get_commits(&self) -> Vec<String> {
+        match &self.command {
+            Commands::Check { commits, .. } => commits.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn get_explain(&self) -> bool {
+        match &self.command {
+            Commands::Check { explain, .. } => *explain,
+            _ => false,
+        }
+    }
+
+    pub fn get_diff_context(&self) -> bool {
+        match &self.command {
+            Commands::Check { diff_context, .. } => *diff_context,
+            _ => false,
+        }
+    }
+
+    pub fn get_with_metadata(&self) -> bool {
+        match &self.command {
+            Commands::Check { with_metadata, .. } => *with_metadata,
+            _ => false,
+        }
+    }
+
+    pub fn get_json_schema_version(&self) -> Option<u32> {
+        match &self.command {
+            Commands::Check {
+                json_schema_version,
+                ..
+            } => *json_schema_version,
+            _ => None,
+        }
+    }
+
+    pub fn get_verbose_skips(&self) -> Option<usize> {
+        match &self.command {
+            Commands::Check { verbose_skips, .. } => *verbose_skips,
+            _ => None,
+        }
+    }
+
+    pub fn get_include_untracked(&self) -> bool {
+        match &self.command {
+            Commands::Check {
+                include_untracked, ..
+            } => *include_untracked,
+            _ => false,
+        }
+    }
+
+    pub fn get_manifest(&self) -> Option<PathBuf> {
+        match &self.command {
+            Commands::Check { manifest, .. } => manifest.clone(),
+            _ => None,
+        }
+    }
+
+    pub fn get_dump_sizes(&self) -> Option<PathBuf> {
+        match &self.command {
+            Commands::Check { dump_sizes, .. } => dump_sizes.clone(),
+            _ => None,
+        }
+    }
+
+    pub fn get_manifest_hash(&self) -> bool {
+        match &self.command {
+            Commands::Check { manifest_hash, .. } => *manifest_hash,
+            _ => false,
+        }
+    }
+
+    pub fn get_baseline_auto(&self) -> bool {
+        match &self.command {
+            Commands::Check { baseline_auto, .. } => *baseline_auto,
+            _ => false,
+        }
+    }
+
+    pub fn get_profile(&self) -> bool {
+        match &self.command {
+            Commands::Check { profile, .. } => *profile,
+            _ => false,
+        }
+    }
+
+    pub fn get_dual_units(&self) -> bool {
+        match &self.command {
+            Commands::Check { dual_units, .. } => *dual_units,
+            _ => false,
+        }
+    }
+
+    pub fn get_only_violations(&self) -> bool {
+        match &self.command {
+            Commands::Check {
+                only_violations, ..
+            } => *only_violations,
+            _ => false,
+        }
+    }
+
+    pub fn get_by_extension(&self) -> bool {
+        match &self.command {
+            Commands::Check { by_extension, .. } => *by_extension,
+            _ => false,
+        }
+    }
+
+    pub fn get_print_discovery(&self) -> bool {
+        match &self.command {
+            Commands::Check {
+                print_discovery, ..
+            } => *print_discovery,
+            _ => false,
+        }
+    }
+
+    pub fn get_subcommand_config(&self) -> Option<PathBuf> {
+        match &self.command {
+            Commands::Check { config, .. }
+            | Commands::Config {
+                action: ConfigAction::Validate { config },
+            } => config.clone(),
             _ => None,
         }
     }
@@ -195,6 +748,20 @@ impl Cli {
             })
     }
 
+    /// What this build supports, for wrappers that should not hardcode it.
+    pub fn tool_info() -> ToolInfo {
+        ToolInfo {
+            version: env!("CARGO_PKG_VERSION"),
+            output_formats: OutputFormat::value_variants()
+                .iter()
+                .filter_map(|format| format.to_possible_value())
+                .map(|value| value.get_name().to_string())
+                .collect(),
+            shells: SUPPORTED_SHELLS.iter().map(|(name, _)| *name).collect(),
+            config_filenames: crate::config::CONFIG_FILENAMES.to_vec(),
+        }
+    }
+
     pub fn generate_completion(shell_str: &str) -> std::result::Result<(), String> {
         let shell = Self::parse_shell(shell_str)?;
         let mut cmd = Self::command();
@@ -202,3 +769,72 @@ impl Cli {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_global_format_applies_to_rules_list() {
+        let cli = Cli::parse_from(["sizelint", "--format", "json", "rules", "list"]);
+        assert_eq!(cli.get_format(), OutputFormat::Json);
+
+        let cli = Cli::parse_from(["sizelint", "rules", "list"]);
+        assert_eq!(cli.get_format(), OutputFormat::Human);
+    }
+
+    #[test]
+    fn test_subcommand_format_overrides_global() {
+        let cli = Cli::parse_from(["sizelint", "--format", "json", "check", "--format", "human"]);
+        assert_eq!(cli.get_format(), OutputFormat::Human);
+
+        let cli = Cli::parse_from(["sizelint", "-f", "human", "rules", "list", "-f", "json"]);
+        assert_eq!(cli.get_format(), OutputFormat::Json);
+    }
+
+    #[test]
+    fn test_git_jobs_from_parallel_git_git_jobs_and_jobs() {
+        let git_jobs = |args: &[&str]| {
+            Cli::parse_from(["sizelint", "check"].iter().chain(args)).get_git_jobs()
+        };
+        assert_eq!(git_jobs(&[]), None);
+        assert_eq!(git_jobs(&["-j", "4"]), Some(4));
+        assert_eq!(git_jobs(&["-j", "4", "--git-jobs", "2"]), Some(2));
+        assert_eq!(
+            git_jobs(&["--git-jobs", "2", "--parallel-git", "false"]),
+            Some(1)
+        );
+        assert_eq!(git_jobs(&["-j", "4", "--parallel-git", "true"]), Some(4));
+    }
+
+    #[test]
+    fn test_verbose_skips_leaves_trailing_path_alone() {
+        let cli = Cli::parse_from(["sizelint", "check", "--verbose-skips", "src/"]);
+        assert_eq!(cli.get_verbose_skips(), Some(10));
+        let Commands::Check { paths, .. } = &cli.command else {
+            panic!("expected check");
+        };
+        assert_eq!(paths, &[PathBuf::from("src/")]);
+
+        let cli = Cli::parse_from(["sizelint", "check", "--verbose-skips=3", "src/"]);
+        assert_eq!(cli.get_verbose_skips(), Some(3));
+    }
+
+    #[test]
+    fn test_tool_info_lists_shells_and_formats() {
+        let json = serde_json::to_value(Cli::tool_info()).unwrap();
+        assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(
+            json["output_formats"],
+            serde_json::json!(["human", "json", "compact", "checkstyle"])
+        );
+        assert_eq!(
+            json["shells"],
+            serde_json::json!(["bash", "zsh", "fish", "powershell", "elvish"])
+        );
+        assert_eq!(
+            json["config_filenames"],
+            serde_json::json!(["sizelint.toml", ".sizelint.toml"])
+        );
+    }
+}
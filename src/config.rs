@@ -1,4 +1,5 @@
 use crate::error::{Result, SizelintError};
+use crate::rules::{SizeThreshold, parse_size_string, parse_size_with_quota};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -6,7 +7,80 @@ use tracing::{Level, debug, span};
 
 const DEFAULT_CONFIG_TOML: &str = include_str!("assets/config.toml");
 
-const CONFIG_FILENAMES: &[&str] = &["sizelint.toml", ".sizelint.toml"];
+/// Config file names searched for, in order, from the checked directory upward.
+pub const CONFIG_FILENAMES: &[&str] = &["sizelint.toml", ".sizelint.toml"];
+
+const PRESETS: &[(&str, &str)] = &[
+    ("web", include_str!("assets/presets/web.toml")),
+    (
+        "data-science",
+        include_str!("assets/presets/data-science.toml"),
+    ),
+    ("mobile", include_str!("assets/presets/mobile.toml")),
+];
+
+/// Top-level config keys, for reporting unknown ones.
+const SIZELINT_KEYS: &[&str] = &[
+    "max_file_size",
+    "warn_file_size",
+    "max_growth",
+    "max_tree_growth",
+    "max_size_revisions",
+    "max_gitdir_size",
+    "quota",
+    "includes",
+    "excludes",
+    "default_rule_excludes",
+    "exclude_common",
+    "check_self",
+    "check_staged",
+    "check_working_tree",
+    "git",
+    "respect_gitignore",
+    "fail_on_warn",
+    "output_trailing_newline",
+    "warn_on_rule_overlap",
+    "warn_unmatched_rules",
+    "use_gitattributes",
+    "use_sidecar_limits",
+    "binary_extensions",
+    "ascii",
+    "symlink_report",
+    "on_base_branch",
+    "boundary",
+    "normalize_crlf",
+    "error_grace_percent",
+    "suppress",
+    "ci_overrides",
+    "rules",
+];
+
+/// Keys of a `[rules.<name>]` table, for reporting unknown ones.
+const RULE_KEYS: &[&str] = &[
+    "enabled",
+    "description",
+    "priority",
+    "max_size",
+    "warn_size",
+    "includes",
+    "excludes",
+    "match_mode",
+    "warn_on_match",
+    "error_on_match",
+    "suggestion",
+    "error_message",
+    "warn_message",
+    "text_only",
+    "executable_only",
+    "inspect_archives",
+    "max_extracted_size",
+    "require_tracked",
+    "warn_is_error",
+    "require_extension",
+    "extensionless_min_size",
+    "scoped_limits",
+    "tags",
+];
 
 fn default_true() -> bool {
     true
@@ -22,15 +96,46 @@ pub struct Config {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SizelintConfig {
     /// Maximum file size (e.g., "10MB", "1GB")
-    pub max_file_size: Option<String>,
+    pub max_file_size: Option<SizeThreshold>,
 
     /// Warning threshold for file size
-    pub warn_file_size: Option<String>,
+    pub warn_file_size: Option<SizeThreshold>,
+
+    /// Maximum size increase of a file within a git range (e.g., "2MB")
+    pub max_growth: Option<SizeThreshold>,
+
+    /// Maximum increase of the total tracked size within a git range
+    pub max_tree_growth: Option<SizeThreshold>,
+
+    /// Maximum number of distinct sizes a file may be committed at in a range
+    pub max_size_revisions: Option<usize>,
+
+    /// Maximum on-disk size of the `.git` directory for `--gitdir-size`
+    pub max_gitdir_size: Option<SizeThreshold>,
+
+    /// Disk quota that bare percentage sizes (e.g., "1%") are relative to
+    pub quota: Option<SizeThreshold>,
+
+    /// Patterns to restrict checking to; empty checks every file
+    #[serde(default)]
+    pub includes: Vec<String>,
 
     /// Patterns to exclude from checking
     #[serde(default)]
     pub excludes: Vec<String>,
 
+    /// Patterns the default rule skips, leaving them to explicit rules
+    #[serde(default)]
+    pub default_rule_excludes: Vec<String>,
+
+    /// Also exclude common generated and vendored directories
+    #[serde(default)]
+    pub exclude_common: bool,
+
+    /// Check the config file in use like any other file
+    #[serde(default)]
+    pub check_self: bool,
+
     /// Check only staged files
     #[serde(default)]
     pub check_staged: bool,
@@ -50,6 +155,208 @@ pub struct SizelintConfig {
     /// Treat warnings as errors
     #[serde(default)]
     pub fail_on_warn: bool,
+
+    /// End machine-readable output with a trailing newline
+    #[serde(default = "default_true")]
+    pub output_trailing_newline: bool,
+
+    /// Warn when a file matches several rules and only one applies
+    #[serde(default)]
+    pub warn_on_rule_overlap: bool,
+
+    /// Warn about enabled rules that matched none of the checked files
+    #[serde(default)]
+    pub warn_unmatched_rules: bool,
+
+    /// Read per-path `sizelint-max` limits from .gitattributes
+    #[serde(default)]
+    pub use_gitattributes: bool,
+
+    /// Read per-file limits from sibling `<file>.sizelimit` files
+    #[serde(default)]
+    pub use_sidecar_limits: bool,
+
+    /// Extensions always treated as binary, without sniffing their content
+    #[serde(default)]
+    pub binary_extensions: Vec<String>,
+
+    /// Use ASCII instead of Unicode symbols in human output
+    #[serde(default)]
+    pub ascii: bool,
+
+    /// Path to report violations on symlinks under
+    #[serde(default)]
+    pub symlink_report: SymlinkReport,
+
+    /// What `git` does when HEAD is already at the base ref
+    #[serde(default)]
+    pub on_base_branch: OnBaseBranch,
+
+    /// Whether a file exactly at a rule's size limit passes
+    #[serde(default)]
+    pub boundary: Boundary,
+
+    /// Measure text files as if CRLF line endings were LF
+    #[serde(default)]
+    pub normalize_crlf: bool,
+
+    /// Percentage above `max_size` that is still only a warning
+    pub error_grace_percent: Option<f64>,
+
+    /// Temporary, expiring suppressions of known violations
+    #[serde(default)]
+    pub suppress: Vec<Suppression>,
+
+    /// Settings that replace the ones above when the `CI` variable is set
+    #[serde(default, skip_serializing_if = "toml::Table::is_empty")]
+    pub ci_overrides: toml::Table,
+}
+
+/// Which path a violation on a symlinked file is reported under.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymlinkReport {
+    /// The symlink itself
+    #[default]
+    Link,
+    /// The resolved target
+    Target,
+    /// The symlink, naming both paths in the message
+    Both,
+}
+
+/// Whether a size limit itself is within the limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Boundary {
+    /// A file exactly at the limit passes
+    #[default]
+    Inclusive,
+    /// A file exactly at the limit is a violation
+    Exclusive,
+}
+
+/// What a bare `git` ref resolves to when HEAD is already at it, e.g.
+/// `--git main` while on `main`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OnBaseBranch {
+    /// Check nothing, since no commits are ahead of the base
+    #[default]
+    Empty,
+    /// Check the last commit (`HEAD~1..HEAD`) instead
+    LastCommit,
+    /// Fail with an error
+    Error,
+}
+
+/// How a rule's `includes` and `excludes` patterns are matched.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    /// Patterns with a `/` match the path, others match the basename anywhere
+    #[default]
+    Auto,
+    /// Patterns match the root-relative path verbatim
+    Path,
+    /// Patterns match the file name only
+    Basename,
+}
+
+/// Hides violations of `rule` on paths matching `path_glob` until the
+/// `until` date (inclusive, `YYYY-MM-DD`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Suppression {
+    pub rule: String,
+    pub path_glob: String,
+    pub until: String,
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// The per-user config merged beneath every other config:
+/// `$XDG_CONFIG_HOME/sizelint/config.toml`, falling back to
+/// `$HOME/.config/sizelint/config.toml`.
+pub fn global_config_path(xdg_config_home: Option<&str>, home: Option<&str>) -> Option<PathBuf> {
+    let config_home = match xdg_config_home.filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => Path::new(home.filter(|dir| !dir.is_empty())?).join(".config"),
+    };
+    Some(config_home.join("sizelint").join("config.toml"))
+}
+
+/// Whether a `CI` environment variable value means we run in CI. Set and
+/// not empty, `0` or `false`, as most CI providers export `CI=true`.
+pub fn ci_enabled(value: Option<&str>) -> bool {
+    value.is_some_and(|v| !v.is_empty() && v != "0" && !v.eq_ignore_ascii_case("false"))
+}
+
+/// A calendar date as `(year, month, day)`, ordered chronologically.
+pub type Date = (i32, u32, u32);
+
+impl Suppression {
+    /// Whether the suppression no longer applies on `today`.
+    pub fn is_expired(&self, today: Date) -> Result<bool> {
+        Ok(parse_date(&self.until)? < today)
+    }
+}
+
+/// Parse a `YYYY-MM-DD` date.
+pub fn parse_date(value: &str) -> Result<Date> {
+    let invalid = || {
+        SizelintError::config_invalid(
+            "until".to_string(),
+            value.to_string(),
+            "a date like 2025-12-31".to_string(),
+        )
+    };
+    let mut parts = value.trim().splitn(3, '-');
+    let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    let date = (
+        year.parse().map_err(|_| invalid())?,
+        month.parse().map_err(|_| invalid())?,
+        day.parse().map_err(|_| invalid())?,
+    );
+    if !(1..=12).contains(&date.1) || !(1..=31).contains(&date.2) {
+        return Err(invalid());
+    }
+    Ok(date)
+}
+
+/// Today's date in UTC.
+pub fn today() -> Date {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    civil_from_days((secs / 86_400) as i64)
+}
+
+/// Convert a proleptic Gregorian date to days since 1970-01-01.
+pub fn days_from_civil((year, month, day): Date) -> i64 {
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (i64::from(month) + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Convert days since 1970-01-01 to a proleptic Gregorian date.
+fn civil_from_days(days: i64) -> Date {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = (yoe + era * 400 + i64::from(month <= 2)) as i32;
+    (year, month, day)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -73,12 +380,15 @@ pub struct RuleDefinition {
     pub priority: i32,
 
     // File size rule parameters
-    pub max_size: Option<String>,
-    pub warn_size: Option<String>,
+    pub max_size: Option<SizeThreshold>,
+    pub warn_size: Option<SizeThreshold>,
     #[serde(default)]
     pub includes: Vec<String>,
     #[serde(default)]
     pub excludes: Vec<String>,
+    // How includes/excludes are matched against a path
+    #[serde(default)]
+    pub match_mode: MatchMode,
 
     // Match-based violations
     #[serde(default)]
@@ -89,6 +399,62 @@ pub struct RuleDefinition {
     // Actionable suggestion shown to users when this rule triggers
     #[serde(default)]
     pub suggestion: Option<String>,
+
+    // Violation messages replacing the generated text, by severity
+    #[serde(default)]
+    pub error_message: Option<String>,
+    #[serde(default)]
+    pub warn_message: Option<String>,
+
+    // Only apply this rule to text files; binary files fall through to other rules
+    #[serde(default)]
+    pub text_only: bool,
+
+    // Only apply this rule to files with an execute bit set (Unix only)
+    #[serde(default)]
+    pub executable_only: bool,
+
+    // Check the extracted size of tar / tar.gz archives against max_extracted_size
+    #[serde(default)]
+    pub inspect_archives: bool,
+    pub max_extracted_size: Option<SizeThreshold>,
+
+    // Flag matching files that are not tracked by git (no-op outside a repo)
+    #[serde(default)]
+    pub require_tracked: bool,
+
+    // Fail the run on this rule's warnings, like fail_on_warn for one rule
+    #[serde(default)]
+    pub warn_is_error: bool,
+
+    // Flag matching files without an extension, from extensionless_min_size on
+    #[serde(default)]
+    pub require_extension: bool,
+    pub extensionless_min_size: Option<SizeThreshold>,
+
+    // Location-dependent max_size; the first scope matching the path wins
+    #[serde(default)]
+    pub scoped_limits: Vec<ScopedLimit>,
+
+    // Groups this rule belongs to, selected with `check --group`
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl RuleDefinition {
+    /// Whether the rule runs for the `--group` names in `groups`: every rule
+    /// runs without groups, otherwise only rules tagged with one of them.
+    pub fn in_groups(&self, groups: &[String]) -> bool {
+        groups.is_empty() || self.tags.iter().any(|tag| groups.contains(tag))
+    }
+}
+
+/// A `max` size for the files of a rule whose path, relative to the checked
+/// root, matches the `path` glob.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScopedLimit {
+    pub path: String,
+    pub max: SizeThreshold,
 }
 
 impl Default for Config {
@@ -98,77 +464,353 @@ impl Default for Config {
 }
 
 impl SizelintConfig {
-    fn merge_from(&mut self, other: SizelintConfig) {
-        if other.max_file_size.is_some() {
-            self.max_file_size = other.max_file_size;
-        }
-        if other.warn_file_size.is_some() {
-            self.warn_file_size = other.warn_file_size;
-        }
-        if !other.excludes.is_empty() {
-            self.excludes = other.excludes;
+    /// Smallest version of a file counted by `max_size_revisions`: the
+    /// warning size, else the maximum size, else every version.
+    pub fn revision_min_size(&self) -> u64 {
+        self.warn_file_size
+            .as_ref()
+            .or(self.max_file_size.as_ref())
+            .map_or(0, SizeThreshold::bytes)
+    }
+}
+
+impl RulesConfig {
+    /// Enabled rules, sorted by name for a stable evaluation order.
+    pub fn get_enabled_rules(&self) -> Vec<(&String, &RuleDefinition)> {
+        let mut rules: Vec<_> = self.rules.iter().filter(|(_, rule)| rule.enabled).collect();
+        rules.sort_by_key(|(name, _)| *name);
+        rules
+    }
+}
+
+/// Errors for the keys of `table`, a whole config file, that sizelint does
+/// not know, each suggesting the closest known key.
+fn unknown_keys(table: &toml::Table, path: &Path) -> Vec<SizelintError> {
+    let mut errors = Vec::new();
+    let mut check = |table: &toml::Table, prefix: &str, known: &[&str]| {
+        for key in table.keys().filter(|key| !known.contains(&key.as_str())) {
+            errors.push(SizelintError::config_unknown_key(
+                format!("{prefix}{key}"),
+                path.to_path_buf(),
+                closest_key(key, known),
+            ));
         }
-        self.check_staged = other.check_staged;
-        self.check_working_tree = other.check_working_tree;
-        if other.git.is_some() {
-            self.git = other.git;
+    };
+
+    // Overrides do not nest
+    let override_keys: Vec<&str> = SIZELINT_KEYS
+        .iter()
+        .copied()
+        .filter(|key| *key != "ci_overrides")
+        .collect();
+    let mut scopes = vec![(table, "", SIZELINT_KEYS)];
+    if let Some(toml::Value::Table(overrides)) = table.get("ci_overrides") {
+        scopes.push((overrides, "ci_overrides.", &override_keys));
+    }
+    for (table, prefix, known) in scopes {
+        check(table, prefix, known);
+        if let Some(toml::Value::Table(rules)) = table.get("rules") {
+            for (name, rule) in rules {
+                if let toml::Value::Table(rule) = rule {
+                    check(rule, &format!("{prefix}rules.{name}."), RULE_KEYS);
+                }
+            }
         }
-        self.respect_gitignore = other.respect_gitignore;
-        self.fail_on_warn = other.fail_on_warn;
     }
+    errors
 }
 
-impl RulesConfig {
-    fn merge_from(&mut self, other: RulesConfig) {
-        for (name, rule_def) in other.rules {
-            self.rules.insert(name, rule_def);
+/// Replace the bare percentage sizes in the config `table` with absolute
+/// bytes of `quota`. A percentage without a quota is an error.
+fn resolve_percentages(table: &mut toml::Table, quota: Option<u64>) -> Result<()> {
+    let resolve = |table: &mut toml::Table, key: &str| -> Result<()> {
+        if let Some(toml::Value::String(size)) = table.get_mut(key)
+            && size.trim().ends_with('%')
+        {
+            *size = format!("{}B", parse_size_with_quota(size, quota)?);
+        }
+        Ok(())
+    };
+
+    for key in [
+        "max_file_size",
+        "warn_file_size",
+        "max_growth",
+        "max_tree_growth",
+        "max_gitdir_size",
+    ] {
+        resolve(table, key)?;
+    }
+    if let Some(toml::Value::Table(rules)) = table.get_mut("rules") {
+        for (_, rule) in rules.iter_mut() {
+            if let toml::Value::Table(rule) = rule {
+                for key in [
+                    "max_size",
+                    "warn_size",
+                    "max_extracted_size",
+                    "extensionless_min_size",
+                ] {
+                    resolve(rule, key)?;
+                }
+                if let Some(toml::Value::Array(scopes)) = rule.get_mut("scoped_limits") {
+                    for scope in scopes.iter_mut() {
+                        if let toml::Value::Table(scope) = scope {
+                            resolve(scope, "max")?;
+                        }
+                    }
+                }
+            }
         }
     }
+    Ok(())
+}
 
-    pub fn get_enabled_rules(&self) -> Vec<(&String, &RuleDefinition)> {
-        self.rules.iter().filter(|(_, rule)| rule.enabled).collect()
+/// The key of `known` within two edits of `key`, if any.
+fn closest_key<'a>(key: &str, known: &[&'a str]) -> Option<&'a str> {
+    known
+        .iter()
+        .map(|candidate| (edit_distance(key, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
     }
+    row[b.len()]
 }
 
 impl Config {
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::load_from_file_with(path, false)
+    }
+
+    /// Like [`Config::load_from_file`], but with `lenient` unknown keys are
+    /// ignored instead of failing the load.
+    pub fn load_from_file_with<P: AsRef<Path>>(path: P, lenient: bool) -> Result<Self> {
         let _span = span!(Level::DEBUG, "Config::load_from_file", path = %path.as_ref().display())
             .entered();
 
-        let content = std::fs::read_to_string(path.as_ref())
-            .map_err(|e| SizelintError::config_read(path.as_ref().to_path_buf(), e))?;
-
-        let config: Config = toml::from_str(&content)
-            .map_err(|e| SizelintError::config_parse(path.as_ref().to_path_buf(), e))?;
+        let mut table = Self::load_table(path.as_ref(), lenient)?;
+        Self::resolve_quota_sizes([&mut table])?;
+        let config = Self::from_table(table)?;
 
         debug!("Config loaded successfully from file");
         Ok(config)
     }
 
+    /// The keys the config file at `path` sets, for layering with
+    /// [`Config::merge_table`] once percentage sizes are resolved with
+    /// [`Config::resolve_quota_sizes`]. Unknown keys are an error unless
+    /// `lenient`.
+    pub fn load_table(path: &Path, lenient: bool) -> Result<toml::Table> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| SizelintError::config_read(path.to_path_buf(), e))?;
+
+        let table: toml::Table = toml::from_str(&content)
+            .map_err(|e| SizelintError::config_parse(path.to_path_buf(), e))?;
+        if !lenient {
+            let mut errors = unknown_keys(&table, path);
+            match errors.len() {
+                0 => {}
+                1 => return Err(errors.remove(0)),
+                _ => return Err(SizelintError::config_errors(errors)),
+            }
+        }
+        // Report type errors against the file rather than a later merge. The
+        // quota may come from another layer, so percentages are checked
+        // against a stand-in one here
+        let mut checked = table.clone();
+        resolve_percentages(&mut checked, Some(0))?;
+        toml::Table::try_into::<Config>(checked)
+            .map_err(|e| SizelintError::config_parse(path.to_path_buf(), e))?;
+        Ok(table)
+    }
+
+    /// A config with only the keys in `table` set.
+    pub fn from_table(table: toml::Table) -> Result<Self> {
+        table.try_into().map_err(|e: toml::de::Error| {
+            SizelintError::config_invalid("config".to_string(), "table".to_string(), e.to_string())
+        })
+    }
+
     pub fn load_with_defaults<P: AsRef<Path>>(path: P) -> Result<Self> {
         let _span =
             span!(Level::DEBUG, "Config::load_with_defaults", path = %path.as_ref().display())
                 .entered();
 
+        let mut table = Self::load_table(path.as_ref(), false)?;
+        Self::resolve_quota_sizes([&mut table])?;
         let mut config = Self::default();
-
-        let user_config = Self::load_from_file(path)?;
-        config.merge_from_user_config(user_config);
+        config.merge_table(table)?;
 
         debug!("Config loaded and merged with defaults");
         Ok(config)
     }
 
-    fn merge_from_user_config(&mut self, user_config: Config) {
-        self.sizelint.merge_from(user_config.sizelint);
+    /// Replace bare percentage sizes (e.g. `"1%"`) in the config file
+    /// `layers`, lowest first, with absolute bytes of the `quota` they
+    /// merge to, so they parse like any other size. Sizes in `ci_overrides`
+    /// use the quota it sets, if any.
+    pub fn resolve_quota_sizes<'a>(
+        layers: impl IntoIterator<Item = &'a mut toml::Table>,
+    ) -> Result<()> {
+        let quota_of = |table: &toml::Table| -> Result<Option<u64>> {
+            table
+                .get("quota")
+                .and_then(|q| q.as_str())
+                .map(parse_size_string)
+                .transpose()
+        };
+
+        let mut layers: Vec<&mut toml::Table> = layers.into_iter().collect();
+        let mut quota = None;
+        for layer in &layers {
+            quota = quota_of(layer)?.or(quota);
+        }
+        for layer in &mut layers {
+            if let Some(toml::Value::Table(overrides)) = layer.get_mut("ci_overrides") {
+                let ci_quota = quota_of(overrides)?.or(quota);
+                resolve_percentages(overrides, ci_quota)?;
+            }
+            resolve_percentages(layer, quota)?;
+        }
+        Ok(())
+    }
 
-        if let Some(user_rules) = user_config.rules {
-            if let Some(ref mut default_rules) = self.rules {
-                default_rules.merge_from(user_rules);
+    /// Whether warnings of the rule `name` fail the run on their own.
+    pub fn warn_is_error(&self, name: &str) -> bool {
+        self.rules
+            .as_ref()
+            .and_then(|r| r.rules.get(name))
+            .is_some_and(|rule| rule.warn_is_error)
+    }
+
+    /// Problems that parse fine but make no sense, such as a `warn_size`
+    /// above the `max_size` it applies with. Empty for a valid config.
+    pub fn validate(&self) -> Vec<SizelintError> {
+        let mut errors = Vec::new();
+        if let Some(percent) = self.sizelint.error_grace_percent
+            && !(percent >= 0.0 && percent.is_finite())
+        {
+            errors.push(SizelintError::config_invalid(
+                "error_grace_percent".to_string(),
+                percent.to_string(),
+                "a non-negative percentage".to_string(),
+            ));
+        }
+
+        let mut check_sizes =
+            |field: String, max: Option<&SizeThreshold>, warn: Option<&SizeThreshold>| {
+                if let (Some(max), Some(warn)) = (max, warn)
+                    && warn.bytes() > max.bytes()
+                {
+                    errors.push(SizelintError::config_invalid(
+                        field,
+                        warn.to_string(),
+                        format!("a warning size no larger than the maximum size {max}"),
+                    ));
+                }
+            };
+
+        let max_file_size = self.sizelint.max_file_size.as_ref();
+        let warn_file_size = self.sizelint.warn_file_size.as_ref();
+        check_sizes("warn_file_size".to_string(), max_file_size, warn_file_size);
+
+        let mut rules: Vec<_> = self.rules.iter().flat_map(|r| &r.rules).collect();
+        rules.sort_by_key(|(name, _)| *name);
+        for (name, rule) in &rules {
+            check_sizes(
+                format!("rules.{name}.warn_size"),
+                rule.max_size.as_ref().or(max_file_size),
+                rule.warn_size.as_ref().or(warn_file_size),
+            );
+        }
+        for (name, rule) in rules {
+            if let Err(e) = crate::rules::ConfigurableRule::new(name.clone(), rule.clone()) {
+                errors.push(e);
+            }
+        }
+        errors
+    }
+
+    /// Apply `ci_overrides` on top of the other settings when `ci` is set.
+    /// Keys not named in the table keep their configured value, including
+    /// the keys of a `[ci_overrides.rules.NAME]` rule that already exists.
+    pub fn apply_ci_overrides(&mut self, ci: bool) -> Result<()> {
+        if !ci || self.sizelint.ci_overrides.is_empty() {
+            return Ok(());
+        }
+        let invalid = |e: &dyn std::fmt::Display| {
+            SizelintError::config_invalid(
+                "ci_overrides".to_string(),
+                "table".to_string(),
+                e.to_string(),
+            )
+        };
+
+        let overrides = std::mem::take(&mut self.sizelint.ci_overrides);
+        let mut table = toml::Table::try_from(&*self).map_err(|e| invalid(&e))?;
+        for (key, value) in overrides.clone() {
+            if key == "rules"
+                && let (Some(toml::Value::Table(rules)), toml::Value::Table(rule_overrides)) =
+                    (table.get_mut("rules"), &value)
+            {
+                for (name, rule) in rule_overrides {
+                    if let (Some(toml::Value::Table(base)), toml::Value::Table(keys)) =
+                        (rules.get_mut(name), rule)
+                    {
+                        base.extend(keys.clone());
+                    } else {
+                        rules.insert(name.clone(), rule.clone());
+                    }
+                }
             } else {
-                self.rules = Some(user_rules);
+                table.insert(key, value);
             }
         }
+        *self = table.try_into().map_err(|e| invalid(&e))?;
+        self.sizelint.ci_overrides = overrides;
+        debug!("Applied CI overrides");
+        Ok(())
+    }
+
+    /// Layer the keys of a config file's `table` over this config. Keys it
+    /// sets replace the ones here and keys it leaves out keep their values,
+    /// while each `[rules.NAME]` it sets replaces that whole rule.
+    pub fn merge_table(&mut self, table: toml::Table) -> Result<()> {
+        let invalid = |e: &dyn std::fmt::Display| {
+            SizelintError::config_invalid("config".to_string(), "table".to_string(), e.to_string())
+        };
+
+        let mut merged = toml::Table::try_from(&*self).map_err(|e| invalid(&e))?;
+        for (key, value) in table {
+            if key == "rules"
+                && let (Some(toml::Value::Table(base)), toml::Value::Table(rules)) =
+                    (merged.get_mut("rules"), &value)
+            {
+                base.extend(rules.clone());
+            } else {
+                merged.insert(key, value);
+            }
+        }
+        *self = merged.try_into().map_err(|e| invalid(&e))?;
+        Ok(())
     }
 
     pub fn find_config_file<P: AsRef<Path>>(start_dir: P) -> Option<PathBuf> {
@@ -190,6 +832,135 @@ impl Config {
     pub fn create_default_config() -> String {
         DEFAULT_CONFIG_TOML.to_string()
     }
+
+    /// Configuration content for a named preset, e.g. `web`.
+    pub fn create_preset_config(name: &str) -> Result<String> {
+        PRESETS
+            .iter()
+            .find(|(preset, _)| *preset == name)
+            .map(|(_, content)| content.to_string())
+            .ok_or_else(|| {
+                let supported: Vec<&str> = PRESETS.iter().map(|(preset, _)| *preset).collect();
+                SizelintError::config_invalid(
+                    "preset".to_string(),
+                    name.to_string(),
+                    format!("one of: {}", supported.join(", ")),
+                )
+            })
+    }
+}
+
+/// The layers a config was merged from, lowest first, kept to report
+/// where a key got its value with `--trace-config`.
+#[derive(Debug, Clone)]
+pub struct ConfigTrace {
+    layers: Vec<(String, toml::Table)>,
+}
+
+/// The values one key took in each layer that set it, and its merged value.
+/// Displays as `max_file_size: default=2MB → ./sizelint.toml=1MB (final)`,
+/// adding the merged value on its own when no layer set it verbatim.
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyTrace {
+    pub key: String,
+    pub sources: Vec<TraceStep>,
+    #[serde(rename = "final")]
+    pub resolved: Option<toml::Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceStep {
+    pub source: String,
+    pub value: toml::Value,
+}
+
+impl ConfigTrace {
+    /// A trace with no layers yet.
+    pub fn new() -> Self {
+        Self { layers: Vec::new() }
+    }
+
+    /// A trace starting from the embedded defaults, for configs merged over
+    /// [`Config::default`].
+    pub fn with_defaults() -> Self {
+        let defaults =
+            toml::from_str(DEFAULT_CONFIG_TOML).expect("Embedded default config must be valid");
+        Self {
+            layers: vec![("default".to_string(), defaults)],
+        }
+    }
+
+    /// Add `table` from `source` on top of the layers so far.
+    pub fn record(&mut self, source: String, table: toml::Table) {
+        self.layers.push((source, table));
+    }
+
+    /// Where the dotted `key`, e.g. `rules.media.max_size`, was set, and
+    /// its value in the merged `config`.
+    pub fn trace(&self, key: &str, config: &Config) -> Result<KeyTrace> {
+        let merged = toml::Table::try_from(config).map_err(|e| {
+            SizelintError::config_invalid("config".to_string(), key.to_string(), e.to_string())
+        })?;
+        let sources = self
+            .layers
+            .iter()
+            .filter_map(|(source, table)| {
+                lookup(table, key).map(|value| TraceStep {
+                    source: source.clone(),
+                    value: value.clone(),
+                })
+            })
+            .collect();
+        Ok(KeyTrace {
+            key: key.to_string(),
+            sources,
+            resolved: lookup(&merged, key).cloned(),
+        })
+    }
+}
+
+impl Default for ConfigTrace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for KeyTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let show = |value: &toml::Value| match value {
+            toml::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        let mut steps: Vec<String> = self
+            .sources
+            .iter()
+            .map(|step| format!("{}={}", step.source, show(&step.value)))
+            .collect();
+        match &self.resolved {
+            Some(value) if self.sources.last().is_some_and(|last| last.value == *value) => {
+                if let Some(last) = steps.last_mut() {
+                    last.push_str(" (final)");
+                }
+            }
+            Some(value) => steps.push(format!("merged={} (final)", show(value))),
+            None => steps.push("unset (final)".to_string()),
+        }
+        write!(f, "{}: {}", self.key, steps.join(" → "))
+    }
+}
+
+/// The value at the dotted `key` in `table`.
+fn lookup<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+    let (first, rest) = match key.split_once('.') {
+        Some((first, rest)) => (first, Some(rest)),
+        None => (key, None),
+    };
+    let value = table.get(first)?;
+    match (rest, value) {
+        (None, value) => Some(value),
+        (Some(rest), toml::Value::Table(inner)) => lookup(inner, rest),
+        (Some(_), _) => None,
+    }
 }
 
 #[cfg(test)]
@@ -211,4 +982,444 @@ mod tests {
         let result = toml::from_str::<Config>(DEFAULT_CONFIG_TOML);
         assert!(result.is_ok(), "Embedded config must be valid TOML");
     }
+
+    #[test]
+    fn test_embedded_presets_are_valid_toml() {
+        for (name, content) in PRESETS {
+            let result = toml::from_str::<Config>(content);
+            assert!(result.is_ok(), "Preset '{name}' must be valid TOML");
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_warn_size_above_max_size() {
+        assert!(Config::default().validate().is_empty());
+
+        let config: Config = toml::from_str(
+            r#"
+[rules.images]
+includes = ["*.png"]
+max_size = "1MB"
+warn_size = "5MB"
+"#,
+        )
+        .unwrap();
+        let errors = config.validate();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "Invalid configuration: rules.images.warn_size = '5MB'"
+        );
+    }
+
+    #[test]
+    fn test_validation_errors_combine_into_one_report() {
+        use miette::Diagnostic;
+
+        let config: Config = toml::from_str(
+            r#"
+[rules.images]
+includes = ["*.png"]
+max_size = "1MB"
+warn_size = "5MB"
+
+[rules.logs]
+includes = ["[*.log"]
+"#,
+        )
+        .unwrap();
+        let combined = SizelintError::config_errors(config.validate());
+        assert_eq!(combined.to_string(), "Found 2 configuration errors");
+
+        let related: Vec<String> = combined.related().unwrap().map(|e| e.to_string()).collect();
+        assert_eq!(
+            related,
+            vec![
+                "Invalid configuration: rules.images.warn_size = '5MB'",
+                "Invalid exclude pattern '[*.log'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ci_overrides_apply_only_in_ci() {
+        let config: Config = toml::from_str(
+            r#"
+max_file_size = "5MB"
+
+[rules.bins]
+includes = ["*.bin"]
+max_size = "1MB"
+
+[ci_overrides]
+fail_on_warn = true
+max_file_size = "1MB"
+
+[ci_overrides.rules.bins]
+max_size = "1KB"
+"#,
+        )
+        .unwrap();
+        let bins = |config: &Config| config.rules.as_ref().unwrap().rules["bins"].clone();
+
+        let mut local = config.clone();
+        local.apply_ci_overrides(ci_enabled(None)).unwrap();
+        assert!(!local.sizelint.fail_on_warn);
+        assert_eq!(
+            local.sizelint.max_file_size.as_ref().unwrap().as_str(),
+            "5MB"
+        );
+        assert_eq!(bins(&local).max_size.unwrap().as_str(), "1MB");
+
+        let mut ci = config;
+        ci.apply_ci_overrides(ci_enabled(Some("true"))).unwrap();
+        assert!(ci.sizelint.fail_on_warn);
+        assert_eq!(ci.sizelint.max_file_size.as_ref().unwrap().as_str(), "1MB");
+        // Settings the overrides leave out keep their configured values
+        assert!(ci.sizelint.respect_gitignore);
+        assert_eq!(bins(&ci).max_size.unwrap().as_str(), "1KB");
+        assert_eq!(bins(&ci).includes, vec!["*.bin"]);
+
+        assert!(!ci_enabled(Some("false")));
+        assert!(!ci_enabled(Some("")));
+    }
+
+    #[test]
+    fn test_percentage_sizes_resolve_against_quota() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("sizelint.toml");
+        std::fs::write(
+            &path,
+            r#"
+quota = "10GB"
+
+[rules.data]
+includes = ["*.bin"]
+max_size = "1%"
+"#,
+        )
+        .unwrap();
+
+        let config = Config::load_from_file(&path).unwrap();
+        let rule = &config.rules.as_ref().unwrap().rules["data"];
+        // 1% of 10 GiB, truncated to whole bytes
+        assert_eq!(
+            rule.max_size.as_ref().map(SizeThreshold::as_str),
+            Some("107374182B")
+        );
+
+        let rule = crate::rules::ConfigurableRule::new("data".to_string(), rule.clone()).unwrap();
+        let file = tmp.path().join("big.bin");
+        std::fs::File::create(&file)
+            .unwrap()
+            .set_len(107_374_183)
+            .unwrap();
+        assert_eq!(rule.check(&file).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_percentage_sizes_resolve_across_layers() {
+        let mut user: toml::Table = toml::from_str("quota = \"10GB\"\n").unwrap();
+        let mut repo: toml::Table = toml::from_str(
+            r#"
+max_file_size = "1%"
+warn_file_size = "1%"
+max_growth = "1%"
+max_tree_growth = "1%"
+max_gitdir_size = "1%"
+
+[rules.data]
+max_size = "1%"
+warn_size = "1%"
+max_extracted_size = "1%"
+extensionless_min_size = "1%"
+scoped_limits = [{ path = "big/**", max = "1%" }]
+
+[ci_overrides]
+quota = "1GB"
+max_file_size = "1%"
+"#,
+        )
+        .unwrap();
+
+        Config::resolve_quota_sizes([&mut user, &mut repo]).unwrap();
+        let mut config = Config::from_table(toml::Table::new()).unwrap();
+        config.merge_table(user).unwrap();
+        config.merge_table(repo).unwrap();
+
+        // 1% of 10 GiB, truncated to whole bytes
+        let one_percent = Some("107374182B");
+        let sizes = &config.sizelint;
+        for size in [
+            &sizes.max_file_size,
+            &sizes.warn_file_size,
+            &sizes.max_growth,
+            &sizes.max_tree_growth,
+            &sizes.max_gitdir_size,
+        ] {
+            assert_eq!(size.as_ref().map(SizeThreshold::as_str), one_percent);
+        }
+        let rule = &config.rules.as_ref().unwrap().rules["data"];
+        for size in [
+            &rule.max_size,
+            &rule.warn_size,
+            &rule.max_extracted_size,
+            &rule.extensionless_min_size,
+        ] {
+            assert_eq!(size.as_ref().map(SizeThreshold::as_str), one_percent);
+        }
+        assert_eq!(rule.scoped_limits[0].max.as_str(), "107374182B");
+
+        // CI overrides take a percentage of their own quota
+        config.apply_ci_overrides(true).unwrap();
+        assert_eq!(
+            config
+                .sizelint
+                .max_file_size
+                .as_ref()
+                .map(SizeThreshold::as_str),
+            Some("10737418B")
+        );
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(20_454), (2026, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(parse_date("2025-12-31").unwrap(), (2025, 12, 31));
+        assert!(parse_date("2025-13-01").is_err());
+        assert!(parse_date("tomorrow").is_err());
+    }
+
+    #[test]
+    fn test_web_preset_contains_web_rules() {
+        let content = Config::create_preset_config("web").unwrap();
+        let config: Config = toml::from_str(&content).unwrap();
+
+        let rules = config.rules.unwrap();
+        assert!(
+            rules.rules["bundles"]
+                .includes
+                .contains(&"*.js".to_string())
+        );
+        assert!(rules.rules.contains_key("images"));
+    }
+
+    #[test]
+    fn test_unknown_preset_lists_available() {
+        let err = Config::create_preset_config("desktop").unwrap_err();
+        match err {
+            SizelintError::ConfigInvalid { expected, .. } => {
+                assert!(expected.contains("web"));
+                assert!(expected.contains("data-science"));
+                assert!(expected.contains("mobile"));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_unknown_key_suggests_closest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sizelint.toml");
+        std::fs::write(&path, "max_filesize = \"1MB\"\n").unwrap();
+
+        match Config::load_from_file(&path).unwrap_err() {
+            SizelintError::ConfigUnknownKey { key, help, .. } => {
+                assert_eq!(key, "max_filesize");
+                assert!(help.contains("'max_file_size'"), "{help}");
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+
+        let config = Config::load_from_file_with(&path, true).unwrap();
+        assert!(config.sizelint.max_file_size.is_none());
+    }
+
+    #[test]
+    fn test_unknown_rule_keys_are_reported() {
+        let table: toml::Table = toml::from_str(
+            r#"
+[rules.media]
+max_sise = "1MB"
+colour = "red"
+
+[ci_overrides.rules.media]
+max_sise = "1KB"
+
+[ci_overrides.ci_overrides]
+fail_on_warn = true
+"#,
+        )
+        .unwrap();
+
+        let errors = unknown_keys(&table, Path::new("sizelint.toml"));
+        let keys: Vec<_> = errors
+            .iter()
+            .map(|e| match e {
+                SizelintError::ConfigUnknownKey { key, help, .. } => (key.as_str(), help.as_str()),
+                other => panic!("unexpected error: {other:?}"),
+            })
+            .collect();
+        assert_eq!(keys.len(), 4);
+        assert_eq!(keys[0].0, "rules.media.colour");
+        assert!(keys[0].1.starts_with("Remove the key"));
+        assert_eq!(keys[1].0, "rules.media.max_sise");
+        assert!(keys[1].1.contains("'max_size'"));
+        assert_eq!(keys[2].0, "ci_overrides.ci_overrides");
+        assert_eq!(keys[3].0, "ci_overrides.rules.media.max_sise");
+    }
+
+    #[test]
+    fn test_default_config_keys_are_known() {
+        let table = toml::Table::try_from(Config::default()).unwrap();
+        assert!(unknown_keys(&table, Path::new("sizelint.toml")).is_empty());
+        for (_, preset) in PRESETS {
+            let table: toml::Table = toml::from_str(preset).unwrap();
+            assert!(unknown_keys(&table, Path::new("sizelint.toml")).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_known_keys_cover_every_field() {
+        // JSON keeps unset options as null, where TOML would drop them
+        let fields = |value: serde_json::Value| -> Vec<String> {
+            value.as_object().unwrap().keys().cloned().collect()
+        };
+
+        for key in fields(serde_json::to_value(Config::default().sizelint).unwrap()) {
+            assert!(SIZELINT_KEYS.contains(&key.as_str()), "{key}");
+        }
+        for key in fields(serde_json::to_value(RuleDefinition::default()).unwrap()) {
+            assert!(RULE_KEYS.contains(&key.as_str()), "{key}");
+        }
+    }
+
+    #[test]
+    fn test_merge_table_keeps_keys_the_upper_layer_leaves_out() {
+        let user: toml::Table = toml::from_str(
+            r#"
+fail_on_warn = true
+boundary = "exclusive"
+respect_gitignore = false
+
+[rules.media]
+includes = ["*.mp4"]
+max_size = "10MB"
+"#,
+        )
+        .unwrap();
+        let repo: toml::Table = toml::from_str(
+            r#"
+max_file_size = "1MB"
+
+[rules.docs]
+includes = ["*.pdf"]
+"#,
+        )
+        .unwrap();
+
+        let mut config = Config::default();
+        config.merge_table(user).unwrap();
+        config.merge_table(repo).unwrap();
+
+        assert!(config.sizelint.fail_on_warn);
+        assert_eq!(config.sizelint.boundary, Boundary::Exclusive);
+        assert!(!config.sizelint.respect_gitignore);
+        assert_eq!(config.sizelint.max_file_size.unwrap().as_str(), "1MB");
+        let rules = &config.rules.unwrap().rules;
+        assert!(rules.contains_key("media"));
+        assert!(rules.contains_key("docs"));
+        assert!(rules.contains_key("default"));
+
+        // A key the upper layer does set still wins
+        let mut config = Config::default();
+        config
+            .merge_table(
+                toml::from_str(
+                    "fail_on_warn = true
+",
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        config
+            .merge_table(
+                toml::from_str(
+                    "fail_on_warn = false
+",
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        assert!(!config.sizelint.fail_on_warn);
+    }
+
+    #[test]
+    fn test_trace_shows_base_and_override() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("base.toml");
+        let child = dir.path().join("sizelint.toml");
+        std::fs::write(&base, "max_file_size = \"5MB\"\nfail_on_warn = true\n").unwrap();
+        std::fs::write(&child, "max_file_size = \"2MB\"\n").unwrap();
+
+        let mut config = Config::default();
+        config
+            .merge_table(Config::load_table(&base, false).unwrap())
+            .unwrap();
+        config
+            .merge_table(Config::load_table(&child, false).unwrap())
+            .unwrap();
+        let mut trace = ConfigTrace::with_defaults();
+        trace.record(
+            base.display().to_string(),
+            Config::load_table(&base, false).unwrap(),
+        );
+        trace.record(
+            child.display().to_string(),
+            Config::load_table(&child, false).unwrap(),
+        );
+
+        let size = trace.trace("max_file_size", &config).unwrap();
+        let sources: Vec<_> = size.sources.iter().map(|s| s.source.as_str()).collect();
+        assert_eq!(
+            sources,
+            [
+                "default",
+                &*base.display().to_string(),
+                &*child.display().to_string()
+            ]
+        );
+        assert_eq!(
+            size.to_string(),
+            format!(
+                "max_file_size: default=2MB → {}=5MB → {}=2MB (final)",
+                base.display(),
+                child.display()
+            )
+        );
+
+        // The child leaves fail_on_warn unset, so the base value stays
+        let fail_on_warn = trace.trace("fail_on_warn", &config).unwrap();
+        assert_eq!(
+            fail_on_warn.to_string(),
+            format!(
+                "fail_on_warn: default=false → {}=true (final)",
+                base.display()
+            )
+        );
+        // Set by no layer, so only the merged value is shown
+        assert_eq!(
+            trace.trace("ascii", &config).unwrap().to_string(),
+            "ascii: merged=false (final)"
+        );
+        assert!(
+            trace
+                .trace("rules.media.max_size", &config)
+                .unwrap()
+                .to_string()
+                .ends_with("unset (final)")
+        );
+    }
 }
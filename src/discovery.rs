@@ -1,18 +1,82 @@
 use crate::error::{Result, SizelintError};
-use crate::git::GitRepo;
+use crate::git::{CommitWindow, DiffFilter, GitLayout, GitRepo};
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use ignore::WalkBuilder;
 use rayon::prelude::*;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tracing::{Level, debug, span};
 
 const DEFAULT_FILES_CAPACITY: usize = 1024;
 const DEFAULT_DIR_CAPACITY: usize = 512;
 
+/// Generated and vendored directories skipped with `exclude_common`, in
+/// addition to the configured excludes.
+pub const COMMON_EXCLUDES: &[&str] = &[
+    "**/node_modules/**",
+    "**/target/**",
+    "**/vendor/**",
+    "**/.venv/**",
+    "**/dist/**",
+];
+
+/// Parse a `--modified-since` value relative to `now`: a duration such as
+/// `90s`, `30m`, `1h`, `2d` or `1w`, or a `YYYY-MM-DD` date (midnight UTC).
+pub fn parse_modified_since(value: &str, now: SystemTime) -> Result<SystemTime> {
+    let invalid = || {
+        SizelintError::config_invalid(
+            "modified_since".to_string(),
+            value.to_string(),
+            "a duration like 30m, 1h or 2d, or a date like 2025-12-31".to_string(),
+        )
+    };
+
+    let value = value.trim();
+    if value.contains('-') {
+        let date = crate::config::parse_date(value).map_err(|_| invalid())?;
+        let days = crate::config::days_from_civil(date);
+        let secs = u64::try_from(days * 86_400).map_err(|_| invalid())?;
+        return Ok(UNIX_EPOCH + Duration::from_secs(secs));
+    }
+
+    let unit_start = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let amount: u64 = value[..unit_start].parse().map_err(|_| invalid())?;
+    let unit_secs = match &value[unit_start..] {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        "w" => 7 * 86_400,
+        _ => return Err(invalid()),
+    };
+    now.checked_sub(Duration::from_secs(amount.saturating_mul(unit_secs)))
+        .ok_or_else(invalid)
+}
+
+/// The files in `files` modified after `cutoff`. Files whose modification
+/// time cannot be read are kept rather than skipped.
+pub fn modified_since(files: Vec<PathBuf>, cutoff: SystemTime) -> Vec<PathBuf> {
+    files
+        .into_par_iter()
+        .filter(
+            |path| match std::fs::metadata(path).and_then(|m| m.modified()) {
+                Ok(modified) => modified > cutoff,
+                Err(_) => true,
+            },
+        )
+        .collect()
+}
+
 pub struct FileDiscovery {
     root: PathBuf,
     git_repo: Option<GitRepo>,
+    includes: GlobSet,
     excludes: GlobSet,
+    diff_filter: DiffFilter,
+    commit_window: CommitWindow,
+    include_untracked: bool,
 }
 
 impl FileDiscovery {
@@ -38,10 +102,63 @@ impl FileDiscovery {
         Ok(FileDiscovery {
             root,
             git_repo,
+            includes: GlobSet::empty(),
             excludes,
+            diff_filter: DiffFilter::default(),
+            commit_window: CommitWindow::default(),
+            include_untracked: false,
         })
     }
 
+    /// Locate the git repository through `layout` instead of from the root.
+    pub fn with_git_layout(mut self, layout: GitLayout) -> Self {
+        self.git_repo = GitRepo::discover_with_layout(&self.root, layout).ok();
+        self
+    }
+
+    /// Only discover files matching one of `patterns`; none keeps every file.
+    pub fn with_includes(mut self, patterns: &[String]) -> Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let glob = Glob::new(pattern)
+                .map_err(|e| SizelintError::config_invalid_pattern(pattern.clone(), e))?;
+            builder.add(glob);
+        }
+        self.includes = builder.build().map_err(|e| {
+            SizelintError::config_invalid(
+                "includes".to_string(),
+                "globset_builder".to_string(),
+                format!("Failed to build include patterns: {e}"),
+            )
+        })?;
+        Ok(self)
+    }
+
+    /// Bound the git processes of history walks to `jobs`, as
+    /// [`GitRepo::with_jobs`].
+    pub fn with_git_jobs(mut self, jobs: Option<usize>) -> Self {
+        self.git_repo = self.git_repo.map(|repo| repo.with_jobs(jobs));
+        self
+    }
+
+    /// Restrict git diff-based discovery to the given kinds of changes.
+    pub fn with_diff_filter(mut self, diff_filter: DiffFilter) -> Self {
+        self.diff_filter = diff_filter;
+        self
+    }
+
+    /// Restrict history walks to commits within the given dates.
+    pub fn with_commit_window(mut self, commit_window: CommitWindow) -> Self {
+        self.commit_window = commit_window;
+        self
+    }
+
+    /// Also report untracked, non-ignored files in working tree discovery.
+    pub fn with_untracked(mut self, include_untracked: bool) -> Self {
+        self.include_untracked = include_untracked;
+        self
+    }
+
     fn create_walker(&self, root: &Path, respect_gitignore: bool) -> WalkBuilder {
         let mut builder = WalkBuilder::new(root);
         builder
@@ -58,7 +175,6 @@ impl FileDiscovery {
 
         walker.run(|| {
             let files = &files;
-            let excludes = &self.excludes;
             let root = &self.root;
 
             Box::new(move |entry| {
@@ -74,7 +190,7 @@ impl FileDiscovery {
                         }
 
                         let rel = path.strip_prefix(root).unwrap_or(path);
-                        if !excludes.is_match(rel) {
+                        if !self.is_skipped(rel) {
                             files.lock().unwrap().push(path.to_path_buf());
                         }
                     }
@@ -108,7 +224,7 @@ impl FileDiscovery {
     pub fn discover_staged_files(&self) -> Result<Vec<PathBuf>> {
         match &self.git_repo {
             Some(git_repo) => {
-                let staged_files = git_repo.get_staged_files()?;
+                let staged_files = git_repo.get_staged_files(self.diff_filter)?;
                 Ok(self.filter_files(staged_files))
             }
             None => Err(crate::git::GitError::RepoNotFound {
@@ -121,7 +237,10 @@ impl FileDiscovery {
     pub fn discover_working_tree_files(&self) -> Result<Vec<PathBuf>> {
         match &self.git_repo {
             Some(git_repo) => {
-                let working_files = git_repo.get_working_tree_files()?;
+                let mut working_files = git_repo.get_working_tree_files(self.diff_filter)?;
+                if self.include_untracked {
+                    working_files.extend(git_repo.get_untracked_files()?);
+                }
                 Ok(self.filter_files(working_files))
             }
             None => Err(crate::git::GitError::RepoNotFound {
@@ -134,7 +253,7 @@ impl FileDiscovery {
     pub fn discover_git_diff_files(&self, range: &str) -> Result<Vec<PathBuf>> {
         match &self.git_repo {
             Some(git_repo) => {
-                let diff_files = git_repo.get_diff_files(range)?;
+                let diff_files = git_repo.get_diff_files(range, self.diff_filter)?;
                 Ok(self.filter_files(diff_files))
             }
             None => Err(crate::git::GitError::RepoNotFound {
@@ -147,15 +266,9 @@ impl FileDiscovery {
     pub fn discover_history_blobs(&self, range: &str) -> Result<Vec<crate::git::HistoryBlob>> {
         match &self.git_repo {
             Some(git_repo) => {
-                let blobs = git_repo.walk_history_blobs(range)?;
-                Ok(blobs
-                    .into_iter()
-                    .filter(|blob| {
-                        let path = Path::new(&blob.path);
-                        let rel = path.strip_prefix(&self.root).unwrap_or(path);
-                        !self.excludes.is_match(rel)
-                    })
-                    .collect())
+                let blobs =
+                    git_repo.walk_history_blobs(range, self.diff_filter, &self.commit_window)?;
+                Ok(self.filter_blobs(blobs))
             }
             None => Err(crate::git::GitError::RepoNotFound {
                 path: self.root.clone(),
@@ -164,13 +277,94 @@ impl FileDiscovery {
         }
     }
 
+    /// Blobs from every commit reachable from HEAD, minus excluded paths.
+    pub fn discover_all_history_blobs(&self) -> Result<Vec<crate::git::HistoryBlob>> {
+        match &self.git_repo {
+            Some(git_repo) => {
+                let blobs =
+                    git_repo.walk_all_history_blobs(self.diff_filter, &self.commit_window)?;
+                Ok(self.filter_blobs(blobs))
+            }
+            None => Err(crate::git::GitError::RepoNotFound {
+                path: self.root.clone(),
+            }
+            .into()),
+        }
+    }
+
+    /// Blobs from the commits in `range`, or all of history without one,
+    /// minus excluded paths, handed to `each` `batch_commits` commits at a
+    /// time.
+    pub fn discover_history_blob_batches(
+        &self,
+        range: Option<&str>,
+        batch_commits: usize,
+        mut each: impl FnMut(Vec<crate::git::HistoryBlob>) -> Result<()>,
+    ) -> Result<()> {
+        match &self.git_repo {
+            Some(git_repo) => git_repo.walk_history_blob_batches(
+                range,
+                self.diff_filter,
+                &self.commit_window,
+                batch_commits,
+                |blobs| each(self.filter_blobs(blobs)),
+            ),
+            None => Err(crate::git::GitError::RepoNotFound {
+                path: self.root.clone(),
+            }
+            .into()),
+        }
+    }
+
+    /// Blobs added or modified by each of `commits`, minus excluded paths.
+    pub fn discover_commit_blobs(
+        &self,
+        commits: &[String],
+    ) -> Result<Vec<crate::git::HistoryBlob>> {
+        match &self.git_repo {
+            Some(git_repo) => {
+                let blobs = git_repo.commit_blobs(commits, self.diff_filter)?;
+                Ok(self.filter_blobs(blobs))
+            }
+            None => Err(crate::git::GitError::RepoNotFound {
+                path: self.root.clone(),
+            }
+            .into()),
+        }
+    }
+
+    /// Blobs in the tree at `rev`, minus excluded paths.
+    pub fn discover_tree_blobs(&self, rev: &str) -> Result<Vec<crate::git::HistoryBlob>> {
+        match &self.git_repo {
+            Some(git_repo) => {
+                let blobs = git_repo.tree_blobs_at(rev)?;
+                Ok(self.filter_blobs(blobs))
+            }
+            None => Err(crate::git::GitError::RepoNotFound {
+                path: self.root.clone(),
+            }
+            .into()),
+        }
+    }
+
+    fn filter_blobs(&self, blobs: Vec<crate::git::HistoryBlob>) -> Vec<crate::git::HistoryBlob> {
+        blobs
+            .into_iter()
+            .filter(|blob| {
+                let path = Path::new(&blob.path);
+                let rel = path.strip_prefix(&self.root).unwrap_or(path);
+                !self.is_skipped(rel)
+            })
+            .collect()
+    }
+
     pub fn discover_specific_paths(&self, paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
         let mut files = Vec::new();
 
         for path in paths {
             if path.is_file() {
                 let rel = path.strip_prefix(&self.root).unwrap_or(path);
-                if !self.excludes.is_match(rel) {
+                if !self.is_skipped(rel) {
                     files.push(path.clone());
                 }
             } else if path.is_dir() {
@@ -193,11 +387,16 @@ impl FileDiscovery {
             .into_par_iter()
             .filter(|path| {
                 let rel = path.strip_prefix(&self.root).unwrap_or(path);
-                path.exists() && !self.excludes.is_match(rel)
+                path.exists() && !self.is_skipped(rel)
             })
             .collect()
     }
 
+    /// Whether `rel` is excluded or falls outside the include patterns.
+    fn is_skipped(&self, rel: &Path) -> bool {
+        self.excludes.is_match(rel) || (!self.includes.is_empty() && !self.includes.is_match(rel))
+    }
+
     pub fn is_in_git_repo(&self) -> bool {
         self.git_repo.is_some()
     }
@@ -38,6 +38,15 @@ pub enum SizelintError {
         expected: String,
     },
 
+    #[error("Unknown configuration key '{key}' in {path}")]
+    #[diagnostic(code(sizelint::config::unknown_key))]
+    ConfigUnknownKey {
+        key: String,
+        path: PathBuf,
+        #[help]
+        help: String,
+    },
+
     #[error("Invalid exclude pattern '{pattern}'")]
     #[diagnostic(
         code(sizelint::config::invalid_pattern),
@@ -49,6 +58,13 @@ pub enum SizelintError {
         source: globset::Error,
     },
 
+    #[error("Found {} configuration errors", .errors.len())]
+    #[diagnostic(code(sizelint::config::errors))]
+    ConfigErrors {
+        #[related]
+        errors: Vec<SizelintError>,
+    },
+
     // File system errors
     #[error("Failed to {operation} {path}")]
     #[diagnostic(code(sizelint::filesystem::operation_failed))]
@@ -59,6 +75,17 @@ pub enum SizelintError {
         source: std::io::Error,
     },
 
+    #[error("Failed to read archive {path}")]
+    #[diagnostic(
+        code(sizelint::archive::read_error),
+        help("The archive may be corrupt or truncated - check that it extracts with tar or unzip")
+    )]
+    ArchiveRead {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
     #[error("Failed to get current directory")]
     #[diagnostic(
         code(sizelint::filesystem::current_dir_error),
@@ -120,10 +147,24 @@ impl SizelintError {
         }
     }
 
+    /// An unknown key, suggesting `suggestion` if it is a likely typo.
+    pub fn config_unknown_key(key: String, path: PathBuf, suggestion: Option<&str>) -> Self {
+        let help = match suggestion {
+            Some(known) => format!("Did you mean '{known}'? Pass --lenient to ignore unknown keys"),
+            None => "Remove the key, or pass --lenient to ignore unknown keys".to_string(),
+        };
+        Self::ConfigUnknownKey { key, path, help }
+    }
+
     pub fn config_invalid_pattern(pattern: String, source: globset::Error) -> Self {
         Self::ConfigInvalidPattern { pattern, source }
     }
 
+    /// Several configuration errors reported together as one diagnostic.
+    pub fn config_errors(errors: Vec<SizelintError>) -> Self {
+        Self::ConfigErrors { errors }
+    }
+
     pub fn filesystem(operation: String, path: PathBuf, source: std::io::Error) -> Self {
         Self::FileSystem {
             operation,
@@ -132,6 +173,15 @@ impl SizelintError {
         }
     }
 
+    /// Whether this is a filesystem error for a path that does not exist.
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Self::FileSystem { source, .. } if source.kind() == std::io::ErrorKind::NotFound)
+    }
+
+    pub fn archive_read(path: PathBuf, source: std::io::Error) -> Self {
+        Self::ArchiveRead { path, source }
+    }
+
     pub fn invalid_size_format(input: String, reason: String) -> Self {
         Self::InvalidSizeFormat { input, reason }
     }
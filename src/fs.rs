@@ -0,0 +1,253 @@
+use crate::error::{Result, SizelintError};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Group label for files without an extension in [`sizes_by_extension`].
+pub const NO_EXTENSION: &str = "(none)";
+
+/// Suffix of the sidecar file holding a per-file limit, e.g. `foo.bin.sizelimit`.
+pub const SIDECAR_SUFFIX: &str = ".sizelimit";
+
+/// Write `contents` to `path` atomically.
+///
+/// The data goes to a temporary file in the same directory which is then
+/// renamed over `path`, so an interrupted write never leaves a truncated file.
+pub fn write_atomic<P: AsRef<Path>>(path: P, contents: impl AsRef<[u8]>) -> Result<()> {
+    write_atomic_with(path.as_ref(), |file| file.write_all(contents.as_ref()))
+}
+
+fn write_atomic_with<F>(path: &Path, write: F) -> Result<()>
+where
+    F: FnOnce(&mut File) -> io::Result<()>,
+{
+    let tmp_path = temp_path_for(path);
+
+    let result = File::create(&tmp_path).and_then(|mut file| {
+        write(&mut file)?;
+        file.sync_all()
+    });
+
+    if let Err(e) = result.and_then(|()| fs::rename(&tmp_path, path)) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(SizelintError::filesystem(
+            "write".to_string(),
+            path.to_path_buf(),
+            e,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Total size of all files below `dir`. Symlinks are not followed.
+pub fn dir_size(dir: &Path) -> Result<u64> {
+    let entries = fs::read_dir(dir).map_err(|e| {
+        SizelintError::filesystem("read directory".to_string(), dir.to_path_buf(), e)
+    })?;
+
+    let mut total = 0;
+    for entry in entries {
+        let entry = entry.map_err(|e| {
+            SizelintError::filesystem("read directory".to_string(), dir.to_path_buf(), e)
+        })?;
+        let path = entry.path();
+        let metadata = fs::symlink_metadata(&path).map_err(|e| {
+            SizelintError::filesystem("get file metadata".to_string(), path.clone(), e)
+        })?;
+        if metadata.is_dir() {
+            total += dir_size(&path)?;
+        } else {
+            total += metadata.len();
+        }
+    }
+    Ok(total)
+}
+
+/// Total size of `files` per lowercase extension. Files that cannot be
+/// stat'ed are left out.
+pub fn sizes_by_extension(files: &[PathBuf]) -> BTreeMap<String, u64> {
+    let mut sizes = BTreeMap::new();
+    for file in files {
+        let Ok(metadata) = fs::metadata(file) else {
+            continue;
+        };
+        let extension = file
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_else(|| NO_EXTENSION.to_string());
+        *sizes.entry(extension).or_insert(0) += metadata.len();
+    }
+    sizes
+}
+
+/// Whether `path`, following symlinks, is a regular file.
+///
+/// Content is only ever read from regular files: opening a FIFO blocks until
+/// a writer appears, and a device may never reach end of file.
+pub fn is_regular_file(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|m| m.is_file())
+}
+
+/// Per-file maximum sizes from sidecar files: `foo.bin.sizelimit` holding
+/// e.g. `5MB` caps `foo.bin`. Files without a sidecar are left out.
+pub fn sidecar_limits(files: &[PathBuf]) -> Result<HashMap<PathBuf, u64>> {
+    let mut limits = HashMap::new();
+    for file in files {
+        let mut sidecar = file.clone().into_os_string();
+        sidecar.push(SIDECAR_SUFFIX);
+        let sidecar = PathBuf::from(sidecar);
+        if !is_regular_file(&sidecar) {
+            continue;
+        }
+        let content = std::fs::read_to_string(&sidecar).map_err(|e| {
+            SizelintError::filesystem("read size limit".to_string(), sidecar.clone(), e)
+        })?;
+        let limit = crate::rules::parse_size_string(content.trim()).map_err(|_| {
+            SizelintError::config_invalid(
+                sidecar.display().to_string(),
+                content.trim().to_string(),
+                "a size such as 5MB".to_string(),
+            )
+        })?;
+        limits.insert(file.clone(), limit);
+    }
+    Ok(limits)
+}
+
+fn temp_path_for(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{file_name}.{}.tmp", std::process::id()))
+}
+
+/// A `path,bytes` CSV with a header and one row per file in `files`,
+/// sorted by path relative to `root`. Files that cannot be stat'ed are left
+/// out.
+pub fn sizes_csv(files: &[PathBuf], root: &Path) -> String {
+    let mut rows: Vec<(String, u64)> = files
+        .iter()
+        .filter_map(|file| {
+            let size = fs::metadata(file).ok()?.len();
+            let rel = file.strip_prefix(root).unwrap_or(file);
+            Some((rel.to_string_lossy().to_string(), size))
+        })
+        .collect();
+    rows.sort();
+
+    let mut csv = String::from("path,bytes\n");
+    for (path, size) in rows {
+        csv.push_str(&format!("{},{size}\n", csv_field(&path)));
+    }
+    csv
+}
+
+/// `field` quoted as RFC 4180 requires when it contains a comma, quote or
+/// line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic_replaces_contents() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("sizelint.toml");
+        fs::write(&path, "old").unwrap();
+
+        write_atomic(&path, "new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_dir_size_sums_nested_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("objects/pack")).unwrap();
+        fs::write(tmp.path().join("HEAD"), vec![0u8; 10]).unwrap();
+        fs::write(tmp.path().join("objects/pack/a.pack"), vec![0u8; 1000]).unwrap();
+
+        assert_eq!(dir_size(tmp.path()).unwrap(), 1010);
+    }
+
+    #[test]
+    fn test_sizes_by_extension_sums_each_type() {
+        let tmp = tempfile::tempdir().unwrap();
+        let files: Vec<PathBuf> = [
+            ("a.png", 100),
+            ("b.png", 200),
+            ("c.PNG", 300),
+            ("clip.mp4", 5000),
+            ("Makefile", 7),
+        ]
+        .iter()
+        .map(|(name, len)| {
+            let path = tmp.path().join(name);
+            fs::write(&path, vec![0u8; *len]).unwrap();
+            path
+        })
+        .collect();
+
+        let sizes = sizes_by_extension(&files);
+        let mut rows: Vec<_> = sizes.into_iter().collect();
+        rows.sort_by_key(|row| std::cmp::Reverse(row.1));
+        assert_eq!(
+            rows,
+            vec![
+                ("mp4".to_string(), 5000),
+                ("png".to_string(), 600),
+                (NO_EXTENSION.to_string(), 7),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sizes_csv_lists_every_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir(tmp.path().join("assets")).unwrap();
+        let files: Vec<PathBuf> = [
+            ("assets/logo.png", 1234),
+            ("a,b \"c\".txt", 5),
+            ("empty", 0),
+        ]
+        .iter()
+        .map(|(name, len)| {
+            let path = tmp.path().join(name);
+            fs::write(&path, vec![0u8; *len]).unwrap();
+            path
+        })
+        .collect();
+
+        assert_eq!(
+            sizes_csv(&files, tmp.path()),
+            "path,bytes\n\"a,b \"\"c\"\".txt\",5\nassets/logo.png,1234\nempty,0\n"
+        );
+    }
+
+    #[test]
+    fn test_interrupted_write_keeps_original() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("sizelint.toml");
+        fs::write(&path, "original").unwrap();
+
+        let result = write_atomic_with(&path, |file| {
+            file.write_all(b"partial")?;
+            Err(io::Error::other("disk full"))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "original");
+        assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 1);
+    }
+}
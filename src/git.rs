@@ -1,10 +1,21 @@
 use miette::Diagnostic;
 use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use thiserror::Error;
 
+/// Explicit locations of the git directory and work tree, for bare
+/// repositories, linked worktrees and CI checkouts that `git rev-parse`
+/// cannot find from the checked path. Passed to git as `GIT_DIR` and
+/// `GIT_WORK_TREE`.
+#[derive(Debug, Clone, Default)]
+pub struct GitLayout {
+    pub git_dir: Option<PathBuf>,
+    pub work_tree: Option<PathBuf>,
+}
+
 #[derive(Error, Debug, Diagnostic)]
 pub enum GitError {
     #[error("Git repository not found at {path}")]
@@ -21,6 +32,22 @@ pub enum GitError {
     )]
     RefNotFound { git_ref: String, repo: PathBuf },
 
+    #[error("HEAD is already at '{git_ref}', so no commits are being checked")]
+    #[diagnostic(
+        code(sizelint::git::base_is_head),
+        help(
+            "Run from a feature branch, or set on_base_branch = \"last-commit\" to check the last commit instead"
+        )
+    )]
+    BaseIsHead { git_ref: String },
+
+    #[error("History window starts at '{since}', after it ends at '{until}'")]
+    #[diagnostic(
+        code(sizelint::git::reversed_window),
+        help("Pass an earlier date to --since than to --until")
+    )]
+    ReversedWindow { since: String, until: String },
+
     #[error("Paths span multiple git repositories")]
     #[diagnostic(
         code(sizelint::git::multiple_repos),
@@ -46,6 +73,53 @@ pub enum GitError {
 
 type Result<T> = std::result::Result<T, GitError>;
 
+/// gitattribute carrying a per-path maximum size, e.g. `data/** sizelint-max=5MB`.
+pub const MAX_SIZE_ATTRIBUTE: &str = "sizelint-max";
+
+/// Upper bound for the combined length of path arguments passed to a single
+/// git invocation. Stays well below `ARG_MAX` and the Windows command-line limit.
+const MAX_PATH_ARGS_LEN: usize = 30_000;
+
+/// Which kinds of changes git diff-based discovery reports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiffFilter {
+    /// Added, copied, modified, renamed and type-changed paths.
+    #[default]
+    Changed,
+    /// Only newly added paths.
+    Added,
+}
+
+impl DiffFilter {
+    fn as_arg(self) -> &'static str {
+        match self {
+            DiffFilter::Changed => "--diff-filter=ACMRT",
+            DiffFilter::Added => "--diff-filter=A",
+        }
+    }
+}
+
+/// Commit date bounds for history walks, passed to `git rev-list` as
+/// `--since` and `--until`. Dates use any format git accepts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitWindow {
+    pub since: Option<String>,
+    pub until: Option<String>,
+}
+
+impl CommitWindow {
+    fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(since) = &self.since {
+            args.push(format!("--since={since}"));
+        }
+        if let Some(until) = &self.until {
+            args.push(format!("--until={until}"));
+        }
+        args
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct HistoryBlob {
     pub path: String,
@@ -61,13 +135,28 @@ struct BlobEntry {
 
 pub struct GitRepo {
     root: PathBuf,
+    // Most `git diff-tree` processes run at once; all CPUs when unset
+    jobs: Option<usize>,
+    layout: GitLayout,
 }
 
 impl GitRepo {
     pub fn discover<P: AsRef<Path>>(start_path: P) -> Result<Self> {
-        let path = start_path.as_ref();
+        Self::discover_with_layout(start_path, GitLayout::default())
+    }
 
-        let output = Command::new("git")
+    /// Like [`GitRepo::discover`], locating the repository through `layout`,
+    /// which is also kept for every later git command.
+    pub fn discover_with_layout<P: AsRef<Path>>(start_path: P, layout: GitLayout) -> Result<Self> {
+        let path = start_path.as_ref();
+        let repo = GitRepo {
+            root: path.to_path_buf(),
+            jobs: None,
+            layout,
+        };
+
+        let output = repo
+            .git_command()
             .arg("rev-parse")
             .arg("--show-toplevel")
             .current_dir(path)
@@ -84,27 +173,87 @@ impl GitRepo {
 
         Ok(GitRepo {
             root: PathBuf::from(root),
+            ..repo
         })
     }
 
+    /// Pass `layout` to every later git command as `GIT_DIR` and
+    /// `GIT_WORK_TREE`.
+    pub fn with_layout(mut self, layout: GitLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Bound the `git diff-tree` processes of history walks to `jobs`
+    /// instead of one per CPU.
+    pub fn with_jobs(mut self, jobs: Option<usize>) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
     pub fn root(&self) -> &Path {
         &self.root
     }
 
-    pub fn get_staged_files(&self) -> Result<Vec<PathBuf>> {
-        let command = "git diff --staged --name-only --diff-filter=ACMRT";
-        let output = self.exec(&["diff", "--staged", "--name-only", "--diff-filter=ACMRT"])?;
+    /// A `git` command with this repository's layout in its environment.
+    fn git_command(&self) -> Command {
+        let mut command = Command::new("git");
+        if let Some(git_dir) = &self.layout.git_dir {
+            command.env("GIT_DIR", git_dir);
+        }
+        if let Some(work_tree) = &self.layout.work_tree {
+            command.env("GIT_WORK_TREE", work_tree);
+        }
+        command
+    }
+
+    /// Absolute path of the `.git` directory.
+    pub fn git_dir(&self) -> Result<PathBuf> {
+        let command = "git rev-parse --absolute-git-dir";
+        let output = self.exec(&["rev-parse", "--absolute-git-dir"])?;
 
         if !output.status.success() {
             return Err(self.command_failed(command, &output));
         }
 
+        Ok(PathBuf::from(
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        ))
+    }
+
+    /// Whether HEAD points at a commit, which it does not in a freshly
+    /// initialized repository.
+    pub fn has_commits(&self) -> Result<bool> {
+        let output = self.exec(&["rev-parse", "--verify", "--quiet", "HEAD^{commit}"])?;
+        Ok(output.status.success())
+    }
+
+    pub fn get_staged_files(&self, filter: DiffFilter) -> Result<Vec<PathBuf>> {
+        let command = format!("git diff --staged --name-only {}", filter.as_arg());
+        let output = self.exec(&["diff", "--staged", "--name-only", filter.as_arg()])?;
+
+        if !output.status.success() {
+            return Err(self.command_failed(&command, &output));
+        }
+
         Ok(self.parse_paths(&output.stdout))
     }
 
-    pub fn get_working_tree_files(&self) -> Result<Vec<PathBuf>> {
-        let command = "git diff --name-only --diff-filter=ACMRT";
-        let output = self.exec(&["diff", "--name-only", "--diff-filter=ACMRT"])?;
+    pub fn get_working_tree_files(&self, filter: DiffFilter) -> Result<Vec<PathBuf>> {
+        let command = format!("git diff --name-only {}", filter.as_arg());
+        let output = self.exec(&["diff", "--name-only", filter.as_arg()])?;
+
+        if !output.status.success() {
+            return Err(self.command_failed(&command, &output));
+        }
+
+        Ok(self.parse_paths(&output.stdout))
+    }
+
+    /// Untracked files that are not ignored (`git ls-files --others --exclude-standard`).
+    pub fn get_untracked_files(&self) -> Result<Vec<PathBuf>> {
+        let command = "git ls-files --others --exclude-standard";
+        let output = self.exec(&["ls-files", "--others", "--exclude-standard"])?;
 
         if !output.status.success() {
             return Err(self.command_failed(command, &output));
@@ -116,7 +265,8 @@ impl GitRepo {
     /// Count the number of commits in a range.
     pub fn count_commits_in_range(&self, range: &str) -> Result<usize> {
         let expanded = self.expand_git_range(range)?;
-        let output = Command::new("git")
+        let output = self
+            .git_command()
             .args(["rev-list", "--count"])
             .arg(&expanded)
             .current_dir(&self.root)
@@ -144,7 +294,8 @@ impl GitRepo {
         }
 
         // Verify the ref exists before trying merge-base
-        let verify = Command::new("git")
+        let verify = self
+            .git_command()
             .args(["rev-parse", "--verify", &format!("{range}^{{commit}}")])
             .current_dir(&self.root)
             .output()
@@ -158,7 +309,8 @@ impl GitRepo {
         }
 
         let command = format!("git merge-base {range} HEAD");
-        let output = Command::new("git")
+        let output = self
+            .git_command()
             .args(["merge-base", range, "HEAD"])
             .current_dir(&self.root)
             .output()
@@ -172,14 +324,25 @@ impl GitRepo {
         Ok(format!("{merge_base}..HEAD"))
     }
 
-    pub fn get_diff_files(&self, range: &str) -> Result<Vec<PathBuf>> {
+    /// Whether `range` is a bare ref whose merge-base with HEAD is HEAD
+    /// itself, as for `--git main` while on `main`, so the range is empty.
+    pub fn base_is_head(&self, range: &str) -> Result<bool> {
+        if range.contains("..") {
+            return Ok(false);
+        }
         let expanded = self.expand_git_range(range)?;
-        let command = format!("git diff --name-only --diff-filter=ACMRT {expanded}");
+        Ok(self.count_commits_in_range(&expanded)? == 0)
+    }
 
-        let output = Command::new("git")
+    pub fn get_diff_files(&self, range: &str, filter: DiffFilter) -> Result<Vec<PathBuf>> {
+        let expanded = self.expand_git_range(range)?;
+        let command = format!("git diff --name-only {} {expanded}", filter.as_arg());
+
+        let output = self
+            .git_command()
             .arg("diff")
             .arg("--name-only")
-            .arg("--diff-filter=ACMRT")
+            .arg(filter.as_arg())
             .arg(&expanded)
             .current_dir(&self.root)
             .output()
@@ -192,14 +355,14 @@ impl GitRepo {
         Ok(self.parse_paths(&output.stdout))
     }
 
-    fn rev_list_commits(&self, expanded_range: &str) -> Result<Vec<String>> {
-        let command = format!("git rev-list --no-merges {expanded_range}");
-        let output = Command::new("git")
-            .args(["rev-list", "--no-merges"])
-            .arg(expanded_range)
-            .current_dir(&self.root)
-            .output()
-            .map_err(GitError::Exec)?;
+    fn rev_list_commits(&self, expanded_range: &str, window: &CommitWindow) -> Result<Vec<String>> {
+        self.check_window(window)?;
+        let window_args = window.args();
+        let mut args = vec!["rev-list", "--no-merges"];
+        args.extend(window_args.iter().map(String::as_str));
+        args.push(expanded_range);
+        let command = format!("git {}", args.join(" "));
+        let output = self.exec(&args)?;
 
         if !output.status.success() {
             return Err(self.command_failed(&command, &output));
@@ -212,18 +375,49 @@ impl GitRepo {
             .collect())
     }
 
+    /// Reject windows whose start lies after their end, comparing the
+    /// timestamps git itself resolves the dates to.
+    fn check_window(&self, window: &CommitWindow) -> Result<()> {
+        let (Some(since), Some(until)) = (&window.since, &window.until) else {
+            return Ok(());
+        };
+        if self.date_timestamp(since)? > self.date_timestamp(until)? {
+            return Err(GitError::ReversedWindow {
+                since: since.clone(),
+                until: until.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Seconds since the epoch for a git date such as `2024-01-01` or
+    /// `2 weeks ago`, via `git rev-parse --since`.
+    fn date_timestamp(&self, date: &str) -> Result<i64> {
+        let arg = format!("--since={date}");
+        let command = format!("git rev-parse {arg}");
+        let output = self.exec(&["rev-parse", &arg])?;
+        if !output.status.success() {
+            return Err(self.command_failed(&command, &output));
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        stdout
+            .trim()
+            .strip_prefix("--max-age=")
+            .and_then(|ts| ts.parse().ok())
+            .ok_or_else(|| GitError::CommandFailed {
+                command,
+                exit_code: 0,
+                stderr: format!("unexpected output: {}", stdout.trim()),
+            })
+    }
+
     /// Spawn a single `git diff-tree -r --stdin` process fed with commit hashes,
     /// parse the raw diff output into `BlobEntry` values.
     /// Skips submodule entries (mode 160000).
-    fn diff_tree_entries(&self, commits: &[String]) -> Result<Vec<BlobEntry>> {
-        let mut child = Command::new("git")
-            .args([
-                "diff-tree",
-                "-r",
-                "--root",
-                "--stdin",
-                "--diff-filter=ACMRT",
-            ])
+    fn diff_tree_entries(&self, commits: &[String], filter: DiffFilter) -> Result<Vec<BlobEntry>> {
+        let mut child = self
+            .git_command()
+            .args(["diff-tree", "-r", "--root", "--stdin", filter.as_arg()])
             .current_dir(&self.root)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
@@ -289,35 +483,53 @@ impl GitRepo {
         Ok(entries)
     }
 
-    /// Skips merges and submodule entries (mode 160000).
-    /// Parallelizes tree-diffing across available CPU cores.
-    fn collect_history_entries(&self, range: &str) -> Result<Vec<BlobEntry>> {
-        let expanded = self.expand_git_range(range)?;
-        let commits = self.rev_list_commits(&expanded)?;
+    /// Non-merge commits in `range` and `window`, or every one reachable
+    /// from HEAD when there is no range.
+    fn history_commits(&self, range: Option<&str>, window: &CommitWindow) -> Result<Vec<String>> {
+        match range {
+            Some(range) => {
+                let expanded = self.expand_git_range(range)?;
+                self.rev_list_commits(&expanded, window)
+            }
+            None => self.rev_list_commits("HEAD", window),
+        }
+    }
 
+    /// Added/modified blob entries for each of `commits`, diffed in parallel.
+    fn commit_entries(&self, commits: &[String], filter: DiffFilter) -> Result<Vec<BlobEntry>> {
         if commits.is_empty() {
             return Ok(Vec::new());
         }
 
-        let num_cpus = std::thread::available_parallelism()
-            .map(|n| n.get())
-            .unwrap_or(1);
-        let chunk_size = commits.len().div_ceil(num_cpus).max(1);
-
-        let chunks: Vec<&[String]> = commits.chunks(chunk_size).collect();
-        let results: Result<Vec<Vec<BlobEntry>>> = chunks
+        let jobs = self.jobs.unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        });
+        let results: Result<Vec<Vec<BlobEntry>>> = commit_chunks(commits, jobs)
             .into_par_iter()
-            .map(|chunk| self.diff_tree_entries(chunk))
+            .map(|chunk| self.diff_tree_entries(chunk, filter))
             .collect();
 
         Ok(results?.into_iter().flatten().collect())
     }
 
-    /// Resolve blob sizes in batch via a single `git cat-file --batch-check`
-    /// process instead of spawning one process per blob.
-    fn batch_blob_sizes(&self, entries: &[BlobEntry]) -> Result<Vec<u64>> {
-        let mut child = Command::new("git")
-            .args(["cat-file", "--batch-check"])
+    /// Feed `objects` to a single `git cat-file --batch-check` process.
+    fn batch_check(&self, objects: Vec<String>) -> Result<std::process::Output> {
+        self.exec_with_stdin(&["cat-file", "--batch-check"], objects, b'\n')
+    }
+
+    /// Run `git <args>`, writing each of `records` followed by `terminator`
+    /// to its stdin.
+    fn exec_with_stdin(
+        &self,
+        args: &[&str],
+        records: Vec<String>,
+        terminator: u8,
+    ) -> Result<std::process::Output> {
+        let mut child = self
+            .git_command()
+            .args(args)
             .current_dir(&self.root)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
@@ -325,15 +537,15 @@ impl GitRepo {
             .spawn()
             .map_err(GitError::Exec)?;
 
-        // Write hashes on a separate thread to avoid deadlock: with many
-        // blobs the stdout pipe buffer fills while we're still writing to
+        // Write records on a separate thread to avoid deadlock: with many
+        // records the stdout pipe buffer fills while we're still writing to
         // stdin, blocking both sides.
         let stdin = child.stdin.take().unwrap();
-        let hashes: Vec<String> = entries.iter().map(|e| e.blob_hash.clone()).collect();
         let writer_thread = std::thread::spawn(move || -> std::io::Result<()> {
             let mut writer = std::io::BufWriter::new(stdin);
-            for hash in &hashes {
-                writeln!(writer, "{hash}")?;
+            for record in &records {
+                writer.write_all(record.as_bytes())?;
+                writer.write_all(&[terminator])?;
             }
             Ok(())
         });
@@ -345,9 +557,65 @@ impl GitRepo {
             .map_err(GitError::Exec)?;
 
         if !output.status.success() {
-            return Err(self.command_failed("git cat-file --batch-check", &output));
+            return Err(self.command_failed(&format!("git {}", args.join(" ")), &output));
         }
 
+        Ok(output)
+    }
+
+    /// Values of the gitattribute `attr` for each of `paths`, keyed by the
+    /// path as given. Paths where the attribute is unspecified, set or unset
+    /// without a value are omitted, as are paths outside the repository.
+    pub fn check_attr(&self, attr: &str, paths: &[PathBuf]) -> Result<HashMap<PathBuf, String>> {
+        let requested: HashMap<String, &PathBuf> = paths
+            .iter()
+            .filter_map(|p| {
+                let rel = self.repo_relative(p)?;
+                Some((rel.to_string_lossy().to_string(), p))
+            })
+            .collect();
+        if requested.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let rel_paths: Vec<String> = requested.keys().cloned().collect();
+        let output =
+            self.exec_with_stdin(&["check-attr", "-z", "--stdin", attr], rel_paths, b'\0')?;
+
+        // Output records: "<path>\0<attr>\0<value>\0"
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let fields: Vec<&str> = stdout.split('\0').collect();
+        Ok(fields
+            .chunks_exact(3)
+            .filter(|record| !matches!(record[2], "unspecified" | "set" | "unset"))
+            .filter_map(|record| {
+                let path = requested.get(record[0])?;
+                Some(((*path).clone(), record[2].to_string()))
+            })
+            .collect())
+    }
+
+    /// `path` relative to the repository root, resolving relative paths
+    /// against the current directory and symlinked parent directories the
+    /// way `git rev-parse --show-toplevel` does. `None` outside the root.
+    fn repo_relative(&self, path: &Path) -> Option<PathBuf> {
+        let absolute = std::path::absolute(path).ok()?;
+        let resolved = match (absolute.parent(), absolute.file_name()) {
+            (Some(parent), Some(name)) => std::fs::canonicalize(parent).ok()?.join(name),
+            _ => absolute,
+        };
+        resolved
+            .strip_prefix(&self.root)
+            .ok()
+            .map(Path::to_path_buf)
+    }
+
+    /// Resolve blob sizes in batch via a single `git cat-file --batch-check`
+    /// process instead of spawning one process per blob.
+    fn batch_blob_sizes(&self, entries: &[BlobEntry]) -> Result<Vec<u64>> {
+        let hashes: Vec<String> = entries.iter().map(|e| e.blob_hash.clone()).collect();
+        let output = self.batch_check(hashes)?;
+
         // Each output line: "<hash> <type> <size>" or "<hash> missing"
         let stdout = String::from_utf8_lossy(&output.stdout);
         stdout
@@ -378,12 +646,137 @@ impl GitRepo {
             .collect()
     }
 
-    /// Walk every commit in the range and collect all added/modified blobs.
+    /// Sizes of `paths` as stored at `rev`. Paths that do not exist at `rev`
+    /// are absent from the result.
+    pub fn blob_sizes_at(&self, rev: &str, paths: &[PathBuf]) -> Result<HashMap<PathBuf, u64>> {
+        if paths.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let objects: Vec<String> = paths
+            .iter()
+            .map(|p| {
+                let rel = p.strip_prefix(&self.root).unwrap_or(p);
+                format!("{rev}:{}", rel.to_string_lossy())
+            })
+            .collect();
+        let output = self.batch_check(objects)?;
+
+        // Each output line: "<hash> blob <size>" or "<rev>:<path> missing"
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout
+            .lines()
+            .zip(paths)
+            .filter_map(|(line, path)| {
+                if line.ends_with(" missing") {
+                    return None;
+                }
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                match parts.as_slice() {
+                    [_, "blob", size] => size.parse().ok().map(|size| (path.clone(), size)),
+                    _ => None,
+                }
+            })
+            .collect())
+    }
+
+    /// The commit a range is compared against: the left side of `A..B`, or
+    /// the merge-base of `A...B`.
+    pub fn range_base(&self, range: &str) -> Result<String> {
+        let expanded = self.expand_git_range(range)?;
+
+        if let Some((left, right)) = expanded.split_once("...") {
+            let left = if left.is_empty() { "HEAD" } else { left };
+            let right = if right.is_empty() { "HEAD" } else { right };
+            let command = format!("git merge-base {left} {right}");
+            let output = self.exec(&["merge-base", left, right])?;
+            if !output.status.success() {
+                return Err(self.command_failed(&command, &output));
+            }
+            return Ok(String::from_utf8_lossy(&output.stdout).trim().to_string());
+        }
+
+        match expanded.split_once("..") {
+            Some(("", _)) => Ok("HEAD".to_string()),
+            Some((left, _)) => Ok(left.to_string()),
+            None => Ok(expanded),
+        }
+    }
+
+    /// Walk every commit in the range and `window` and collect all
+    /// added/modified blobs.
     /// Uses `git rev-list` + parallel `git diff-tree --stdin` workers +
     /// single `git cat-file --batch-check`.
-    pub fn walk_history_blobs(&self, range: &str) -> Result<Vec<HistoryBlob>> {
-        let entries = self.collect_history_entries(range)?;
+    pub fn walk_history_blobs(
+        &self,
+        range: &str,
+        filter: DiffFilter,
+        window: &CommitWindow,
+    ) -> Result<Vec<HistoryBlob>> {
+        let commits = self.history_commits(Some(range), window)?;
+        let entries = self.commit_entries(&commits, filter)?;
+        self.history_blobs(entries)
+    }
+
+    /// Walk every commit reachable from HEAD within `window` and collect all
+    /// added/modified blobs.
+    pub fn walk_all_history_blobs(
+        &self,
+        filter: DiffFilter,
+        window: &CommitWindow,
+    ) -> Result<Vec<HistoryBlob>> {
+        let commits = self.history_commits(None, window)?;
+        let entries = self.commit_entries(&commits, filter)?;
+        self.history_blobs(entries)
+    }
+
+    /// Walk history like [`walk_history_blobs`](Self::walk_history_blobs),
+    /// or every commit reachable from HEAD without a `range`, handing the
+    /// blobs of each `batch_commits` commits to `each` once their sizes are
+    /// known. Only one batch is held in memory at a time.
+    pub fn walk_history_blob_batches<E: From<GitError>>(
+        &self,
+        range: Option<&str>,
+        filter: DiffFilter,
+        window: &CommitWindow,
+        batch_commits: usize,
+        mut each: impl FnMut(Vec<HistoryBlob>) -> std::result::Result<(), E>,
+    ) -> std::result::Result<(), E> {
+        let commits = self.history_commits(range, window)?;
+        for batch in commits.chunks(batch_commits.max(1)) {
+            let entries = self.commit_entries(batch, filter)?;
+            each(self.history_blobs(entries)?)?;
+        }
+        Ok(())
+    }
+
+    /// Collect the added/modified blobs of exactly `commits`, rather than
+    /// of every commit in a range.
+    pub fn commit_blobs(&self, commits: &[String], filter: DiffFilter) -> Result<Vec<HistoryBlob>> {
+        let mut resolved = Vec::with_capacity(commits.len());
+        for commit in commits {
+            let hash = self.resolve_commit(commit)?;
+            if !resolved.contains(&hash) {
+                resolved.push(hash);
+            }
+        }
+        let entries = self.commit_entries(&resolved, filter)?;
+        self.history_blobs(entries)
+    }
+
+    /// Full hash of the commit `rev` names, as `git diff-tree --stdin` needs.
+    fn resolve_commit(&self, rev: &str) -> Result<String> {
+        let output = self.exec(&["rev-parse", "--verify", &format!("{rev}^{{commit}}")])?;
+        if !output.status.success() {
+            return Err(GitError::RefNotFound {
+                git_ref: rev.to_string(),
+                repo: self.root.clone(),
+            });
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
 
+    fn history_blobs(&self, entries: Vec<BlobEntry>) -> Result<Vec<HistoryBlob>> {
         if entries.is_empty() {
             return Ok(vec![]);
         }
@@ -401,8 +794,116 @@ impl GitRepo {
             .collect())
     }
 
+    /// Total size of tracked blobs at HEAD, grouped by top-level directory.
+    ///
+    /// Files at the repository root are grouped under `.`.
+    pub fn tree_sizes_by_dir(&self) -> Result<BTreeMap<String, u64>> {
+        let mut sizes = BTreeMap::new();
+        for (path, size) in self.tree_blob_sizes("HEAD")? {
+            let dir = match path.split_once('/') {
+                Some((top, _)) => top,
+                None => ".",
+            };
+            *sizes.entry(dir.to_string()).or_insert(0) += size;
+        }
+
+        Ok(sizes)
+    }
+
+    /// Every blob in the tree at `rev` with its size, without checking it out.
+    /// Paths are absolute under the repository root, as in
+    /// [`walk_history_blobs`](Self::walk_history_blobs).
+    pub fn tree_blobs_at(&self, rev: &str) -> Result<Vec<HistoryBlob>> {
+        Ok(self
+            .tree_blob_sizes(rev)?
+            .into_iter()
+            .map(|(path, size)| HistoryBlob {
+                path: self.root.join(path).to_string_lossy().to_string(),
+                size,
+                commit: rev.to_string(),
+            })
+            .collect())
+    }
+
+    /// Total size of all blobs in the tree at `rev`.
+    pub fn tree_size(&self, rev: &str) -> Result<u64> {
+        Ok(self
+            .tree_blob_sizes(rev)?
+            .iter()
+            .map(|(_, size)| size)
+            .sum())
+    }
+
+    /// Repo-relative path and size of every blob in the tree at `rev`.
+    fn tree_blob_sizes(&self, rev: &str) -> Result<Vec<(String, u64)>> {
+        let command = format!("git ls-tree -r -l -z {rev}");
+        let output = self.exec(&["ls-tree", "-r", "-l", "-z", rev])?;
+
+        if !output.status.success() {
+            return Err(self.command_failed(&command, &output));
+        }
+
+        let mut blobs = Vec::new();
+        // Each entry: "<mode> <type> <object> <size>\t<path>"
+        for entry in String::from_utf8_lossy(&output.stdout).split('\0') {
+            let Some((meta, path)) = entry.split_once('\t') else {
+                continue;
+            };
+            let parts: Vec<&str> = meta.split_whitespace().collect();
+            let [_, "blob", _, size] = parts.as_slice() else {
+                continue;
+            };
+            let Ok(size) = size.parse::<u64>() else {
+                continue;
+            };
+            blobs.push((path.to_string(), size));
+        }
+
+        Ok(blobs)
+    }
+
+    /// Paths from `paths` that are tracked in the index.
+    pub fn filter_tracked(&self, paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+        let outputs = self.chunked_exec(&["ls-files", "-z", "--"], paths)?;
+
+        let mut tracked = Vec::new();
+        for output in outputs {
+            tracked.extend(
+                String::from_utf8_lossy(&output.stdout)
+                    .split('\0')
+                    .filter(|p| !p.is_empty())
+                    .map(|p| self.root.join(p)),
+            );
+        }
+        Ok(tracked)
+    }
+
+    /// Run `git <args> <paths...>`, splitting `paths` across as many
+    /// invocations as needed to stay under the command-line length limit.
+    fn chunked_exec(&self, args: &[&str], paths: &[PathBuf]) -> Result<Vec<std::process::Output>> {
+        let command = format!("git {}", args.join(" "));
+        let mut outputs = Vec::new();
+
+        for chunk in chunk_paths(paths, MAX_PATH_ARGS_LEN) {
+            let output = self
+                .git_command()
+                .args(args)
+                .args(chunk)
+                .current_dir(&self.root)
+                .output()
+                .map_err(GitError::Exec)?;
+
+            if !output.status.success() {
+                return Err(self.command_failed(&command, &output));
+            }
+            outputs.push(output);
+        }
+
+        Ok(outputs)
+    }
+
     fn exec(&self, args: &[&str]) -> Result<std::process::Output> {
-        Command::new("git")
+        self.git_command()
             .args(args)
             .current_dir(&self.root)
             .output()
@@ -426,6 +927,37 @@ impl GitRepo {
     }
 }
 
+/// Split `commits` into at most `jobs` chunks of similar size, one per
+/// `git diff-tree` process.
+fn commit_chunks(commits: &[String], jobs: usize) -> Vec<&[String]> {
+    let chunk_size = commits.len().div_ceil(jobs.max(1)).max(1);
+    commits.chunks(chunk_size).collect()
+}
+
+/// Split `paths` into consecutive chunks whose combined length (plus one
+/// separator per path) does not exceed `max_len`. A single path longer than
+/// `max_len` gets a chunk of its own.
+fn chunk_paths(paths: &[PathBuf], max_len: usize) -> Vec<&[PathBuf]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut len = 0;
+
+    for (i, path) in paths.iter().enumerate() {
+        let path_len = path.as_os_str().len() + 1;
+        if i > start && len + path_len > max_len {
+            chunks.push(&paths[start..i]);
+            start = i;
+            len = 0;
+        }
+        len += path_len;
+    }
+    if start < paths.len() {
+        chunks.push(&paths[start..]);
+    }
+
+    chunks
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -468,6 +1000,68 @@ mod tests {
         (tmp, repo)
     }
 
+    #[test]
+    fn test_layouts_are_kept_per_repo() {
+        let (_tmp, plain) = setup_test_repo();
+        let (_other_tmp, other) = setup_test_repo();
+        let other_git_dir = other.git_dir().unwrap();
+
+        let layout = GitLayout {
+            git_dir: Some(other_git_dir.clone()),
+            work_tree: Some(other.root().to_path_buf()),
+        };
+        let redirected = GitRepo::discover_with_layout(plain.root(), layout).unwrap();
+
+        assert_eq!(redirected.root(), other.root());
+        assert_eq!(redirected.git_dir().unwrap(), other_git_dir);
+        // Discovering with a layout leaves other repositories untouched
+        assert_ne!(plain.git_dir().unwrap(), other_git_dir);
+        assert_eq!(
+            GitRepo::discover(plain.root()).unwrap().root(),
+            plain.root()
+        );
+    }
+
+    #[test]
+    fn test_commit_chunks_bounded_by_jobs() {
+        let commits: Vec<String> = (0..10).map(|i| format!("{i:040}")).collect();
+
+        assert_eq!(commit_chunks(&commits, 1), vec![&commits[..]]);
+        assert_eq!(commit_chunks(&commits, 0).len(), 1);
+        assert_eq!(commit_chunks(&commits, 4).len(), 4);
+        assert_eq!(commit_chunks(&commits, 32).len(), 10);
+        assert_eq!(commit_chunks(&commits, 3).concat(), commits);
+    }
+
+    #[test]
+    fn test_chunk_paths_covers_all_paths_within_limit() {
+        let paths: Vec<PathBuf> = (0..10_000)
+            .map(|i| PathBuf::from(format!("some/deeply/nested/directory/file_{i}.txt")))
+            .collect();
+
+        let chunks = chunk_paths(&paths, MAX_PATH_ARGS_LEN);
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            let len: usize = chunk.iter().map(|p| p.as_os_str().len() + 1).sum();
+            assert!(len <= MAX_PATH_ARGS_LEN);
+        }
+
+        let rejoined: Vec<PathBuf> = chunks.concat();
+        assert_eq!(rejoined, paths);
+    }
+
+    #[test]
+    fn test_chunk_paths_oversized_path_gets_own_chunk() {
+        let paths = vec![
+            PathBuf::from("a"),
+            PathBuf::from("b".repeat(50)),
+            PathBuf::from("c"),
+        ];
+        let chunks = chunk_paths(&paths, 10);
+        assert_eq!(chunks.len(), 3);
+        assert!(chunk_paths(&[], 10).is_empty());
+    }
+
     #[test]
     #[ignore = "requires git binary"]
     fn test_expand_git_range_bare_ref() {
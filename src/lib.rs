@@ -1,10 +1,14 @@
 pub mod app;
+pub mod archive;
+pub mod baseline;
 pub mod cli;
 pub mod config;
 pub mod discovery;
 pub mod error;
+pub mod fs;
 pub mod git;
 pub mod log;
+pub mod manifest;
 pub mod output;
 pub mod rules;
 
@@ -3,8 +3,10 @@ use tracing_subscriber::{FmtSubscriber, filter::EnvFilter};
 
 const LOG_ENV: &str = "SIZELINT_LOG";
 
-pub fn init(debug: bool, quiet: bool) -> Result<(), Box<dyn std::error::Error>> {
-    let default_level = if debug {
+pub fn init(debug: bool, verbose: u8, quiet: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let default_level = if verbose >= 2 {
+        Level::TRACE
+    } else if debug || verbose == 1 {
         Level::DEBUG
     } else if quiet {
         Level::WARN
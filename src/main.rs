@@ -1,11 +1,13 @@
 use clap::Parser;
+use sizelint::app::EXIT_INTERRUPTED;
 use sizelint::{App, Cli};
 use std::process::ExitCode;
+use std::sync::Arc;
 
 fn main() -> ExitCode {
     let cli = Cli::parse();
 
-    if let Err(e) = sizelint::log::init(cli.debug, cli.get_quiet()) {
+    if let Err(e) = sizelint::log::init(cli.debug, cli.verbose, cli.get_quiet()) {
         eprintln!("Failed to initialize logging: {e}");
         return ExitCode::FAILURE;
     }
@@ -20,6 +22,17 @@ fn main() -> ExitCode {
         }
     };
 
+    let app = Arc::new(app);
+    let handler_app = Arc::clone(&app);
+    if let Err(e) = ctrlc::set_handler(move || {
+        if let Err(e) = handler_app.print_partial_results() {
+            eprintln!("{:?}", miette::Report::new(e));
+        }
+        std::process::exit(EXIT_INTERRUPTED);
+    }) {
+        tracing::debug!("Failed to install interrupt handler: {e}");
+    }
+
     match app.run() {
         Ok(code) => {
             tracing::debug!("Sizelint completed");
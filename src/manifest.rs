@@ -0,0 +1,200 @@
+use crate::error::{Result, SizelintError};
+use crate::rules::{Severity, Violation};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Size and optional content hash of one checked file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+/// One entry per file in `paths`, sorted by path. Paths are reported
+/// relative to `base`; hashing reads every file, so it is opt-in.
+pub fn build_manifest(paths: &[PathBuf], base: &Path, hash: bool) -> Result<Vec<ManifestEntry>> {
+    let entries: Result<Vec<_>> = paths
+        .par_iter()
+        .map(|path| {
+            let size = std::fs::metadata(path)
+                .map_err(|e| {
+                    SizelintError::filesystem("get file metadata".to_string(), path.clone(), e)
+                })?
+                .len();
+            let sha256 = if hash { Some(sha256_file(path)?) } else { None };
+
+            Ok(ManifestEntry {
+                path: path
+                    .strip_prefix(base)
+                    .unwrap_or(path)
+                    .display()
+                    .to_string(),
+                size,
+                sha256,
+            })
+        })
+        .collect();
+
+    let mut entries = entries?;
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+/// Paths listed in a committed manifest, either one per line (blank lines
+/// and `#` comments skipped) or as JSON: an array of path strings, or of
+/// entries as written by [`build_manifest`]. Relative paths are resolved
+/// against the manifest's directory.
+pub fn read_path_list(manifest: &Path) -> Result<Vec<PathBuf>> {
+    let content = std::fs::read_to_string(manifest)
+        .map_err(|e| SizelintError::filesystem("read".to_string(), manifest.to_path_buf(), e))?;
+
+    let listed: Vec<String> = if content.trim_start().starts_with('[') {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Listed {
+            Path(String),
+            Entry { path: String },
+        }
+        serde_json::from_str::<Vec<Listed>>(&content)?
+            .into_iter()
+            .map(|listed| match listed {
+                Listed::Path(path) | Listed::Entry { path } => path,
+            })
+            .collect()
+    } else {
+        content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect()
+    };
+
+    let base = manifest.parent().unwrap_or(Path::new(""));
+    Ok(listed.into_iter().map(|path| base.join(path)).collect())
+}
+
+/// One error per listed path that does not exist.
+pub fn missing_file_violations(missing: &[PathBuf]) -> Vec<Violation> {
+    missing
+        .iter()
+        .map(|path| {
+            Violation::new(
+                path.clone(),
+                "from_manifest".to_string(),
+                "File listed in manifest does not exist".to_string(),
+                Severity::Error,
+            )
+            .with_actual_value("missing".to_string())
+            .with_expected_value("present".to_string())
+        })
+        .collect()
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path)
+        .map_err(|e| SizelintError::filesystem("open".to_string(), path.to_path_buf(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .map_err(|e| SizelintError::filesystem("read".to_string(), path.to_path_buf(), e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_lists_all_files_with_stable_hashes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let empty = tmp.path().join("empty.txt");
+        let data = tmp.path().join("data/blob.bin");
+        std::fs::create_dir_all(tmp.path().join("data")).unwrap();
+        std::fs::write(&empty, "").unwrap();
+        std::fs::write(&data, vec![7u8; 4096]).unwrap();
+        let paths = vec![empty, data];
+
+        let manifest = build_manifest(&paths, tmp.path(), false).unwrap();
+        assert_eq!(
+            manifest,
+            vec![
+                ManifestEntry {
+                    path: "data/blob.bin".to_string(),
+                    size: 4096,
+                    sha256: None,
+                },
+                ManifestEntry {
+                    path: "empty.txt".to_string(),
+                    size: 0,
+                    sha256: None,
+                },
+            ]
+        );
+
+        let hashed = build_manifest(&paths, tmp.path(), true).unwrap();
+        assert_eq!(hashed, build_manifest(&paths, tmp.path(), true).unwrap());
+        assert_eq!(
+            hashed[1].sha256.as_deref(),
+            Some("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+    }
+
+    #[test]
+    fn test_path_list_flags_oversized_and_missing_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(tmp.path().join("big.bin"), vec![0u8; 2048]).unwrap();
+        std::fs::write(tmp.path().join("small.txt"), "ok").unwrap();
+        let list = tmp.path().join("assets.txt");
+        std::fs::write(&list, "# expected assets\nbig.bin\n\nsmall.txt\ngone.bin\n").unwrap();
+        let json = tmp.path().join("assets.json");
+        std::fs::write(
+            &json,
+            r#"["big.bin", {"path": "small.txt", "size": 2}, "gone.bin"]"#,
+        )
+        .unwrap();
+
+        let paths = read_path_list(&list).unwrap();
+        assert_eq!(read_path_list(&json).unwrap(), paths);
+        let (present, missing): (Vec<_>, Vec<_>) = paths.into_iter().partition(|p| p.exists());
+
+        let mut engine = crate::rules::RuleEngine::new();
+        engine.add_rule(
+            crate::rules::ConfigurableRule::new(
+                "default".to_string(),
+                crate::config::RuleDefinition {
+                    enabled: true,
+                    max_size: Some("1KB".parse().unwrap()),
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+        );
+        let violations = engine.check_files(&present).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, tmp.path().join("big.bin"));
+
+        let missing = missing_file_violations(&missing);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].path, tmp.path().join("gone.bin"));
+        assert_eq!(missing[0].severity, Severity::Error);
+    }
+}
@@ -1,21 +1,176 @@
 use crate::cli::OutputFormat;
 use crate::error::Result;
-use crate::rules::{Severity, Violation};
+use crate::rules::{PassedFile, Severity, Violation, format_size, format_size_dual};
 use colored::*;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
+/// Siblings listed next to a violation with `with_sibling_context`.
+const SIBLING_CONTEXT: usize = 3;
+
+/// Directory entries read when looking for siblings, so huge directories
+/// stay cheap.
+const SIBLING_SCAN_LIMIT: usize = 1000;
+
+/// `OutputSummary` layouts that `with_schema_version` can select.
+pub const JSON_SCHEMA_VERSIONS: &[u32] = &[1, 2];
+
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+/// Progress messages held back by [`defer_progress`], if deferring.
+static DEFERRED_PROGRESS: Mutex<Option<Vec<String>>> = Mutex::new(None);
+
+/// Status and decoration symbols used in human output.
+pub struct Symbols {
+    pub ok: &'static str,
+    pub error: &'static str,
+    pub warning: &'static str,
+    pub arrow: &'static str,
+    pub gutter: &'static str,
+    pub dash: &'static str,
+    pub rule: &'static str,
+}
+
+const UNICODE_SYMBOLS: Symbols = Symbols {
+    ok: "✓",
+    error: "✗",
+    warning: "!",
+    arrow: "→",
+    gutter: "┃",
+    dash: "—",
+    rule: "━",
+};
+
+const ASCII_SYMBOLS: Symbols = Symbols {
+    ok: "[OK]",
+    error: "[X]",
+    warning: "[!]",
+    arrow: "->",
+    gutter: "|",
+    dash: "-",
+    rule: "=",
+};
+
+/// Use ASCII symbols for all subsequent human output.
+pub fn set_ascii(ascii: bool) {
+    ASCII.store(ascii, Ordering::Relaxed);
+}
+
+/// The symbol set selected by [`set_ascii`].
+pub fn symbols() -> &'static Symbols {
+    symbols_for(ASCII.load(Ordering::Relaxed))
+}
+
+fn symbols_for(ascii: bool) -> &'static Symbols {
+    if ascii {
+        &ASCII_SYMBOLS
+    } else {
+        &UNICODE_SYMBOLS
+    }
+}
+
+/// Whether the locale from `LC_ALL`, `LC_CTYPE` or `LANG` (first one set)
+/// can display UTF-8. An unset locale is assumed to be capable.
+pub fn locale_supports_utf8() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))
+        .is_none_or(|locale| {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OutputSummary {
+    /// Layout of this summary, from version 2 on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u32>,
+    /// Random UUID identifying this run, with `with_metadata`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    /// RFC 3339 time the report was created, with `with_metadata`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
     pub total_files_checked: usize,
     pub total_violations: usize,
     pub error_count: usize,
     pub warning_count: usize,
     pub rules_run: Vec<String>,
     pub violations: Vec<ViolationOutput>,
+    /// Error and warning counts per rule, from version 2 on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub by_rule: Option<BTreeMap<String, RuleCounts>>,
+    /// Files listed by `with_passed_files`, from version 2 on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passed: Option<Vec<PassedOutput>>,
+    /// How the run exits, when given with `with_exit_status`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit: Option<ExitStatus>,
+}
+
+/// The exit code a check run ends with, and why.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExitStatus {
+    pub code: u8,
+    pub reason: ExitReason,
+    /// Whether warnings fail the run through `fail_on_warn`, rather than
+    /// only through rules with `warn_is_error`
+    #[serde(skip)]
+    pub fail_on_warn: bool,
+}
+
+impl ExitStatus {
+    /// The decisive reason a failing run fails, e.g. `Failed: 3 errors
+    /// (fail on error)`, or `None` when it passes.
+    fn failure_reason(&self, summary: &OutputSummary) -> Option<String> {
+        let count = |n: usize, noun: &str| format!("{n} {noun}{}", if n == 1 { "" } else { "s" });
+        match self.reason {
+            ExitReason::Errors => Some(format!(
+                "Failed: {} (fail on error)",
+                count(summary.error_count, "error")
+            )),
+            ExitReason::Warnings => Some(format!(
+                "Failed: {} ({})",
+                count(summary.warning_count, "warning"),
+                if self.fail_on_warn {
+                    "fail_on_warn enabled"
+                } else {
+                    "warn_is_error"
+                }
+            )),
+            ExitReason::Clean => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExitReason {
+    /// At least one error
+    Errors,
+    /// No errors, but warnings that fail the run
+    Warnings,
+    /// Nothing that fails the run
+    Clean,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuleCounts {
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PassedOutput {
+    pub path: String,
+    pub size: u64,
+    pub rule: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -26,16 +181,188 @@ pub struct ViolationOutput {
     pub severity: String,
     pub actual_value: Option<String>,
     pub expected_value: Option<String>,
+    /// `actual_value` in bytes, for size violations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actual_bytes: Option<u64>,
+    /// The threshold in `expected_value` in bytes, for size violations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_bytes: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub suggestion: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub commit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<String>,
+    /// Bytes over `limit_bytes`, zero for a file exactly at an exclusive limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overage_bytes: Option<u64>,
+    /// The size limit the file was measured against.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_bytes: Option<u64>,
+}
+
+impl ViolationOutput {
+    /// The JSON form of `violation`, reported under `path`.
+    pub fn new(violation: &Violation, path: String) -> Self {
+        Self {
+            path,
+            rule_name: violation.rule_name.clone(),
+            message: violation.message.clone(),
+            severity: severity_name(violation.severity).to_string(),
+            actual_value: violation.actual_value.clone(),
+            expected_value: violation.expected_value.clone(),
+            actual_bytes: violation.limit.map(|_| violation.sort_key),
+            expected_bytes: violation.limit,
+            suggestion: None,
+            commit: violation.commit.clone(),
+            range: violation.range.clone(),
+            overage_bytes: violation.overage(),
+            limit_bytes: violation.limit,
+        }
+    }
+}
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    }
+}
+
+/// Render a single violation the way `format` shows it, without headers or
+/// a summary: the `path (size, ...)` entry of human output, the compact
+/// line, a one-line JSON object, or a checkstyle `<error>` element. Paths
+/// are shown relative to `base_path`.
+pub fn format_violation(violation: &Violation, format: &OutputFormat, base_path: &Path) -> String {
+    let path = violation
+        .path
+        .strip_prefix(base_path)
+        .unwrap_or(&violation.path)
+        .display()
+        .to_string();
+    match format {
+        OutputFormat::Human => human_entry(violation, &path, false),
+        OutputFormat::Compact => compact_line(violation, &path),
+        OutputFormat::Json => serde_json::to_string(&ViolationOutput::new(violation, path))
+            .expect("ViolationOutput serializes to JSON"),
+        OutputFormat::Checkstyle => checkstyle_error(violation),
+    }
+}
+
+/// `path (12.0 MB, 2.0 MB over, commit abc123, range v1..v2)`, as listed in
+/// human output. With `dual_units`, sizes are shown in both unit families.
+fn human_entry(v: &Violation, path: &str, dual_units: bool) -> String {
+    let size_text = if dual_units {
+        format_size_dual
+    } else {
+        format_size
+    };
+    let mut details = Vec::new();
+    if let Some(actual) = &v.actual_value {
+        match v.limit {
+            Some(_) if dual_units => details.push(format_size_dual(v.sort_key)),
+            _ => details.push(actual.clone()),
+        }
+        if let Some(over) = v.overage().filter(|&over| over > 0) {
+            details.push(format!("{} over", size_text(over)).red().bold().to_string());
+        }
+    }
+    if let Some(commit) = &v.commit {
+        details.push(format!("commit {commit}"));
+    }
+    if let Some(range) = &v.range {
+        details.push(format!("range {range}"));
+    }
+
+    if details.is_empty() {
+        path.bold().to_string()
+    } else {
+        format!("{} ({})", path.bold(), details.join(", "))
+    }
+}
+
+/// Names and sizes of the largest other files in `path`'s directory,
+/// largest first. Only the first [`SIBLING_SCAN_LIMIT`] entries are read.
+fn largest_siblings(path: &Path) -> Vec<(String, u64)> {
+    let Some(entries) = path.parent().and_then(|dir| std::fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+
+    let mut siblings: Vec<(String, u64)> = entries
+        .take(SIBLING_SCAN_LIMIT)
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path() != path)
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+            Some((
+                entry.file_name().to_string_lossy().to_string(),
+                metadata.len(),
+            ))
+        })
+        .collect();
+    siblings.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    siblings.truncate(SIBLING_CONTEXT);
+    siblings
+}
+
+/// `path:severity:rule:bytes:message`, as in compact output.
+fn compact_line(v: &Violation, path: &str) -> String {
+    format!(
+        "{}:{}:{}:{}:{}",
+        path,
+        severity_name(v.severity),
+        v.rule_name,
+        v.sort_key,
+        v.message
+    )
+}
+
+/// `<error severity=".." message=".." source="sizelint.rule"/>`, the
+/// element checkstyle lists a violation as inside its `<file>`.
+fn checkstyle_error(v: &Violation) -> String {
+    let message = match &v.actual_value {
+        Some(actual) => format!("{} ({actual})", v.message),
+        None => v.message.clone(),
+    };
+    format!(
+        "<error severity=\"{}\" message=\"{}\" source=\"sizelint.{}\"/>",
+        severity_name(v.severity),
+        xml_escape(&message),
+        xml_escape(&v.rule_name)
+    )
+}
+
+/// `value` with the characters that are special in XML attributes escaped.
+fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
 }
 
 pub struct OutputFormatter {
     format: OutputFormat,
     quiet: bool,
     base_path: PathBuf,
+    trailing_newline: bool,
+    ascii: bool,
+    explain: bool,
+    sibling_context: bool,
+    metadata: bool,
+    schema_version: u32,
+    exit: Option<ExitStatus>,
+    quiet_on_success: bool,
+    only_violations: bool,
+    dual_units: bool,
+    passed: Vec<PassedFile>,
 }
 
 impl OutputFormatter {
@@ -44,9 +371,101 @@ impl OutputFormatter {
             format,
             quiet,
             base_path,
+            trailing_newline: true,
+            ascii: ASCII.load(Ordering::Relaxed),
+            explain: false,
+            sibling_context: false,
+            metadata: false,
+            schema_version: 1,
+            exit: None,
+            quiet_on_success: false,
+            only_violations: false,
+            dual_units: false,
+            passed: Vec::new(),
+        }
+    }
+
+    /// Use ASCII symbols in human output instead of the global setting.
+    pub fn with_ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    /// Whether machine-readable output ends with a final `\n`.
+    pub fn with_trailing_newline(mut self, trailing_newline: bool) -> Self {
+        self.trailing_newline = trailing_newline;
+        self
+    }
+
+    /// Append a remediation tip to each violation in human output.
+    pub fn with_explain(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        self
+    }
+
+    /// List the largest other files in each violation's directory in
+    /// human output, to show whether the file is an outlier.
+    pub fn with_sibling_context(mut self, sibling_context: bool) -> Self {
+        self.sibling_context = sibling_context;
+        self
+    }
+
+    /// Add a run id and timestamp to JSON output. Off by default so that
+    /// reports of the same tree stay byte-identical.
+    pub fn with_metadata(mut self, metadata: bool) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Layout of JSON output, one of [`JSON_SCHEMA_VERSIONS`]. Version 1 is
+    /// the flat summary; version 2 adds `schema_version`, `by_rule` and
+    /// `passed`.
+    pub fn with_schema_version(mut self, schema_version: u32) -> Self {
+        self.schema_version = schema_version;
+        self
+    }
+
+    /// The exit decision for the reported violations, included in JSON.
+    pub fn with_exit_status(mut self, exit: ExitStatus) -> Self {
+        self.exit = Some(exit);
+        self
+    }
+
+    /// Print nothing at all when there are no violations.
+    pub fn with_quiet_on_success(mut self, quiet_on_success: bool) -> Self {
+        self.quiet_on_success = quiet_on_success;
+        self
+    }
+
+    /// Write only the violations: no summary or passed files, so a clean
+    /// human or compact run prints nothing. JSON is the bare array of
+    /// violations.
+    pub fn with_only_violations(mut self, only_violations: bool) -> Self {
+        self.only_violations = only_violations;
+        self
+    }
+
+    /// Show sizes in human output in both binary and decimal units, e.g.
+    /// `10.0 MiB (10.5 MB)`.
+    pub fn with_dual_units(mut self, dual_units: bool) -> Self {
+        self.dual_units = dual_units;
+        self
+    }
+
+    fn size_text(&self, size: u64) -> String {
+        if self.dual_units {
+            format_size_dual(size)
+        } else {
+            format_size(size)
         }
     }
 
+    /// Files that passed, listed with their rule and size in human output.
+    pub fn with_passed_files(mut self, passed: Vec<PassedFile>) -> Self {
+        self.passed = passed;
+        self
+    }
+
     fn relative_path(&self, path: &Path) -> String {
         path.strip_prefix(&self.base_path)
             .unwrap_or(path)
@@ -54,6 +473,33 @@ impl OutputFormatter {
             .to_string()
     }
 
+    /// One-line summary of the largest error, e.g.
+    /// `Largest violation: big.bin (12.3 MB, limit 10.0 MB)`.
+    pub fn largest_violation_line(&self, violations: &[Violation]) -> Option<String> {
+        let largest = violations
+            .iter()
+            .filter(|v| v.severity == Severity::Error)
+            .max_by_key(|v| v.sort_key)?;
+
+        let mut details = Vec::new();
+        if let Some(actual) = &largest.actual_value {
+            details.push(actual.clone());
+        }
+        if let Some(limit) = largest.limit {
+            details.push(format!("limit {}", format_size(limit)));
+        }
+
+        let path = self.relative_path(&largest.path);
+        if details.is_empty() {
+            Some(format!("Largest violation: {path}"))
+        } else {
+            Some(format!(
+                "Largest violation: {path} ({})",
+                details.join(", ")
+            ))
+        }
+    }
+
     pub fn output_results(
         &self,
         violations: &[Violation],
@@ -62,13 +508,49 @@ impl OutputFormatter {
         suggestions: &HashMap<&str, &str>,
         descriptions: &HashMap<&str, &str>,
     ) -> Result<()> {
+        let mut stdout = io::stdout().lock();
+        self.write_results(
+            &mut stdout,
+            violations,
+            files_checked,
+            elapsed,
+            suggestions,
+            descriptions,
+        )?;
+        stdout.flush()?;
+        Ok(())
+    }
+
+    fn write_results<W: Write>(
+        &self,
+        writer: &mut W,
+        violations: &[Violation],
+        files_checked: usize,
+        elapsed: Duration,
+        suggestions: &HashMap<&str, &str>,
+        descriptions: &HashMap<&str, &str>,
+    ) -> Result<()> {
+        if self.quiet_on_success && violations.is_empty() {
+            return Ok(());
+        }
+
         let summary = self.create_summary(violations, files_checked, suggestions);
 
         match self.format {
-            OutputFormat::Human => {
-                self.output_human(violations, &summary, elapsed, suggestions, descriptions)
+            OutputFormat::Human => self.write_human(
+                writer,
+                violations,
+                &summary,
+                elapsed,
+                suggestions,
+                descriptions,
+            ),
+            OutputFormat::Json if self.only_violations => {
+                self.write_json(writer, &summary.violations)
             }
-            OutputFormat::Json => self.output_json(&summary),
+            OutputFormat::Json => self.write_json(writer, &summary),
+            OutputFormat::Compact => self.write_compact(writer, violations),
+            OutputFormat::Checkstyle => self.write_checkstyle(writer, violations),
         }
     }
 
@@ -78,11 +560,11 @@ impl OutputFormatter {
         files_checked: usize,
         suggestions: &HashMap<&str, &str>,
     ) -> OutputSummary {
-        let mut rules_run = std::collections::HashSet::new();
+        let mut rules_run = std::collections::BTreeSet::new();
         let mut error_count = 0;
         let mut warning_count = 0;
 
-        let violation_outputs: Vec<ViolationOutput> = violations
+        let mut violation_outputs: Vec<ViolationOutput> = violations
             .iter()
             .map(|v| {
                 rules_run.insert(v.rule_name.clone());
@@ -93,41 +575,75 @@ impl OutputFormatter {
                 }
 
                 ViolationOutput {
-                    path: self.relative_path(&v.path),
-                    rule_name: v.rule_name.clone(),
-                    message: v.message.clone(),
-                    severity: match v.severity {
-                        Severity::Error => "error".to_string(),
-                        Severity::Warning => "warning".to_string(),
-                    },
-                    actual_value: v.actual_value.clone(),
-                    expected_value: v.expected_value.clone(),
                     suggestion: suggestions.get(v.rule_name.as_str()).map(|s| s.to_string()),
-                    commit: v.commit.clone(),
+                    ..ViolationOutput::new(v, self.relative_path(&v.path))
                 }
             })
             .collect();
+        // Violations may arrive in hash or thread order; sort for reproducible reports
+        violation_outputs.sort_by(|a, b| {
+            (&a.path, &a.rule_name, &a.severity).cmp(&(&b.path, &b.rule_name, &b.severity))
+        });
+
+        let (run_id, timestamp) = if self.metadata {
+            let now = time::OffsetDateTime::now_utc()
+                .format(&time::format_description::well_known::Rfc3339)
+                .expect("current UTC time formats as RFC 3339");
+            (Some(uuid::Uuid::new_v4().to_string()), Some(now))
+        } else {
+            (None, None)
+        };
+
+        let (schema_version, by_rule, passed) = if self.schema_version >= 2 {
+            let mut by_rule: BTreeMap<String, RuleCounts> = BTreeMap::new();
+            for v in violations {
+                let counts = by_rule.entry(v.rule_name.clone()).or_default();
+                match v.severity {
+                    Severity::Error => counts.errors += 1,
+                    Severity::Warning => counts.warnings += 1,
+                }
+            }
+            let passed = self
+                .passed
+                .iter()
+                .map(|p| PassedOutput {
+                    path: self.relative_path(&p.path),
+                    size: p.size,
+                    rule: p.rule.clone(),
+                })
+                .collect();
+            (Some(self.schema_version), Some(by_rule), Some(passed))
+        } else {
+            (None, None, None)
+        };
 
         OutputSummary {
+            schema_version,
+            run_id,
+            timestamp,
             total_files_checked: files_checked,
             total_violations: violations.len(),
             error_count,
             warning_count,
             rules_run: rules_run.into_iter().collect(),
             violations: violation_outputs,
+            by_rule,
+            passed,
+            exit: self.exit,
         }
     }
 
-    fn output_human(
+    fn write_human<W: Write>(
         &self,
+        writer: &mut W,
         violations: &[Violation],
         summary: &OutputSummary,
         elapsed: Duration,
         suggestions: &HashMap<&str, &str>,
         descriptions: &HashMap<&str, &str>,
     ) -> Result<()> {
-        let mut stdout = io::stdout();
-        let gutter = "┃".dimmed();
+        let symbols = symbols_for(self.ascii);
+        let gutter = symbols.gutter.dimmed();
 
         let mut by_rule: BTreeMap<&str, Vec<&Violation>> = BTreeMap::new();
         for v in violations {
@@ -137,16 +653,16 @@ impl OutputFormatter {
         for (rule_name, rule_violations) in &by_rule {
             if let Some(description) = descriptions.get(rule_name) {
                 writeln!(
-                    stdout,
+                    writer,
                     "{} {} {}",
                     rule_name.bold(),
-                    "—".dimmed(),
+                    symbols.dash.dimmed(),
                     description.dimmed()
                 )?;
             } else {
-                writeln!(stdout, "{}", rule_name.bold())?;
+                writeln!(writer, "{}", rule_name.bold())?;
             }
-            writeln!(stdout, "{gutter}")?;
+            writeln!(writer, "{gutter}")?;
 
             let mut errors: Vec<&Violation> = Vec::new();
             let mut warnings: Vec<&Violation> = Vec::new();
@@ -156,8 +672,8 @@ impl OutputFormatter {
                     Severity::Warning => warnings.push(v),
                 }
             }
-            errors.sort_by(|a, b| b.sort_key.cmp(&a.sort_key));
-            warnings.sort_by(|a, b| b.sort_key.cmp(&a.sort_key));
+            errors.sort_by_key(|v| std::cmp::Reverse(v.sort_key));
+            warnings.sort_by_key(|v| std::cmp::Reverse(v.sort_key));
 
             for (severity_group, marker, color_fn) in [
                 (
@@ -171,49 +687,67 @@ impl OutputFormatter {
                     continue;
                 }
                 let message = &severity_group[0].message;
-                writeln!(stdout, "{gutter} {} {}", color_fn(marker.bold()), message)?;
+                writeln!(writer, "{gutter} {} {}", color_fn(marker.bold()), message)?;
                 for v in severity_group {
-                    let path_str = self.relative_path(&v.path);
-                    match (&v.actual_value, &v.commit) {
-                        (Some(actual), Some(commit)) => {
-                            writeln!(
-                                stdout,
-                                "{gutter}     {} ({}, commit {})",
-                                path_str.bold(),
-                                actual,
-                                commit
-                            )?;
-                        }
-                        (Some(actual), None) => {
-                            writeln!(stdout, "{gutter}     {} ({})", path_str.bold(), actual)?;
-                        }
-                        (None, Some(commit)) => {
+                    writeln!(
+                        writer,
+                        "{gutter}     {}",
+                        human_entry(v, &self.relative_path(&v.path), self.dual_units)
+                    )?;
+                    if self.explain {
+                        let tip = remediation_tip(v);
+                        writeln!(writer, "{gutter}       {} {}", "tip:".cyan(), tip.dimmed())?;
+                    }
+                    if self.sibling_context {
+                        let siblings = largest_siblings(&v.path);
+                        if !siblings.is_empty() {
+                            let listed: Vec<String> = siblings
+                                .iter()
+                                .map(|(name, size)| format!("{name} ({})", self.size_text(*size)))
+                                .collect();
                             writeln!(
-                                stdout,
-                                "{gutter}     {} (commit {})",
-                                path_str.bold(),
-                                commit
+                                writer,
+                                "{gutter}       {} {}",
+                                "next to:".cyan(),
+                                listed.join(", ").dimmed()
                             )?;
                         }
-                        (None, None) => {
-                            writeln!(stdout, "{gutter}     {}", path_str.bold())?;
-                        }
                     }
                 }
             }
 
             if let Some(suggestion) = suggestions.get(rule_name) {
-                writeln!(stdout, "{gutter}")?;
-                writeln!(stdout, "{} {}", "hint:".cyan().bold(), suggestion.dimmed())?;
+                writeln!(writer, "{gutter}")?;
+                writeln!(writer, "{} {}", "hint:".cyan().bold(), suggestion.dimmed())?;
             }
 
-            writeln!(stdout)?;
+            writeln!(writer)?;
+        }
+
+        if self.only_violations {
+            return Ok(());
+        }
+
+        if !self.passed.is_empty() {
+            writeln!(writer, "{}", "Largest passing files".bold())?;
+            writeln!(writer, "{gutter}")?;
+            for p in &self.passed {
+                writeln!(
+                    writer,
+                    "{gutter} {} {} ({}, rule {})",
+                    symbols.ok.green(),
+                    self.relative_path(&p.path),
+                    self.size_text(p.size),
+                    p.rule
+                )?;
+            }
+            writeln!(writer)?;
         }
 
         if !self.quiet {
-            writeln!(stdout)?;
+            writeln!(writer)?;
             writeln!(
-                stdout,
+                writer,
                 "{}",
                 format!("Analysis took {:.2}s", elapsed.as_secs_f64()).dimmed()
             )?;
@@ -241,7 +775,10 @@ impl OutputFormatter {
                 ));
             }
 
-            let status = if summary.error_count > 0 {
+            let failed = self
+                .exit
+                .map_or(summary.error_count > 0, |exit| exit.code != 0);
+            let status = if failed {
                 "FAILED".red().bold()
             } else if summary.warning_count > 0 {
                 "WARNINGS".yellow().bold()
@@ -249,30 +786,796 @@ impl OutputFormatter {
                 "PASSED".green().bold()
             };
 
-            writeln!(stdout, "{}. [{}]", parts.join(", "), status)?;
+            writeln!(writer, "{}. [{}]", parts.join(", "), status)?;
+            if let Some(reason) = self.exit.and_then(|exit| exit.failure_reason(summary)) {
+                writeln!(writer, "{}", reason.red())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// One `path:severity:rule:bytes:message` line per violation, sorted by
+    /// path. Never colored and without a summary, for `grep` and `awk`.
+    fn write_compact<W: Write>(&self, writer: &mut W, violations: &[Violation]) -> Result<()> {
+        let mut sorted: Vec<&Violation> = violations.iter().collect();
+        sorted.sort_by(|a, b| a.path.cmp(&b.path).then(a.rule_name.cmp(&b.rule_name)));
+
+        for v in sorted {
+            writeln!(writer, "{}", compact_line(v, &self.relative_path(&v.path)))?;
         }
+        Ok(())
+    }
 
+    /// A checkstyle XML report with one `<file>` element per file that has
+    /// violations, files sorted by path.
+    fn write_checkstyle<W: Write>(&self, writer: &mut W, violations: &[Violation]) -> Result<()> {
+        let mut by_file: BTreeMap<String, Vec<&Violation>> = BTreeMap::new();
+        for v in violations {
+            by_file
+                .entry(self.relative_path(&v.path))
+                .or_default()
+                .push(v);
+        }
+
+        writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(writer, "<checkstyle version=\"4.3\">")?;
+        for (path, file_violations) in &by_file {
+            writeln!(writer, "  <file name=\"{}\">", xml_escape(path))?;
+            for v in file_violations {
+                writeln!(writer, "    {}", checkstyle_error(v))?;
+            }
+            writeln!(writer, "  </file>")?;
+        }
+        write!(writer, "</checkstyle>")?;
+        if self.trailing_newline {
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    /// Print any serializable value as pretty JSON to stdout.
+    pub fn print_json<T: Serialize>(&self, value: &T) -> Result<()> {
+        let mut stdout = io::stdout().lock();
+        self.write_json(&mut stdout, value)?;
+        stdout.flush()?;
         Ok(())
     }
 
-    fn output_json(&self, summary: &OutputSummary) -> Result<()> {
-        let json = serde_json::to_string_pretty(summary)?;
+    /// Machine formats always use `\n` line endings, regardless of platform.
+    fn write_json<W: Write, T: Serialize>(&self, writer: &mut W, value: &T) -> Result<()> {
+        let json = serde_json::to_string_pretty(value)?;
 
-        println!("{json}");
+        writer.write_all(json.as_bytes())?;
+        if self.trailing_newline {
+            writer.write_all(b"\n")?;
+        }
         Ok(())
     }
 }
 
+/// Print a table of bytes per group, such as a directory or an extension,
+/// largest first, followed by the total.
+pub fn print_size_table(sizes: &BTreeMap<String, u64>) -> Result<()> {
+    let mut stdout = io::stdout();
+    let mut rows: Vec<_> = sizes.iter().collect();
+    rows.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+
+    let width = rows.iter().map(|(dir, _)| dir.len()).max().unwrap_or(0);
+    for (dir, size) in rows {
+        writeln!(stdout, "{dir:<width$}  {:>10}", format_size(*size))?;
+    }
+    let total: u64 = sizes.values().sum();
+    writeln!(
+        stdout,
+        "{}",
+        format!("{:<width$}  {:>10}", "total", format_size(total)).bold()
+    )?;
+    Ok(())
+}
+
+/// Extensions of generated files that usually belong in `.gitignore`.
+const GENERATED_EXTENSIONS: &[&str] = &["log", "out", "tmp", "trace", "dump"];
+
+/// A short, rule-aware hint on how to resolve `violation`.
+pub fn remediation_tip(violation: &Violation) -> String {
+    match violation.expected_value.as_deref() {
+        Some("not matched") => {
+            return format!(
+                "Rule '{}' does not allow files matching its patterns; remove or rename the file",
+                violation.rule_name
+            );
+        }
+        Some("tracked") => {
+            return "Track the file with `git add` or delete it".to_string();
+        }
+        _ => {}
+    }
+
+    let generated = violation
+        .path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| GENERATED_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+    if generated {
+        "Generated output rarely belongs in git; add it to .gitignore".to_string()
+    } else {
+        "Store large binaries with Git LFS (`git lfs track`) instead of in git".to_string()
+    }
+}
+
 pub fn print_progress(message: &str) {
+    if let Some(deferred) = DEFERRED_PROGRESS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_mut()
+    {
+        deferred.push(message.to_string());
+        return;
+    }
     if !cfg!(test) {
-        eprintln!("{} {}", "→".dimmed(), message.dimmed());
+        eprintln!("{} {}", symbols().arrow.dimmed(), message.dimmed());
+    }
+}
+
+/// Hold back progress messages until [`finish_deferred_progress`].
+pub fn defer_progress() {
+    *DEFERRED_PROGRESS.lock().unwrap_or_else(|e| e.into_inner()) = Some(Vec::new());
+}
+
+/// Stop deferring, printing the held-back messages if `show` is set.
+pub fn finish_deferred_progress(show: bool) {
+    let deferred = DEFERRED_PROGRESS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take();
+    if show {
+        for message in deferred.into_iter().flatten() {
+            print_progress(&message);
+        }
     }
 }
 
 pub fn print_error(message: &str) {
-    eprintln!("{} {}", "✗".red().bold(), message.red());
+    eprintln!("{} {}", symbols().error.red().bold(), message.red());
+}
+
+pub fn print_warning(message: &str) {
+    eprintln!("{} {}", symbols().warning.yellow().bold(), message.yellow());
 }
 
 pub fn print_success(message: &str) {
-    eprintln!("{} {}", "✓".green().bold(), message.green());
+    eprintln!("{} {}", symbols().ok.green().bold(), message.green());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_summary() -> OutputSummary {
+        OutputSummary {
+            schema_version: None,
+            run_id: None,
+            timestamp: None,
+            total_files_checked: 0,
+            total_violations: 0,
+            error_count: 0,
+            warning_count: 0,
+            rules_run: vec![],
+            violations: vec![],
+            by_rule: None,
+            passed: None,
+            exit: None,
+        }
+    }
+
+    fn size_violation(path: &str, size: u64, severity: Severity) -> Violation {
+        Violation::new(
+            PathBuf::from(path),
+            "default".to_string(),
+            "File exceeds maximum allowed size 1.0 MB".to_string(),
+            severity,
+        )
+        .with_actual_value(format_size(size))
+        .with_expected_value("≤ 1.0 MB".to_string())
+        .with_sort_key(size)
+        .with_limit(1024 * 1024)
+    }
+
+    #[test]
+    fn test_largest_violation_line_names_biggest_error() {
+        let formatter = OutputFormatter::new(OutputFormat::Human, false, PathBuf::from("/repo"));
+        let violations = vec![
+            size_violation("/repo/small.bin", 2 * 1024 * 1024, Severity::Error),
+            size_violation("/repo/huge.log", 50 * 1024 * 1024, Severity::Warning),
+            size_violation("/repo/big.bin", 12 * 1024 * 1024, Severity::Error),
+        ];
+
+        assert_eq!(
+            formatter.largest_violation_line(&violations).unwrap(),
+            "Largest violation: big.bin (12.0 MB, limit 1.0 MB)"
+        );
+
+        // With an exclusive boundary the expected value reads "< 100 B"
+        let exact = Violation::new(
+            PathBuf::from("/repo/exact.bin"),
+            "default".to_string(),
+            "File exceeds maximum allowed size 100 B".to_string(),
+            Severity::Error,
+        )
+        .with_actual_value(format_size(100))
+        .with_expected_value("< 100 B".to_string())
+        .with_sort_key(100)
+        .with_limit(100);
+        assert_eq!(
+            formatter.largest_violation_line(&[exact]).unwrap(),
+            "Largest violation: exact.bin (100 B, limit 100 B)"
+        );
+    }
+
+    #[test]
+    fn test_human_output_highlights_overage() {
+        colored::control::set_override(false);
+        let formatter = OutputFormatter::new(OutputFormat::Human, false, PathBuf::from("/repo"));
+        let violation = Violation::new(
+            PathBuf::from("/repo/big.bin"),
+            "default".to_string(),
+            "File exceeds maximum allowed size 10.0 MB".to_string(),
+            Severity::Error,
+        )
+        .with_actual_value(format_size(12 * 1024 * 1024))
+        .with_expected_value("≤ 10.0 MB".to_string())
+        .with_sort_key(12 * 1024 * 1024)
+        .with_limit(10 * 1024 * 1024);
+
+        let mut buf = Vec::new();
+        formatter
+            .write_results(
+                &mut buf,
+                &[violation],
+                1,
+                Duration::ZERO,
+                &HashMap::new(),
+                &HashMap::new(),
+            )
+            .unwrap();
+        let out = String::from_utf8(buf).unwrap();
+
+        assert!(out.contains("big.bin (12.0 MB, 2.0 MB over)"));
+    }
+
+    #[test]
+    fn test_ascii_human_output_has_no_unicode() {
+        let formatter = OutputFormatter::new(OutputFormat::Human, false, PathBuf::from("/repo"))
+            .with_ascii(true);
+        let violations = vec![
+            size_violation("/repo/big.bin", 2 * 1024 * 1024, Severity::Error),
+            size_violation("/repo/huge.log", 3 * 1024 * 1024, Severity::Warning),
+        ];
+        let summary = formatter.create_summary(&violations, 2, &HashMap::new());
+        let descriptions = HashMap::from([("default", "Default file size check")]);
+
+        let mut buf = Vec::new();
+        formatter
+            .write_human(
+                &mut buf,
+                &violations,
+                &summary,
+                Duration::ZERO,
+                &HashMap::new(),
+                &descriptions,
+            )
+            .unwrap();
+
+        assert!(buf.is_ascii());
+        assert!(String::from_utf8(buf).unwrap().contains("big.bin"));
+    }
+
+    #[test]
+    fn test_explain_appends_remediation_tips() {
+        let formatter = OutputFormatter::new(OutputFormat::Human, false, PathBuf::from("/repo"))
+            .with_ascii(true)
+            .with_explain(true);
+        let violations = vec![
+            size_violation("/repo/big.bin", 2 * 1024 * 1024, Severity::Error),
+            Violation::new(
+                PathBuf::from("/repo/secret.pem"),
+                "no_keys".to_string(),
+                "File /repo/secret.pem matches rule pattern".to_string(),
+                Severity::Error,
+            )
+            .with_actual_value("matched".to_string())
+            .with_expected_value("not matched".to_string()),
+        ];
+        let summary = formatter.create_summary(&violations, 2, &HashMap::new());
+
+        let mut buf = Vec::new();
+        formatter
+            .write_human(
+                &mut buf,
+                &violations,
+                &summary,
+                Duration::ZERO,
+                &HashMap::new(),
+                &HashMap::new(),
+            )
+            .unwrap();
+        let out = String::from_utf8(buf).unwrap();
+
+        assert!(out.contains("Git LFS"));
+        assert!(out.contains("Rule 'no_keys' does not allow files matching its patterns"));
+        assert_eq!(
+            remediation_tip(&size_violation("/repo/build.log", 1, Severity::Warning)),
+            "Generated output rarely belongs in git; add it to .gitignore"
+        );
+    }
+
+    #[test]
+    fn test_sibling_context_lists_neighbouring_sizes() {
+        colored::control::set_override(false);
+        let tmp = tempfile::tempdir().unwrap();
+        let big = tmp.path().join("big.bin");
+        std::fs::write(&big, vec![0u8; 8192]).unwrap();
+        for (name, size) in [
+            ("a.bin", 100),
+            ("b.bin", 2048),
+            ("c.bin", 10),
+            ("d.bin", 500),
+        ] {
+            std::fs::write(tmp.path().join(name), vec![0u8; size]).unwrap();
+        }
+
+        let formatter = OutputFormatter::new(OutputFormat::Human, false, tmp.path().to_path_buf())
+            .with_ascii(true)
+            .with_sibling_context(true);
+        let violations = vec![size_violation(big.to_str().unwrap(), 8192, Severity::Error)];
+        let summary = formatter.create_summary(&violations, 5, &HashMap::new());
+
+        let mut buf = Vec::new();
+        formatter
+            .write_human(
+                &mut buf,
+                &violations,
+                &summary,
+                Duration::ZERO,
+                &HashMap::new(),
+                &HashMap::new(),
+            )
+            .unwrap();
+        let out = String::from_utf8(buf).unwrap();
+
+        assert!(out.contains("next to: b.bin (2.0 KB), d.bin (500 B), a.bin (100 B)"));
+        assert!(!out.contains("c.bin"));
+    }
+
+    #[test]
+    fn test_footer_states_why_the_run_failed() {
+        colored::control::set_override(false);
+        let footer = |violations: &[Violation], reason: ExitReason, fail_on_warn: bool| {
+            let formatter =
+                OutputFormatter::new(OutputFormat::Human, false, PathBuf::from("/repo"))
+                    .with_exit_status(ExitStatus {
+                        code: u8::from(reason != ExitReason::Clean),
+                        reason,
+                        fail_on_warn,
+                    });
+            let summary = formatter.create_summary(violations, 3, &HashMap::new());
+            let mut buf = Vec::new();
+            formatter
+                .write_human(
+                    &mut buf,
+                    violations,
+                    &summary,
+                    Duration::ZERO,
+                    &HashMap::new(),
+                    &HashMap::new(),
+                )
+                .unwrap();
+            let out = String::from_utf8(buf).unwrap();
+            out.lines().rev().take(2).collect::<Vec<_>>().join("\n")
+        };
+        let warnings = [
+            size_violation("/repo/a.bin", 2048, Severity::Warning),
+            size_violation("/repo/b.bin", 2048, Severity::Warning),
+        ];
+        let errors = [size_violation("/repo/a.bin", 2048, Severity::Error)];
+
+        let out = footer(&warnings, ExitReason::Warnings, true);
+        assert!(out.contains("Failed: 2 warnings (fail_on_warn enabled)"));
+        assert!(out.contains("[FAILED]"));
+        let out = footer(&errors, ExitReason::Errors, false);
+        assert!(out.contains("Failed: 1 error (fail on error)"));
+        let out = footer(&warnings, ExitReason::Clean, false);
+        assert!(out.contains("[WARNINGS]"));
+        assert!(!out.contains("Failed:"));
+    }
+
+    #[test]
+    fn test_quiet_on_success_prints_only_failures() {
+        let formatter = OutputFormatter::new(OutputFormat::Human, false, PathBuf::from("/repo"))
+            .with_quiet_on_success(true);
+        let render = |violations: &[Violation]| {
+            let mut buf = Vec::new();
+            formatter
+                .write_results(
+                    &mut buf,
+                    violations,
+                    3,
+                    Duration::ZERO,
+                    &HashMap::new(),
+                    &HashMap::new(),
+                )
+                .unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+
+        assert_eq!(render(&[]), "");
+
+        let out = render(&[size_violation("/repo/big.bin", 4096, Severity::Error)]);
+        assert!(out.contains("big.bin"));
+        assert!(out.contains("Checked 3 files"));
+    }
+
+    #[test]
+    fn test_only_violations_omits_summary_and_clean_output() {
+        let render = |format, violations: &[Violation]| {
+            let formatter = OutputFormatter::new(format, false, PathBuf::from("/repo"))
+                .with_only_violations(true);
+            let mut buf = Vec::new();
+            formatter
+                .write_results(
+                    &mut buf,
+                    violations,
+                    3,
+                    Duration::ZERO,
+                    &HashMap::new(),
+                    &HashMap::new(),
+                )
+                .unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+
+        assert_eq!(render(OutputFormat::Human, &[]), "");
+        assert_eq!(render(OutputFormat::Compact, &[]), "");
+        assert_eq!(render(OutputFormat::Json, &[]), "[]\n");
+
+        let violations = [size_violation("/repo/big.bin", 4096, Severity::Error)];
+        let out = render(OutputFormat::Human, &violations);
+        assert!(out.contains("big.bin"));
+        assert!(!out.contains("Checked 3 files"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render(OutputFormat::Json, &violations)).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 1);
+        assert_eq!(json[0]["path"], "big.bin");
+    }
+
+    #[test]
+    fn test_passed_files_listed_with_size_and_rule() {
+        let formatter = OutputFormatter::new(OutputFormat::Human, false, PathBuf::from("/repo"))
+            .with_passed_files(vec![PassedFile {
+                path: PathBuf::from("/repo/assets/logo.png"),
+                size: 2048,
+                rule: "images".to_string(),
+            }]);
+        let mut buf = Vec::new();
+        formatter
+            .write_results(
+                &mut buf,
+                &[],
+                1,
+                Duration::ZERO,
+                &HashMap::new(),
+                &HashMap::new(),
+            )
+            .unwrap();
+        let out = String::from_utf8(buf).unwrap();
+
+        assert!(out.contains("Largest passing files"));
+        assert!(out.contains("assets/logo.png (2.0 KB, rule images)"));
+    }
+
+    #[test]
+    fn test_format_violation_matches_formatter_output() {
+        let violations = [
+            size_violation("/repo/big.bin", 12 * 1024 * 1024, Severity::Error)
+                .with_limit(10 * 1024 * 1024),
+        ];
+        let base = PathBuf::from("/repo");
+        let render = |format: OutputFormat| {
+            let formatter = OutputFormatter::new(format, true, base.clone());
+            let mut buf = Vec::new();
+            formatter
+                .write_results(
+                    &mut buf,
+                    &violations,
+                    1,
+                    Duration::ZERO,
+                    &HashMap::new(),
+                    &HashMap::new(),
+                )
+                .unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+
+        let line = format_violation(&violations[0], &OutputFormat::Compact, &base);
+        assert_eq!(render(OutputFormat::Compact), format!("{line}\n"));
+
+        let entry = format_violation(&violations[0], &OutputFormat::Human, &base);
+        assert!(
+            render(OutputFormat::Human)
+                .lines()
+                .any(|l| l.ends_with(&entry))
+        );
+
+        let json = format_violation(&violations[0], &OutputFormat::Json, &base);
+        let summary: OutputSummary = serde_json::from_str(&render(OutputFormat::Json)).unwrap();
+        assert_eq!(serde_json::to_string(&summary.violations[0]).unwrap(), json);
+    }
+
+    #[test]
+    fn test_checkstyle_groups_violations_by_file() {
+        let formatter =
+            OutputFormatter::new(OutputFormat::Checkstyle, false, PathBuf::from("/repo"));
+        let mut escaped = size_violation("/repo/a&b <raw>.bin", 2048, Severity::Warning);
+        escaped.message = "Size \"too\" big".to_string();
+        let mut archive = size_violation("/repo/big.tar", 4096, Severity::Error);
+        archive.rule_name = "archives".to_string();
+        let violations = vec![
+            size_violation("/repo/big.tar", 4096, Severity::Error),
+            escaped,
+            archive,
+        ];
+
+        let mut buf = Vec::new();
+        formatter
+            .write_results(
+                &mut buf,
+                &violations,
+                3,
+                Duration::ZERO,
+                &HashMap::new(),
+                &HashMap::new(),
+            )
+            .unwrap();
+        let xml = String::from_utf8(buf).unwrap();
+
+        // Every element opened is closed, in order
+        let mut open = Vec::new();
+        for tag in xml.split('<').skip(1).map(|t| &t[..t.find('>').unwrap()]) {
+            if let Some(name) = tag.strip_prefix('/') {
+                assert_eq!(open.pop(), Some(name));
+            } else if !tag.starts_with('?') && !tag.ends_with('/') {
+                open.push(tag.split_whitespace().next().unwrap());
+            }
+        }
+        assert!(open.is_empty());
+
+        assert_eq!(xml.matches("<file ").count(), 2);
+        assert_eq!(xml.matches("<file name=\"big.tar\">").count(), 1);
+        let big_tar = &xml[xml.find("big.tar").unwrap()..];
+        let big_tar = &big_tar[..big_tar.find("</file>").unwrap()];
+        assert!(big_tar.contains("source=\"sizelint.default\""));
+        assert!(big_tar.contains("source=\"sizelint.archives\""));
+        assert!(xml.contains("<file name=\"a&amp;b &lt;raw&gt;.bin\">"));
+        assert!(xml.contains(
+            "<error severity=\"warning\" message=\"Size &quot;too&quot; big (2.0 KB)\" \
+             source=\"sizelint.default\"/>"
+        ));
+    }
+
+    #[test]
+    fn test_compact_field_layout() {
+        let formatter = OutputFormatter::new(OutputFormat::Compact, false, PathBuf::from("/repo"));
+        let violations = vec![size_violation(
+            "/repo/assets/big.bin",
+            3_145_728,
+            Severity::Error,
+        )];
+
+        let mut buf = Vec::new();
+        formatter.write_compact(&mut buf, &violations).unwrap();
+
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "assets/big.bin:error:default:3145728:File exceeds maximum allowed size 1.0 MB\n"
+        );
+    }
+
+    #[test]
+    fn test_json_is_deterministic() {
+        let formatter = OutputFormatter::new(OutputFormat::Json, false, PathBuf::from("/repo"));
+        let mut violations: Vec<Violation> = ["zeta", "alpha", "mid", "beta"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let mut v = size_violation(&format!("/repo/{name}.bin"), 10, Severity::Error);
+                v.rule_name = format!("rule_{}", i % 3);
+                v
+            })
+            .collect();
+
+        let render = |violations: &[Violation]| {
+            let summary = formatter.create_summary(violations, 4, &HashMap::new());
+            let mut buf = Vec::new();
+            formatter.write_json(&mut buf, &summary).unwrap();
+            buf
+        };
+
+        let first = render(&violations);
+        violations.reverse();
+        assert_eq!(first, render(&violations));
+
+        let summary: OutputSummary = serde_json::from_slice(&first).unwrap();
+        assert_eq!(summary.rules_run, vec!["rule_0", "rule_1", "rule_2"]);
+    }
+
+    #[test]
+    fn test_metadata_only_with_flag() {
+        let render = |metadata: bool| {
+            let formatter = OutputFormatter::new(OutputFormat::Json, false, PathBuf::from("/repo"))
+                .with_metadata(metadata);
+            let summary = formatter.create_summary(&[], 0, &HashMap::new());
+            let mut buf = Vec::new();
+            formatter.write_json(&mut buf, &summary).unwrap();
+            serde_json::from_slice::<serde_json::Value>(&buf).unwrap()
+        };
+
+        let plain = render(false);
+        assert!(plain.get("run_id").is_none());
+        assert!(plain.get("timestamp").is_none());
+
+        let tagged = render(true);
+        let run_id = tagged["run_id"].as_str().unwrap();
+        assert_eq!(
+            uuid::Uuid::parse_str(run_id).unwrap().get_version(),
+            Some(uuid::Version::Random)
+        );
+        let timestamp = tagged["timestamp"].as_str().unwrap();
+        time::OffsetDateTime::parse(timestamp, &time::format_description::well_known::Rfc3339)
+            .unwrap();
+        assert_ne!(run_id, render(true)["run_id"].as_str().unwrap());
+    }
+
+    #[test]
+    fn test_json_reports_overage_and_limit_in_bytes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let big = tmp.path().join("big.bin");
+        std::fs::File::create(&big)
+            .unwrap()
+            .set_len(12 * 1024 * 1024)
+            .unwrap();
+        let rule = crate::rules::ConfigurableRule::new(
+            "default".to_string(),
+            crate::config::RuleDefinition {
+                enabled: true,
+                max_size: Some("10MB".parse().unwrap()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let violations = rule.check(&big).unwrap();
+
+        let formatter = OutputFormatter::new(OutputFormat::Json, false, tmp.path().to_path_buf());
+        let summary = formatter.create_summary(&violations, 1, &HashMap::new());
+        let mut buf = Vec::new();
+        formatter.write_json(&mut buf, &summary).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&buf).unwrap();
+
+        let violation = &json["violations"][0];
+        assert_eq!(violation["overage_bytes"], 2 * 1024 * 1024);
+        assert_eq!(violation["limit_bytes"], 10 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_json_reports_sizes_as_exact_byte_counts() {
+        let tmp = tempfile::tempdir().unwrap();
+        let sized = |name: &str, len: u64| {
+            let path = tmp.path().join(name);
+            std::fs::File::create(&path).unwrap().set_len(len).unwrap();
+            path
+        };
+        let big = sized("big.bin", 12 * 1024 * 1024);
+        let mid = sized("mid.bin", 6 * 1024 * 1024 + 1);
+        let rule = crate::rules::ConfigurableRule::new(
+            "default".to_string(),
+            crate::config::RuleDefinition {
+                enabled: true,
+                max_size: Some("10MB".parse().unwrap()),
+                warn_size: Some("5MB".parse().unwrap()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let mut violations = rule.check(&big).unwrap();
+        violations.extend(rule.check(&mid).unwrap());
+
+        let formatter = OutputFormatter::new(OutputFormat::Json, false, tmp.path().to_path_buf());
+        let summary = formatter.create_summary(&violations, 2, &HashMap::new());
+        let mut buf = Vec::new();
+        formatter.write_json(&mut buf, &summary).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&buf).unwrap();
+
+        let bytes: Vec<(&str, u64, u64)> = json["violations"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| {
+                (
+                    v["path"].as_str().unwrap(),
+                    v["actual_bytes"].as_u64().unwrap(),
+                    v["expected_bytes"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            bytes,
+            vec![
+                ("big.bin", 12 * 1024 * 1024, 10 * 1024 * 1024),
+                ("mid.bin", 6 * 1024 * 1024 + 1, 5 * 1024 * 1024),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schema_version_selects_fields() {
+        let violations = vec![
+            size_violation("/repo/big.bin", 4096, Severity::Error),
+            size_violation("/repo/mid.bin", 2048, Severity::Warning),
+        ];
+        let render = |schema_version: u32| {
+            let formatter = OutputFormatter::new(OutputFormat::Json, false, PathBuf::from("/repo"))
+                .with_schema_version(schema_version)
+                .with_passed_files(vec![PassedFile {
+                    path: PathBuf::from("/repo/small.txt"),
+                    size: 12,
+                    rule: "default".to_string(),
+                }]);
+            let summary = formatter.create_summary(&violations, 3, &HashMap::new());
+            let mut buf = Vec::new();
+            formatter.write_json(&mut buf, &summary).unwrap();
+            serde_json::from_slice::<serde_json::Value>(&buf).unwrap()
+        };
+
+        let v1 = render(1);
+        for field in ["schema_version", "by_rule", "passed"] {
+            assert!(v1.get(field).is_none(), "v1 has {field}");
+        }
+        assert_eq!(v1["error_count"], 1);
+
+        let v2 = render(2);
+        assert_eq!(v2["schema_version"], 2);
+        assert_eq!(
+            v2["by_rule"],
+            serde_json::json!({"default": {"errors": 1, "warnings": 1}})
+        );
+        assert_eq!(
+            v2["passed"],
+            serde_json::json!([{"path": "small.txt", "size": 12, "rule": "default"}])
+        );
+        assert_eq!(v2["violations"], v1["violations"]);
+    }
+
+    #[test]
+    fn test_json_trailing_newline() {
+        let formatter = OutputFormatter::new(OutputFormat::Json, false, PathBuf::new());
+        let mut buf = Vec::new();
+        formatter.write_json(&mut buf, &empty_summary()).unwrap();
+
+        let out = String::from_utf8(buf).unwrap();
+        assert!(out.ends_with("}\n"));
+        assert!(!out.ends_with("\n\n"));
+        assert!(!out.contains('\r'));
+    }
+
+    #[test]
+    fn test_json_without_trailing_newline() {
+        let formatter = OutputFormatter::new(OutputFormat::Json, false, PathBuf::new())
+            .with_trailing_newline(false);
+        let mut buf = Vec::new();
+        formatter.write_json(&mut buf, &empty_summary()).unwrap();
+
+        let out = String::from_utf8(buf).unwrap();
+        assert!(out.ends_with('}'));
+    }
 }
@@ -1,9 +1,16 @@
-use crate::config::RuleDefinition;
+use crate::config::{Boundary, MatchMode, RuleDefinition, SymlinkReport};
 use crate::error::{Result, SizelintError};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
-use tracing::{Level, debug, span};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{Level, debug, span, trace, warn};
 
 // Size constants using binary multipliers
 const BYTES_PER_KB: u64 = 1_024;
@@ -11,20 +18,59 @@ const BYTES_PER_MB: u64 = BYTES_PER_KB * 1_024;
 const BYTES_PER_GB: u64 = BYTES_PER_MB * 1_024;
 const BYTES_PER_TB: u64 = BYTES_PER_GB * 1_024;
 
+// Accepted unit spellings (uppercase); the decimal names are binary too
+const UNIT_MULTIPLIERS: &[(&str, u64)] = &[
+    ("B", 1),
+    ("BYTE", 1),
+    ("BYTES", 1),
+    ("KB", BYTES_PER_KB),
+    ("KIB", BYTES_PER_KB),
+    ("KILOBYTE", BYTES_PER_KB),
+    ("KILOBYTES", BYTES_PER_KB),
+    ("KIBIBYTE", BYTES_PER_KB),
+    ("KIBIBYTES", BYTES_PER_KB),
+    ("MB", BYTES_PER_MB),
+    ("MIB", BYTES_PER_MB),
+    ("MEGABYTE", BYTES_PER_MB),
+    ("MEGABYTES", BYTES_PER_MB),
+    ("MEBIBYTE", BYTES_PER_MB),
+    ("MEBIBYTES", BYTES_PER_MB),
+    ("GB", BYTES_PER_GB),
+    ("GIB", BYTES_PER_GB),
+    ("GIGABYTE", BYTES_PER_GB),
+    ("GIGABYTES", BYTES_PER_GB),
+    ("GIBIBYTE", BYTES_PER_GB),
+    ("GIBIBYTES", BYTES_PER_GB),
+    ("TB", BYTES_PER_TB),
+    ("TIB", BYTES_PER_TB),
+    ("TERABYTE", BYTES_PER_TB),
+    ("TERABYTES", BYTES_PER_TB),
+    ("TEBIBYTE", BYTES_PER_TB),
+    ("TEBIBYTES", BYTES_PER_TB),
+];
+
+// Number of leading bytes inspected when sniffing for binary content
+const BINARY_SNIFF_LEN: usize = 8_000;
+
+// Chunk size used when counting CRLF line endings under `normalize_crlf`
+const CRLF_CHUNK_LEN: usize = 64 * 1024;
+
 // Size formatting constants
 const SIZE_THRESHOLD: f64 = 1024.0;
 const SIZE_UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+const BINARY_UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB"];
+const DECIMAL_UNITS: &[&str] = &["B", "kB", "MB", "GB", "TB"];
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct RuleInfo {
     pub name: String,
     pub description: String,
     pub enabled: bool,
     pub priority: Option<i32>,
-    pub max_size: Option<u64>,
-    pub warn_size: Option<u64>,
-    pub max_size_str: Option<String>,
-    pub warn_size_str: Option<String>,
+    #[serde(flatten, serialize_with = "serialize_max_size")]
+    pub max_size: Option<SizeThreshold>,
+    #[serde(flatten, serialize_with = "serialize_warn_size")]
+    pub warn_size: Option<SizeThreshold>,
     pub includes: Vec<String>,
     pub excludes: Vec<String>,
     pub warn_on_match: bool,
@@ -32,6 +78,35 @@ pub struct RuleInfo {
     pub suggestion: Option<String>,
 }
 
+// JSON keeps both the bytes and the size as written, e.g.
+// `"max_size": 1048576, "max_size_str": "1MB"`
+fn serialize_max_size<S: serde::Serializer>(
+    threshold: &Option<SizeThreshold>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serialize_threshold(threshold, "max_size", "max_size_str", serializer)
+}
+
+fn serialize_warn_size<S: serde::Serializer>(
+    threshold: &Option<SizeThreshold>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serialize_threshold(threshold, "warn_size", "warn_size_str", serializer)
+}
+
+fn serialize_threshold<S: serde::Serializer>(
+    threshold: &Option<SizeThreshold>,
+    bytes_key: &str,
+    str_key: &str,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    use serde::ser::SerializeMap;
+    let mut map = serializer.serialize_map(Some(2))?;
+    map.serialize_entry(bytes_key, &threshold.as_ref().map(SizeThreshold::bytes))?;
+    map.serialize_entry(str_key, &threshold.as_ref().map(SizeThreshold::as_str))?;
+    map.end()
+}
+
 #[derive(Debug, Clone)]
 pub struct Violation {
     pub path: std::path::PathBuf,
@@ -41,7 +116,11 @@ pub struct Violation {
     pub actual_value: Option<String>,
     pub expected_value: Option<String>,
     pub sort_key: u64,
+    /// The limit `sort_key` was measured against, when there is one.
+    pub limit: Option<u64>,
     pub commit: Option<String>,
+    /// The `--git` range the file was found in, when several were given.
+    pub range: Option<String>,
 }
 
 impl Violation {
@@ -59,7 +138,9 @@ impl Violation {
             actual_value: None,
             expected_value: None,
             sort_key: 0,
+            limit: None,
             commit: None,
+            range: None,
         }
     }
 
@@ -77,6 +158,16 @@ impl Violation {
         self.sort_key = key;
         self
     }
+
+    pub fn with_limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// How far `sort_key` exceeds the limit, if a limit is known.
+    pub fn overage(&self) -> Option<u64> {
+        self.limit.map(|limit| self.sort_key.saturating_sub(limit))
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -85,52 +176,733 @@ pub enum Severity {
     Error,
 }
 
+/// A file matched by more than one specific rule, where only `chosen` applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleOverlap {
+    pub path: std::path::PathBuf,
+    pub chosen: String,
+    pub shadowed: Vec<String>,
+}
+
+/// Rule overlaps in the order they were found, and the paths already seen.
+#[derive(Default)]
+struct SeenOverlaps {
+    paths: HashSet<PathBuf>,
+    overlaps: Vec<RuleOverlap>,
+}
+
+/// A checked file that its selected rule let through.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassedFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub rule: String,
+}
+
+/// A check supplied by a library user in addition to the configured rules.
+///
+/// Custom rules apply to every file checked via
+/// [`RuleEngine::check_files`], independent of configured rule priorities.
+pub trait Rule: Send + Sync {
+    fn name(&self) -> &str;
+    fn check(&self, path: &Path) -> Result<Vec<Violation>>;
+}
+
+/// Per-file inputs the engine supplies to a rule check.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CheckContext<'a> {
+    /// Replaces the rule's own `max_size` for this file.
+    pub max_size_override: Option<u64>,
+    /// Files tracked by git, or `None` outside a repository.
+    pub tracked: Option<&'a HashSet<PathBuf>>,
+    /// Replaces the on-disk size, e.g. with CRLF line endings normalized.
+    pub measured_size: Option<u64>,
+}
+
+/// Violations found so far, shared with an interrupt handler so it can
+/// report them if the check is cut short.
+#[derive(Debug, Clone, Default)]
+pub struct PartialResults(Arc<Mutex<Vec<Violation>>>);
+
+impl PartialResults {
+    pub fn record(&self, violations: &[Violation]) {
+        if !violations.is_empty() {
+            self.0
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .extend_from_slice(violations);
+        }
+    }
+
+    pub fn snapshot(&self) -> Vec<Violation> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// Per-rule counts of one [`RuleEngine::check_files_with_stats`] run. The
+/// counters are atomic, so the parallel workers update them without locks.
+#[derive(Debug, Default)]
+pub struct RunStats {
+    counters: HashMap<String, RuleCounters>,
+}
+
+#[derive(Debug, Default)]
+struct RuleCounters {
+    matched: AtomicUsize,
+    errors: AtomicUsize,
+    warnings: AtomicUsize,
+}
+
+/// How many files a rule was applied to, and the violations it found.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RuleStats {
+    pub matched: usize,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+impl RunStats {
+    fn new<'a>(names: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            counters: names
+                .into_iter()
+                .map(|name| (name.to_string(), RuleCounters::default()))
+                .collect(),
+        }
+    }
+
+    fn record(&self, rule: &str, violations: &[Violation]) {
+        let Some(counters) = self.counters.get(rule) else {
+            return;
+        };
+        counters.matched.fetch_add(1, AtomicOrdering::Relaxed);
+        for v in violations {
+            match v.severity {
+                Severity::Error => counters.errors.fetch_add(1, AtomicOrdering::Relaxed),
+                Severity::Warning => counters.warnings.fetch_add(1, AtomicOrdering::Relaxed),
+            };
+        }
+    }
+
+    /// Counts of the rule `name`, or `None` if it was not part of the run.
+    pub fn rule(&self, name: &str) -> Option<RuleStats> {
+        self.counters.get(name).map(|counters| RuleStats {
+            matched: counters.matched.load(AtomicOrdering::Relaxed),
+            errors: counters.errors.load(AtomicOrdering::Relaxed),
+            warnings: counters.warnings.load(AtomicOrdering::Relaxed),
+        })
+    }
+
+    /// Counts of every rule of the run, by name.
+    pub fn by_rule(&self) -> BTreeMap<String, RuleStats> {
+        self.counters
+            .keys()
+            .filter_map(|name| Some((name.clone(), self.rule(name)?)))
+            .collect()
+    }
+}
+
+/// The include patterns of all rules in one glob set per match target, so a
+/// path is tested against every pattern once instead of rule by rule.
+struct RuleIndex {
+    // Pattern i of `by_path` belongs to rule `path_owners[i]`
+    by_path: globset::GlobSet,
+    path_owners: Vec<usize>,
+    by_basename: globset::GlobSet,
+    basename_owners: Vec<usize>,
+    // Rules without includes, or matched relative to their own root
+    always: Vec<usize>,
+}
+
+impl RuleIndex {
+    fn build(rules: &[ConfigurableRule]) -> Option<Self> {
+        let mut by_path = globset::GlobSetBuilder::new();
+        let mut path_owners = Vec::new();
+        let mut by_basename = globset::GlobSetBuilder::new();
+        let mut basename_owners = Vec::new();
+        let mut always = Vec::new();
+
+        for (i, rule) in rules.iter().enumerate() {
+            let mode = rule.definition.match_mode;
+            if rule.definition.includes.is_empty() || mode == MatchMode::Path {
+                always.push(i);
+                continue;
+            }
+            for pattern in &rule.definition.includes {
+                let glob = globset::Glob::new(&expand_pattern(pattern, mode)).ok()?;
+                if mode == MatchMode::Basename {
+                    by_basename.add(glob);
+                    basename_owners.push(i);
+                } else {
+                    by_path.add(glob);
+                    path_owners.push(i);
+                }
+            }
+        }
+
+        Some(Self {
+            by_path: by_path.build().ok()?,
+            path_owners,
+            by_basename: by_basename.build().ok()?,
+            basename_owners,
+            always,
+        })
+    }
+
+    /// Indices of the rules whose includes can match `path`, in rule order.
+    fn candidates(&self, path: &Path) -> Vec<usize> {
+        let mut candidates = self.always.clone();
+        candidates.extend(
+            self.by_path
+                .matches(path)
+                .into_iter()
+                .map(|i| self.path_owners[i]),
+        );
+        let basename = path.file_name().map(Path::new).unwrap_or(path);
+        candidates.extend(
+            self.by_basename
+                .matches(basename)
+                .into_iter()
+                .map(|i| self.basename_owners[i]),
+        );
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+    }
+}
+
 pub struct RuleEngine {
     rules: Vec<ConfigurableRule>,
+    // Built on first use; `None` falls back to testing every rule
+    index: OnceLock<Option<RuleIndex>>,
+    custom_rules: Vec<Box<dyn Rule>>,
+    // Time spent in custom rule `i` is `custom_elapsed[i]`, in nanoseconds
+    custom_elapsed: Vec<AtomicU64>,
+    profile: bool,
+    warn_on_rule_overlap: bool,
+    overlaps: Mutex<SeenOverlaps>,
+    binary_extensions: Vec<String>,
+    normalize_crlf: bool,
+    size_overrides: HashMap<PathBuf, u64>,
+    max_violations: Option<usize>,
+    fail_fast: bool,
+    stopped_early: AtomicBool,
+    partial: PartialResults,
+    tracked: Option<HashSet<PathBuf>>,
+    symlink_report: SymlinkReport,
+    record_passes: bool,
+    passes: Mutex<Vec<PassedFile>>,
 }
 
 impl RuleEngine {
     pub fn new() -> Self {
-        Self { rules: Vec::new() }
+        Self {
+            rules: Vec::new(),
+            index: OnceLock::new(),
+            custom_rules: Vec::new(),
+            custom_elapsed: Vec::new(),
+            profile: false,
+            warn_on_rule_overlap: false,
+            overlaps: Mutex::new(SeenOverlaps::default()),
+            binary_extensions: Vec::new(),
+            normalize_crlf: false,
+            size_overrides: HashMap::new(),
+            max_violations: None,
+            fail_fast: false,
+            stopped_early: AtomicBool::new(false),
+            partial: PartialResults::default(),
+            tracked: None,
+            symlink_report: SymlinkReport::default(),
+            record_passes: false,
+            passes: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// An engine with a single `history_max` rule that caps every path at
+    /// `max_size`, for one-shot history gates that need no config file.
+    pub fn history_max(max_size: &str) -> Result<Self> {
+        let mut engine = Self::new();
+        engine.add_rule(ConfigurableRule::new(
+            "history_max".to_string(),
+            RuleDefinition {
+                enabled: true,
+                description: "Largest blob allowed anywhere in history".to_string(),
+                max_size: Some(max_size.parse()?),
+                ..Default::default()
+            },
+        )?);
+        Ok(engine)
+    }
+
+    /// Files tracked by git, for rules with `require_tracked`. `None` outside
+    /// a repository, where those rules never fire.
+    pub fn with_tracked_files(mut self, tracked: Option<HashSet<PathBuf>>) -> Self {
+        self.tracked = tracked;
+        self
+    }
+
+    /// Which path violations on symlinked files are reported under.
+    pub fn with_symlink_report(mut self, symlink_report: SymlinkReport) -> Self {
+        self.symlink_report = symlink_report;
+        self
+    }
+
+    /// Measure the time each rule spends checking files, for
+    /// [`Self::slowest_rules`].
+    pub fn with_profiling(mut self, enabled: bool) -> Self {
+        self.profile = enabled;
+        self
+    }
+
+    /// Run `check`, adding its duration to `elapsed` when profiling.
+    fn timed<T>(&self, elapsed: &AtomicU64, check: impl FnOnce() -> T) -> T {
+        if !self.profile {
+            return check();
+        }
+        let start = Instant::now();
+        let result = check();
+        let nanos = u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX);
+        elapsed.fetch_add(nanos, AtomicOrdering::Relaxed);
+        result
+    }
+
+    /// The `limit` rules that took longest in total under
+    /// [`Self::with_profiling`], slowest first. Rules that never ran are
+    /// left out.
+    pub fn slowest_rules(&self, limit: usize) -> Vec<(&str, Duration)> {
+        let configured = self.rules.iter().map(|r| (r.name(), &r.elapsed_nanos));
+        let custom = self
+            .custom_rules
+            .iter()
+            .zip(&self.custom_elapsed)
+            .map(|(r, elapsed)| (r.name(), elapsed));
+        let mut timings: Vec<_> = configured
+            .chain(custom)
+            .map(|(name, elapsed)| {
+                let nanos = elapsed.load(AtomicOrdering::Relaxed);
+                (name, Duration::from_nanos(nanos))
+            })
+            .filter(|(_, elapsed)| !elapsed.is_zero())
+            .collect();
+        timings.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        timings.truncate(limit);
+        timings
+    }
+
+    /// Remember every file that passed its rule, for [`Self::largest_passed`].
+    pub fn with_pass_records(mut self, enabled: bool) -> Self {
+        self.record_passes = enabled;
+        self
+    }
+
+    /// Record every violation into `partial` as soon as it is found.
+    pub fn with_partial_results(mut self, partial: PartialResults) -> Self {
+        self.partial = partial;
+        self
+    }
+
+    /// Stop checking further files once `max` violations have been found.
+    pub fn with_max_violations(mut self, max: Option<usize>) -> Self {
+        self.max_violations = max;
+        self
+    }
+
+    /// Stop checking further files at the first error-severity violation and
+    /// report only that one.
+    pub fn with_fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    /// Whether checking stopped at the violation limit, so more may exist.
+    pub fn stopped_early(&self) -> bool {
+        self.stopped_early.load(AtomicOrdering::Relaxed)
+    }
+
+    /// Per-file `max_size` overrides, e.g. from `.gitattributes`.
+    pub fn with_size_overrides(mut self, overrides: HashMap<PathBuf, u64>) -> Self {
+        self.size_overrides = overrides;
+        self
+    }
+
+    /// Extensions that `text_only` rules treat as binary without reading the file.
+    pub fn with_binary_extensions(mut self, extensions: Vec<String>) -> Self {
+        self.binary_extensions = extensions;
+        self
+    }
+
+    /// Measure text files as if their CRLF line endings were LF.
+    pub fn with_normalize_crlf(mut self, enabled: bool) -> Self {
+        self.normalize_crlf = enabled;
+        self
+    }
+
+    /// The size of `path` with each CRLF counted as one byte, or `None` when
+    /// it is measured as-is: normalization is off, or the file is binary,
+    /// unreadable or not a regular file.
+    fn normalized_size(&self, path: &Path) -> Option<u64> {
+        if !self.normalize_crlf || is_binary_file(path, &self.binary_extensions) {
+            return None;
+        }
+        if !std::fs::metadata(path).ok()?.is_file() {
+            return None;
+        }
+        let file = std::fs::File::open(path).ok()?;
+        crlf_normalized_len(std::io::BufReader::with_capacity(CRLF_CHUNK_LEN, file)).ok()
+    }
+
+    /// Warn when a file is matched by several rules and all but one are shadowed.
+    pub fn with_rule_overlap_warnings(mut self, enabled: bool) -> Self {
+        self.warn_on_rule_overlap = enabled;
+        self
+    }
+
+    /// Root for rules with `match_mode = "path"`, applied to every rule
+    /// already added.
+    pub fn with_root(mut self, root: &Path) -> Self {
+        self.rules = self.rules.into_iter().map(|r| r.with_root(root)).collect();
+        self
     }
 
     pub fn add_rule(&mut self, rule: ConfigurableRule) {
         self.rules.push(rule);
+        self.index = OnceLock::new();
     }
 
-    fn best_rule_for(&self, path: &Path) -> Option<&ConfigurableRule> {
+    /// Register a custom rule that runs on every checked file, alongside
+    /// the best matching configured rule.
+    pub fn add_custom_rule(&mut self, rule: Box<dyn Rule>) {
+        self.custom_rules.push(rule);
+        self.custom_elapsed.push(AtomicU64::new(0));
+    }
+
+    /// Highest priority wins; ties go to the alphabetically first rule name
+    /// so the result does not depend on insertion order.
+    fn rule_order(a: &ConfigurableRule, b: &ConfigurableRule) -> std::cmp::Ordering {
+        let by_priority = match (a.get_priority(), b.get_priority()) {
+            (Some(p1), Some(p2)) => p1.cmp(&p2),
+            (Some(_), None) => std::cmp::Ordering::Greater,
+            (None, Some(_)) => std::cmp::Ordering::Less,
+            (None, None) => std::cmp::Ordering::Equal,
+        };
+        by_priority.then_with(|| b.name.cmp(&a.name))
+    }
+
+    /// Whether `rule` applies to `path`. Off disk (sizes from git, archive
+    /// members) content and mode cannot be read, so the file counts as a
+    /// non-executable text file unless its extension says it is binary.
+    fn applies_to(&self, rule: &ConfigurableRule, path: &Path, on_disk: bool) -> bool {
+        let binary = || {
+            if on_disk {
+                is_binary_file(path, &self.binary_extensions)
+            } else {
+                has_binary_extension(path, &self.binary_extensions)
+            }
+        };
+        let applies = rule.is_enabled()
+            && !rule.should_skip_file(path)
+            && !(rule.definition.text_only && binary())
+            && (!rule.definition.executable_only || (on_disk && is_executable(path)));
+        if applies {
+            rule.match_count.fetch_add(1, AtomicOrdering::Relaxed);
+        }
+        applies
+    }
+
+    /// Names of enabled rules that have not matched any checked file so far.
+    pub fn unmatched_rules(&self) -> Vec<&str> {
         self.rules
             .iter()
-            .filter(|r| r.is_enabled() && !r.should_skip_file(path))
-            .max_by(|a, b| match (a.get_priority(), b.get_priority()) {
-                (Some(p1), Some(p2)) => p1.cmp(&p2),
-                (Some(_), None) => std::cmp::Ordering::Greater,
-                (None, Some(_)) => std::cmp::Ordering::Less,
-                (None, None) => std::cmp::Ordering::Equal,
-            })
+            .filter(|r| r.is_enabled() && r.match_count.load(AtomicOrdering::Relaxed) == 0)
+            .map(|r| r.name.as_str())
+            .collect()
+    }
+
+    /// Rules that may match `path`: those whose includes match it, plus
+    /// those that cannot be ruled out by the index. A superset of the
+    /// rules `applies_to` accepts, in insertion order.
+    fn candidate_rules(&self, path: &Path) -> Vec<&ConfigurableRule> {
+        match self.index.get_or_init(|| RuleIndex::build(&self.rules)) {
+            Some(index) => index
+                .candidates(path)
+                .into_iter()
+                .map(|i| &self.rules[i])
+                .collect(),
+            None => self.rules.iter().collect(),
+        }
+    }
+
+    /// All enabled rules matching `path`, best match first.
+    fn matching_rules(&self, path: &Path) -> Vec<&ConfigurableRule> {
+        let mut matching: Vec<_> = self
+            .candidate_rules(path)
+            .into_iter()
+            .filter(|r| self.applies_to(r, path, true))
+            .collect();
+        matching.sort_by(|a, b| Self::rule_order(b, a));
+        matching
+    }
+
+    fn best_rule_for(&self, path: &Path) -> Option<&ConfigurableRule> {
+        self.best_rule_matching(path, true)
+    }
+
+    /// Like [`best_rule_for`](Self::best_rule_for), for a path whose size is
+    /// known but which is not read from disk.
+    fn best_blob_rule_for(&self, path: &Path) -> Option<&ConfigurableRule> {
+        self.best_rule_matching(path, false)
+    }
+
+    fn best_rule_matching(&self, path: &Path, on_disk: bool) -> Option<&ConfigurableRule> {
+        self.candidate_rules(path)
+            .into_iter()
+            .filter(|r| self.applies_to(r, path, on_disk))
+            .max_by(|a, b| Self::rule_order(a, b))
     }
 
     pub fn check_file(&self, path: &Path) -> Result<Vec<Violation>> {
-        match self.best_rule_for(path) {
-            Some(rule) => rule.check(path),
-            None => Ok(vec![]),
+        self.check_file_counted(path, None)
+    }
+
+    fn check_file_counted(&self, path: &Path, stats: Option<&RunStats>) -> Result<Vec<Violation>> {
+        let mut violations = self.check_configured(path, stats)?;
+        for (rule, elapsed) in self.custom_rules.iter().zip(&self.custom_elapsed) {
+            let found = self.timed(elapsed, || rule.check(path))?;
+            if let Some(stats) = stats {
+                stats.record(rule.name(), &found);
+            }
+            violations.extend(found);
+        }
+
+        if !violations.is_empty()
+            && self.symlink_report != SymlinkReport::Link
+            && path.is_symlink()
+            && let Ok(target) = std::fs::canonicalize(path)
+        {
+            for v in &mut violations {
+                match self.symlink_report {
+                    SymlinkReport::Target => v.path = target.clone(),
+                    SymlinkReport::Both => {
+                        v.message =
+                            format!("{} ({} -> {})", v.message, path.display(), target.display());
+                    }
+                    SymlinkReport::Link => {}
+                }
+            }
+        }
+        Ok(violations)
+    }
+
+    fn check_configured(&self, path: &Path, stats: Option<&RunStats>) -> Result<Vec<Violation>> {
+        let rule = if self.warn_on_rule_overlap {
+            self.best_rule_reporting_overlap(path)
+        } else {
+            self.best_rule_for(path)
+        };
+        let Some(rule) = rule else {
+            return Ok(vec![]);
+        };
+
+        let max_size_override = self.size_overrides.get(path).copied();
+        let violations = self.timed(&rule.elapsed_nanos, || {
+            rule.check_with(
+                path,
+                &CheckContext {
+                    max_size_override,
+                    tracked: self.tracked.as_ref(),
+                    measured_size: self.normalized_size(path),
+                },
+            )
+        })?;
+        if let Some(stats) = stats {
+            stats.record(&rule.name, &violations);
+        }
+
+        if tracing::enabled!(Level::TRACE) {
+            let size = std::fs::metadata(path)
+                .map(|m| format_size(m.len()))
+                .unwrap_or_else(|_| "unknown size".to_string());
+            let limit = max_size_override
+                .or(rule.max_size_for(path))
+                .map(format_size)
+                .unwrap_or_else(|| "no limit".to_string());
+            let result = if violations.is_empty() {
+                "ok"
+            } else {
+                "violation"
+            };
+            trace!(
+                "{} -> {} ({} vs {}) = {}",
+                path.display(),
+                rule.name,
+                size,
+                limit,
+                result
+            );
+        }
+
+        if self.record_passes
+            && violations.is_empty()
+            && let Ok(metadata) = std::fs::metadata(path)
+        {
+            self.passes.lock().unwrap().push(PassedFile {
+                path: path.to_path_buf(),
+                size: metadata.len(),
+                rule: rule.name.clone(),
+            });
+        }
+
+        Ok(violations)
+    }
+
+    fn best_rule_reporting_overlap(&self, path: &Path) -> Option<&ConfigurableRule> {
+        // The default rule is the catch-all fallback, so it never counts as shadowed.
+        let matching = self.matching_rules(path);
+        let specific: Vec<_> = matching
+            .iter()
+            .filter(|r| r.get_priority().is_some())
+            .collect();
+        if let [chosen, shadowed @ ..] = specific.as_slice()
+            && !shadowed.is_empty()
+        {
+            self.record_overlap(RuleOverlap {
+                path: path.to_path_buf(),
+                chosen: chosen.name.clone(),
+                shadowed: shadowed.iter().map(|r| r.name.clone()).collect(),
+            });
+        }
+
+        matching.first().copied()
+    }
+
+    fn record_overlap(&self, overlap: RuleOverlap) {
+        let mut seen = self.overlaps.lock().unwrap();
+        if !seen.paths.insert(overlap.path.clone()) {
+            return;
         }
+        warn!(
+            "{}: rule '{}' applied, shadowing {}",
+            overlap.path.display(),
+            overlap.chosen,
+            overlap
+                .shadowed
+                .iter()
+                .map(|name| format!("'{name}'"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        seen.overlaps.push(overlap);
+    }
+
+    /// The `limit` largest files that passed, largest first. Empty unless
+    /// [`Self::with_pass_records`] was enabled.
+    pub fn largest_passed(&self, limit: usize) -> Vec<PassedFile> {
+        let mut passes = self.passes.lock().unwrap().clone();
+        passes.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+        passes.truncate(limit);
+        passes
+    }
+
+    /// Rule overlaps seen so far, one per path.
+    pub fn rule_overlaps(&self) -> Vec<RuleOverlap> {
+        self.overlaps.lock().unwrap().overlaps.clone()
     }
 
     pub fn check_files(&self, paths: &[std::path::PathBuf]) -> Result<Vec<Violation>> {
+        self.check_files_with_stats(paths)
+            .map(|(violations, _)| violations)
+    }
+
+    /// Like [`check_files`](Self::check_files), but also counting for each
+    /// rule the files it was applied to and the violations it found. The
+    /// counts include violations later dropped by `max_violations` or
+    /// `fail_fast`.
+    pub fn check_files_with_stats(
+        &self,
+        paths: &[std::path::PathBuf],
+    ) -> Result<(Vec<Violation>, RunStats)> {
         let _span = span!(Level::DEBUG, "check_files", file_count = paths.len()).entered();
 
-        let violations: Result<Vec<_>> =
-            paths.par_iter().map(|path| self.check_file(path)).collect();
+        let stats = RunStats::new(
+            self.rules
+                .iter()
+                .filter(|r| r.is_enabled())
+                .map(|r| r.name.as_str())
+                .chain(self.custom_rules.iter().map(|r| r.name())),
+        );
 
-        let all_violations: Vec<Violation> = violations?.into_iter().flatten().collect();
+        let found = AtomicUsize::new(0);
+        let found_error = AtomicBool::new(false);
+        let violations: Result<Vec<_>> = paths
+            .par_iter()
+            .map(|path| {
+                let limit_reached = self
+                    .max_violations
+                    .is_some_and(|max| found.load(AtomicOrdering::Relaxed) >= max);
+                let fail_fast = self.fail_fast && found_error.load(AtomicOrdering::Relaxed);
+                if limit_reached || fail_fast {
+                    self.stopped_early.store(true, AtomicOrdering::Relaxed);
+                    return Ok(vec![]);
+                }
+
+                // A file deleted since discovery is skipped, not an error
+                let violations = match self.check_file_counted(path, Some(&stats)) {
+                    Err(e) if e.is_not_found() => {
+                        debug!("{} vanished before it was checked", path.display());
+                        return Ok(vec![]);
+                    }
+                    result => result?,
+                };
+                self.partial.record(&violations);
+                found.fetch_add(violations.len(), AtomicOrdering::Relaxed);
+                if violations.iter().any(|v| v.severity == Severity::Error) {
+                    found_error.store(true, AtomicOrdering::Relaxed);
+                }
+                Ok(violations)
+            })
+            .collect();
+
+        let mut all_violations: Vec<Violation> = violations?.into_iter().flatten().collect();
+        if self.fail_fast
+            && let Some(first_error) = all_violations
+                .iter()
+                .position(|v| v.severity == Severity::Error)
+        {
+            all_violations = vec![all_violations.swap_remove(first_error)];
+            self.stopped_early.store(true, AtomicOrdering::Relaxed);
+        }
+        if let Some(max) = self.max_violations
+            && all_violations.len() > max
+        {
+            all_violations.truncate(max);
+            self.stopped_early.store(true, AtomicOrdering::Relaxed);
+        }
 
         debug!(
             "Found {} total violations across {} files",
             all_violations.len(),
             paths.len()
         );
-        Ok(all_violations)
+        Ok((all_violations, stats))
+    }
+
+    /// Like [`check_files`](Self::check_files), but with sizes that are
+    /// already known (e.g. from `git ls-tree`), so files are never stat'ed
+    /// and need not exist on disk.
+    pub fn check_sized_files(&self, files: &[(PathBuf, u64)]) -> Result<Vec<Violation>> {
+        let violations: Result<Vec<_>> = files
+            .par_iter()
+            .map(|(path, size)| {
+                let Some(rule) = self.best_blob_rule_for(path) else {
+                    return Ok(vec![]);
+                };
+                let violations = rule.check_blob(path, *size)?;
+                self.partial.record(&violations);
+                Ok(violations)
+            })
+            .collect();
+
+        Ok(violations?.into_iter().flatten().collect())
     }
 
     pub fn check_history_blobs(&self, blobs: &[crate::git::HistoryBlob]) -> Result<Vec<Violation>> {
@@ -139,38 +911,42 @@ impl RuleEngine {
             .map(|blob| {
                 let path = Path::new(&blob.path);
 
-                let Some(rule) = self.best_rule_for(path) else {
+                let Some(rule) = self.best_blob_rule_for(path) else {
                     return Ok(vec![]);
                 };
 
-                let blob_violations = rule.check_blob(path, blob.size)?;
-
-                Ok(blob_violations
+                let blob_violations: Vec<_> = rule
+                    .check_blob(path, blob.size)?
                     .into_iter()
                     .map(|mut v| {
                         v.commit = Some(blob.commit.clone());
                         v
                     })
-                    .collect::<Vec<_>>())
+                    .collect();
+                self.partial.record(&blob_violations);
+
+                Ok(blob_violations)
             })
             .collect();
 
         let all_violations: Vec<Violation> = violations?.into_iter().flatten().collect();
 
-        // Per-path deduplication: keep only the largest violation per file path
-        let mut best: std::collections::HashMap<std::path::PathBuf, Violation> =
-            std::collections::HashMap::new();
+        // Per-path deduplication: keep only the largest violation per file
+        // path, the first blob in `blobs` winning ties
+        let mut index: HashMap<PathBuf, usize> = HashMap::new();
+        let mut best: Vec<Violation> = Vec::new();
         for v in all_violations {
-            best.entry(v.path.clone())
-                .and_modify(|existing| {
-                    if v.sort_key > existing.sort_key {
-                        *existing = v.clone();
-                    }
-                })
-                .or_insert(v);
+            match index.get(&v.path) {
+                Some(&i) if v.sort_key > best[i].sort_key => best[i] = v,
+                Some(_) => {}
+                None => {
+                    index.insert(v.path.clone(), best.len());
+                    best.push(v);
+                }
+            }
         }
 
-        Ok(best.into_values().collect())
+        Ok(best)
     }
 
     pub fn suggestions(&self) -> std::collections::HashMap<&str, &str> {
@@ -201,26 +977,17 @@ impl RuleEngine {
         let mut all_rules = self.get_rule_info();
 
         if let Some(rules_config) = &config.rules {
-            for (name, rule_def) in &rules_config.rules {
+            let mut definitions: Vec<_> = rules_config.rules.iter().collect();
+            definitions.sort_by_key(|(name, _)| *name);
+            for (name, rule_def) in definitions {
                 if !rule_def.enabled && !all_rules.iter().any(|r| r.name == *name) {
-                    let max_size = rule_def
-                        .max_size
-                        .as_ref()
-                        .and_then(|s| crate::rules::parse_size_string(s).ok());
-                    let warn_size = rule_def
-                        .warn_size
-                        .as_ref()
-                        .and_then(|s| crate::rules::parse_size_string(s).ok());
-
                     all_rules.push(RuleInfo {
                         name: name.clone(),
                         description: rule_def.description.clone(),
                         enabled: rule_def.enabled,
                         priority: Some(rule_def.priority),
-                        max_size,
-                        warn_size,
-                        max_size_str: rule_def.max_size.clone(),
-                        warn_size_str: rule_def.warn_size.clone(),
+                        max_size: rule_def.max_size.clone(),
+                        warn_size: rule_def.warn_size.clone(),
                         includes: rule_def.includes.clone(),
                         excludes: rule_def.excludes.clone(),
                         warn_on_match: rule_def.warn_on_match,
@@ -247,28 +1014,31 @@ pub struct ConfigurableRule {
     definition: RuleDefinition,
     max_size: Option<u64>,
     warn_size: Option<u64>,
+    max_extracted_size: Option<u64>,
     includes: globset::GlobSet,
     excludes: globset::GlobSet,
+    // One matcher and limit per `scoped_limits` entry, in order
+    scoped_limits: Vec<(globset::GlobMatcher, u64)>,
+    root: Option<PathBuf>,
+    boundary: Boundary,
+    error_grace_percent: Option<f64>,
+    match_count: AtomicUsize,
+    elapsed_nanos: AtomicU64,
 }
 
 impl ConfigurableRule {
     pub fn new(name: String, definition: RuleDefinition) -> Result<Self> {
-        let max_size = definition
-            .max_size
+        let max_size = definition.max_size.as_ref().map(SizeThreshold::bytes);
+        let warn_size = definition.warn_size.as_ref().map(SizeThreshold::bytes);
+        let max_extracted_size = definition
+            .max_extracted_size
             .as_ref()
-            .map(|s| parse_size_string(s))
-            .transpose()?;
-
-        let warn_size = definition
-            .warn_size
-            .as_ref()
-            .map(|s| parse_size_string(s))
-            .transpose()?;
+            .map(SizeThreshold::bytes);
 
         // Build includes globset
         let mut includes_builder = globset::GlobSetBuilder::new();
         for pattern in &definition.includes {
-            let expanded_pattern = expand_if_path(pattern);
+            let expanded_pattern = expand_pattern(pattern, definition.match_mode);
             let glob = globset::Glob::new(&expanded_pattern)
                 .map_err(|e| SizelintError::config_invalid_pattern(pattern.clone(), e))?;
             includes_builder.add(glob);
@@ -284,7 +1054,7 @@ impl ConfigurableRule {
         // Build excludes globset
         let mut excludes_builder = globset::GlobSetBuilder::new();
         for pattern in &definition.excludes {
-            let expanded_pattern = expand_if_path(pattern);
+            let expanded_pattern = expand_pattern(pattern, definition.match_mode);
             let glob = globset::Glob::new(&expanded_pattern)
                 .map_err(|e| SizelintError::config_invalid_pattern(pattern.clone(), e))?;
             excludes_builder.add(glob);
@@ -297,36 +1067,178 @@ impl ConfigurableRule {
             )
         })?;
 
+        let scoped_limits = definition
+            .scoped_limits
+            .iter()
+            .map(|scope| {
+                let glob = globset::Glob::new(&scope.path)
+                    .map_err(|e| SizelintError::config_invalid_pattern(scope.path.clone(), e))?;
+                Ok((glob.compile_matcher(), scope.max.bytes()))
+            })
+            .collect::<Result<_>>()?;
+
         Ok(Self {
             name,
             definition,
             max_size,
             warn_size,
+            max_extracted_size,
             includes,
             excludes,
+            scoped_limits,
+            root: None,
+            boundary: Boundary::default(),
+            error_grace_percent: None,
+            match_count: AtomicUsize::new(0),
+            elapsed_nanos: AtomicU64::new(0),
         })
     }
 
+    /// Directory that `match_mode = "path"` patterns are relative to.
+    pub fn with_root(mut self, root: &Path) -> Self {
+        self.root = Some(root.to_path_buf());
+        self
+    }
+
+    /// Whether a file exactly at a size limit is a violation.
+    pub fn with_boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    /// Report files at most `percent` over `max_size` as warnings instead
+    /// of errors.
+    pub fn with_error_grace(mut self, percent: Option<f64>) -> Self {
+        self.error_grace_percent = percent;
+        self
+    }
+
+    /// Error for a file over `max_size`, or warning while it is within the
+    /// error grace.
+    fn max_size_severity(&self, size: u64, max_size: u64) -> Severity {
+        match self.error_grace_percent {
+            Some(percent) if size as f64 <= max_size as f64 * (1.0 + percent / 100.0) => {
+                Severity::Warning
+            }
+            _ => Severity::Error,
+        }
+    }
+
+    fn grace_note(&self, severity: Severity) -> String {
+        match (severity, self.error_grace_percent) {
+            (Severity::Warning, Some(percent)) => format!(" (within the {percent}% error grace)"),
+            _ => String::new(),
+        }
+    }
+
+    fn exceeds(&self, size: u64, limit: u64) -> bool {
+        match self.boundary {
+            Boundary::Inclusive => size > limit,
+            Boundary::Exclusive => size >= limit,
+        }
+    }
+
+    fn within(&self, limit: u64) -> String {
+        match self.boundary {
+            Boundary::Inclusive => format!("≤ {}", format_size(limit)),
+            Boundary::Exclusive => format!("< {}", format_size(limit)),
+        }
+    }
+
+    /// The part of `path` that includes and excludes are matched against.
+    fn match_target<'a>(&self, path: &'a Path) -> &'a Path {
+        match self.definition.match_mode {
+            MatchMode::Auto => path,
+            MatchMode::Path => self
+                .root
+                .as_deref()
+                .and_then(|root| path.strip_prefix(root).ok())
+                .unwrap_or(path),
+            MatchMode::Basename => path.file_name().map(Path::new).unwrap_or(path),
+        }
+    }
+
+    /// The `max_size` for `path`: the limit of the first scope matching it
+    /// relative to the root, else the rule's own.
+    fn max_size_for(&self, path: &Path) -> Option<u64> {
+        let rel = self
+            .root
+            .as_deref()
+            .and_then(|root| path.strip_prefix(root).ok())
+            .unwrap_or(path);
+        self.scoped_limits
+            .iter()
+            .find(|(glob, _)| glob.is_match(rel))
+            .map(|&(_, max)| max)
+            .or(self.max_size)
+    }
+
     pub fn should_skip_file(&self, path: &Path) -> bool {
+        let target = self.match_target(path);
+
         // If includes are specified, file must match at least one include pattern
-        if !self.definition.includes.is_empty() && !self.includes.is_match(path) {
+        if !self.definition.includes.is_empty() && !self.includes.is_match(target) {
             return true;
         }
 
         // If any exclude pattern matches, skip the file
-        if self.excludes.is_match(path) {
+        if self.excludes.is_match(target) {
             return true;
         }
 
         false
     }
 
-    fn get_file_size(&self, path: &Path) -> Result<u64> {
-        let metadata = std::fs::metadata(path).map_err(|e| {
-            SizelintError::filesystem("get file metadata".to_string(), path.to_path_buf(), e)
-        })?;
-        Ok(metadata.len())
-    }
+    /// An error for an extensionless file of `size` under `require_extension`.
+    fn extensionless_violation(&self, path: &Path, size: u64) -> Option<Violation> {
+        let min_size = self
+            .definition
+            .extensionless_min_size
+            .as_ref()
+            .map_or(0, SizeThreshold::bytes);
+        if !self.definition.require_extension
+            || path.extension().is_some()
+            || !self.exceeds(size, min_size)
+        {
+            return None;
+        }
+
+        Some(
+            Violation::new(
+                path.to_path_buf(),
+                self.name.clone(),
+                self.message_for(
+                    Severity::Error,
+                    if min_size == 0 {
+                        "File has no extension".to_string()
+                    } else {
+                        format!(
+                            "File without an extension exceeds {}",
+                            format_size(min_size)
+                        )
+                    },
+                ),
+                Severity::Error,
+            )
+            .with_actual_value(format_size(size))
+            .with_expected_value("a file extension".to_string())
+            .with_sort_key(size),
+        )
+    }
+
+    fn get_file_size(&self, path: &Path) -> Result<u64> {
+        let metadata = std::fs::metadata(path).map_err(|e| {
+            SizelintError::filesystem("get file metadata".to_string(), path.to_path_buf(), e)
+        })?;
+        Ok(metadata.len())
+    }
+
+    fn size_of(&self, path: &Path, ctx: &CheckContext) -> Result<u64> {
+        match ctx.measured_size {
+            Some(size) => Ok(size),
+            None => self.get_file_size(path),
+        }
+    }
 
     pub fn get_priority(&self) -> Option<i32> {
         if self.name == "default" {
@@ -342,10 +1254,8 @@ impl ConfigurableRule {
             description: self.definition.description.clone(),
             enabled: self.definition.enabled,
             priority: self.get_priority(),
-            max_size: self.max_size,
-            warn_size: self.warn_size,
-            max_size_str: self.definition.max_size.clone(),
-            warn_size_str: self.definition.warn_size.clone(),
+            max_size: self.definition.max_size.clone(),
+            warn_size: self.definition.warn_size.clone(),
             includes: self.definition.includes.clone(),
             excludes: self.definition.excludes.clone(),
             warn_on_match: self.definition.warn_on_match,
@@ -369,59 +1279,119 @@ impl ConfigurableRule {
             return Ok(violations);
         }
 
-        if let Some(max_size) = self.max_size
-            && size > max_size
+        if let Some(violation) = self.extensionless_violation(path, size) {
+            violations.push(violation);
+            return Ok(violations);
+        }
+
+        if let Some(max_size) = self.max_size_for(path)
+            && self.exceeds(size, max_size)
         {
+            let severity = self.max_size_severity(size, max_size);
             violations.push(
                 Violation::new(
                     path.to_path_buf(),
                     self.name.clone(),
-                    format!(
-                        "File exceeds maximum allowed size {}",
-                        format_size(max_size)
+                    self.message_for(
+                        severity,
+                        format!(
+                            "File exceeds maximum allowed size {}{}",
+                            format_size(max_size),
+                            self.grace_note(severity)
+                        ),
                     ),
-                    Severity::Error,
+                    severity,
                 )
                 .with_actual_value(format_size(size))
-                .with_expected_value(format!("≤ {}", format_size(max_size)))
-                .with_sort_key(size),
+                .with_expected_value(self.within(max_size))
+                .with_sort_key(size)
+                .with_limit(max_size),
             );
             return Ok(violations);
         }
 
         if let Some(warn_size) = self.warn_size
-            && size > warn_size
+            && self.exceeds(size, warn_size)
         {
             violations.push(
                 Violation::new(
                     path.to_path_buf(),
                     self.name.clone(),
-                    format!("File exceeds warning threshold {}", format_size(warn_size)),
+                    self.message_for(
+                        Severity::Warning,
+                        format!("File exceeds warning threshold {}", format_size(warn_size)),
+                    ),
                     Severity::Warning,
                 )
                 .with_actual_value(format_size(size))
-                .with_expected_value(format!("≤ {}", format_size(warn_size)))
-                .with_sort_key(size),
+                .with_expected_value(self.within(warn_size))
+                .with_sort_key(size)
+                .with_limit(warn_size),
             );
         }
 
         Ok(violations)
     }
 
+    /// The configured `error_message`/`warn_message` for `severity`, or
+    /// `default` when none is set.
+    fn message_for(&self, severity: Severity, default: String) -> String {
+        let custom = match severity {
+            Severity::Error => &self.definition.error_message,
+            Severity::Warning => &self.definition.warn_message,
+        };
+        custom.clone().unwrap_or(default)
+    }
+
     pub fn check(&self, path: &Path) -> Result<Vec<Violation>> {
+        self.check_with(path, &CheckContext::default())
+    }
+
+    /// Like [`check`](Self::check), with per-file inputs from the engine.
+    pub fn check_with(&self, path: &Path, ctx: &CheckContext) -> Result<Vec<Violation>> {
         let mut violations = Vec::new();
 
         if self.should_skip_file(path) {
             return Ok(violations);
         }
 
+        if self.definition.require_tracked
+            && let Some(tracked) = ctx.tracked
+            && !tracked.contains(path)
+        {
+            violations.push(
+                Violation::new(
+                    path.to_path_buf(),
+                    self.name.clone(),
+                    self.message_for(
+                        Severity::Error,
+                        format!("File {} is not tracked by git", path.display()),
+                    ),
+                    Severity::Error,
+                )
+                .with_actual_value("untracked".to_string())
+                .with_expected_value("tracked".to_string()),
+            );
+            return Ok(violations);
+        }
+
+        if self.definition.require_extension
+            && let Some(violation) = self.extensionless_violation(path, self.size_of(path, ctx)?)
+        {
+            violations.push(violation);
+            return Ok(violations);
+        }
+
         // Check match-based violations first
         if self.definition.error_on_match {
             violations.push(
                 Violation::new(
                     path.to_path_buf(),
                     self.name.clone(),
-                    format!("File {} matches rule pattern", path.display()),
+                    self.message_for(
+                        Severity::Error,
+                        format!("File {} matches rule pattern", path.display()),
+                    ),
                     Severity::Error,
                 )
                 .with_actual_value("matched".to_string())
@@ -435,7 +1405,10 @@ impl ConfigurableRule {
                 Violation::new(
                     path.to_path_buf(),
                     self.name.clone(),
-                    format!("File {} matches rule pattern", path.display()),
+                    self.message_for(
+                        Severity::Warning,
+                        format!("File {} matches rule pattern", path.display()),
+                    ),
                     Severity::Warning,
                 )
                 .with_actual_value("matched".to_string())
@@ -449,43 +1422,80 @@ impl ConfigurableRule {
         }
 
         // Check size-based violations
-        let file_size = self.get_file_size(path)?;
+        let file_size = self.size_of(path, ctx)?;
 
         // Check error threshold (max_size)
-        if let Some(max_size) = self.max_size
-            && file_size > max_size
+        if let Some(max_size) = ctx.max_size_override.or(self.max_size_for(path))
+            && self.exceeds(file_size, max_size)
         {
+            let severity = self.max_size_severity(file_size, max_size);
             violations.push(
                 Violation::new(
                     path.to_path_buf(),
                     self.name.clone(),
-                    format!(
-                        "File exceeds maximum allowed size {}",
-                        format_size(max_size)
+                    self.message_for(
+                        severity,
+                        format!(
+                            "File exceeds maximum allowed size {}{}",
+                            format_size(max_size),
+                            self.grace_note(severity)
+                        ),
                     ),
-                    Severity::Error,
+                    severity,
                 )
                 .with_actual_value(format_size(file_size))
-                .with_expected_value(format!("≤ {}", format_size(max_size)))
-                .with_sort_key(file_size),
+                .with_expected_value(self.within(max_size))
+                .with_sort_key(file_size)
+                .with_limit(max_size),
+            );
+            return Ok(violations);
+        }
+
+        // Check the extracted size of archives
+        if self.definition.inspect_archives
+            && let Some(max_extracted) = self.max_extracted_size
+            && let Some(extracted) = crate::archive::extracted_size(path)?
+            && self.exceeds(extracted, max_extracted)
+        {
+            violations.push(
+                Violation::new(
+                    path.to_path_buf(),
+                    self.name.clone(),
+                    self.message_for(
+                        Severity::Error,
+                        format!(
+                            "Archive expands beyond maximum extracted size {}",
+                            format_size(max_extracted)
+                        ),
+                    ),
+                    Severity::Error,
+                )
+                .with_actual_value(format!("{} extracted", format_size(extracted)))
+                .with_expected_value(self.within(max_extracted))
+                .with_sort_key(extracted)
+                .with_limit(max_extracted),
             );
             return Ok(violations);
         }
 
         // Check warning threshold (warn_size)
         if let Some(warn_size) = self.warn_size
-            && file_size > warn_size
+            && self.exceeds(file_size, warn_size)
         {
             violations.push(
                 Violation::new(
                     path.to_path_buf(),
                     self.name.clone(),
-                    format!("File exceeds warning threshold {}", format_size(warn_size)),
+                    self.message_for(
+                        Severity::Warning,
+                        format!("File exceeds warning threshold {}", format_size(warn_size)),
+                    ),
                     Severity::Warning,
                 )
                 .with_actual_value(format_size(file_size))
-                .with_expected_value(format!("≤ {}", format_size(warn_size)))
-                .with_sort_key(file_size),
+                .with_expected_value(self.within(warn_size))
+                .with_sort_key(file_size)
+                .with_limit(warn_size),
             );
         }
 
@@ -493,16 +1503,272 @@ impl ConfigurableRule {
     }
 }
 
-fn expand_if_path(pattern: &str) -> String {
-    // If pattern contains slash, treat as path
-    // Otherwise, treat as filename pattern and prepend with **/ for recursive matching
-    if pattern.contains('/') {
+/// Flag files that grew by more than `max_growth` bytes since the base.
+///
+/// Files missing from `base_sizes` are new, so their full size is growth.
+pub fn check_growth(
+    paths: &[std::path::PathBuf],
+    base_sizes: &std::collections::HashMap<std::path::PathBuf, u64>,
+    max_growth: u64,
+) -> Result<Vec<Violation>> {
+    let mut violations = Vec::new();
+
+    for path in paths {
+        let current = std::fs::metadata(path)
+            .map_err(|e| {
+                SizelintError::filesystem("get file metadata".to_string(), path.clone(), e)
+            })?
+            .len();
+        let base = base_sizes.get(path).copied().unwrap_or(0);
+        let growth = current.saturating_sub(base);
+
+        if growth > max_growth {
+            violations.push(
+                Violation::new(
+                    path.clone(),
+                    "max_growth".to_string(),
+                    format!("File grew by more than {}", format_size(max_growth)),
+                    Severity::Error,
+                )
+                .with_actual_value(format!("+{}", format_size(growth)))
+                .with_expected_value(format!("≤ +{}", format_size(max_growth)))
+                .with_sort_key(growth)
+                .with_limit(max_growth),
+            );
+        }
+    }
+
+    Ok(violations)
+}
+
+/// Flag the repository as a whole when its total tracked size grew by more
+/// than `max_tree_growth` bytes. Reported against the path `.`.
+pub fn check_tree_growth(
+    base_total: u64,
+    head_total: u64,
+    max_tree_growth: u64,
+) -> Option<Violation> {
+    let growth = head_total.saturating_sub(base_total);
+    (growth > max_tree_growth).then(|| {
+        Violation::new(
+            PathBuf::from("."),
+            "max_tree_growth".to_string(),
+            format!(
+                "Repository grew by more than {}",
+                format_size(max_tree_growth)
+            ),
+            Severity::Error,
+        )
+        .with_actual_value(format!("+{}", format_size(growth)))
+        .with_expected_value(format!("≤ +{}", format_size(max_tree_growth)))
+        .with_sort_key(growth)
+        .with_limit(max_tree_growth)
+    })
+}
+
+/// The distinct sizes each path was committed at across a history walk,
+/// counting only versions of at least `min_size` bytes.
+#[derive(Debug, Default)]
+pub struct SizeRevisions {
+    min_size: u64,
+    sizes: HashMap<PathBuf, std::collections::BTreeSet<u64>>,
+}
+
+impl SizeRevisions {
+    pub fn new(min_size: u64) -> Self {
+        Self {
+            min_size,
+            sizes: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, blobs: &[crate::git::HistoryBlob]) {
+        for blob in blobs.iter().filter(|blob| blob.size >= self.min_size) {
+            self.sizes
+                .entry(PathBuf::from(&blob.path))
+                .or_default()
+                .insert(blob.size);
+        }
+    }
+
+    /// Flag paths committed at more than `max_revisions` distinct sizes,
+    /// a sign of repeated large rewrites bloating the pack files.
+    pub fn check(&self, max_revisions: usize) -> Vec<Violation> {
+        self.sizes
+            .iter()
+            .filter(|(_, sizes)| sizes.len() > max_revisions)
+            .map(|(path, sizes)| {
+                Violation::new(
+                    path.clone(),
+                    "max_size_revisions".to_string(),
+                    format!(
+                        "File was committed at more than {max_revisions} distinct sizes of at least {}",
+                        format_size(self.min_size)
+                    ),
+                    Severity::Error,
+                )
+                .with_actual_value(format!("{} sizes", sizes.len()))
+                .with_expected_value(format!("≤ {max_revisions} sizes"))
+                .with_sort_key(sizes.last().copied().unwrap_or(0))
+            })
+            .collect()
+    }
+}
+
+/// Flag the `.git` directory when its on-disk size exceeds `max_gitdir_size`.
+pub fn check_gitdir_size(git_dir: &Path, size: u64, max_gitdir_size: u64) -> Option<Violation> {
+    (size > max_gitdir_size).then(|| {
+        Violation::new(
+            git_dir.to_path_buf(),
+            "max_gitdir_size".to_string(),
+            format!(
+                "Git directory exceeds maximum size {}",
+                format_size(max_gitdir_size)
+            ),
+            Severity::Error,
+        )
+        .with_actual_value(format_size(size))
+        .with_expected_value(format!("≤ {}", format_size(max_gitdir_size)))
+        .with_sort_key(size)
+        .with_limit(max_gitdir_size)
+    })
+}
+
+/// Whether `path` has any execute bit set. Always false off Unix and for
+/// files that cannot be read (e.g. history blobs).
+pub fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        false
+    }
+}
+
+/// Whether `path` should be treated as binary.
+///
+/// Files whose extension is listed in `binary_extensions` are binary without
+/// being read. Otherwise the leading bytes are sniffed for a NUL byte; files
+/// that cannot be read (e.g. history blobs) or are not regular files (e.g.
+/// FIFOs) count as text.
+pub fn is_binary_file(path: &Path, binary_extensions: &[String]) -> bool {
+    if has_binary_extension(path, binary_extensions) {
+        return true;
+    }
+
+    if !crate::fs::is_regular_file(path) {
+        return false;
+    }
+    let Ok(file) = std::fs::File::open(path) else {
+        return false;
+    };
+    let mut buf = Vec::with_capacity(BINARY_SNIFF_LEN);
+    match file.take(BINARY_SNIFF_LEN as u64).read_to_end(&mut buf) {
+        Ok(_) => buf.contains(&0),
+        Err(_) => false,
+    }
+}
+
+/// The number of bytes read from `reader` with each CRLF counted as one,
+/// a trailing `\r` being carried across chunk boundaries.
+fn crlf_normalized_len(mut reader: impl std::io::BufRead) -> std::io::Result<u64> {
+    let mut len = 0u64;
+    let mut pending_cr = false;
+    loop {
+        let chunk = reader.fill_buf()?;
+        if chunk.is_empty() {
+            return Ok(len);
+        }
+        let mut crlfs = chunk.windows(2).filter(|w| w == b"\r\n").count() as u64;
+        if pending_cr && chunk[0] == b'\n' {
+            crlfs += 1;
+        }
+        pending_cr = chunk[chunk.len() - 1] == b'\r';
+        len += chunk.len() as u64 - crlfs;
+        let consumed = chunk.len();
+        reader.consume(consumed);
+    }
+}
+
+/// Whether the extension of `path` is listed in `binary_extensions`.
+fn has_binary_extension(path: &Path, binary_extensions: &[String]) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| {
+            binary_extensions
+                .iter()
+                .any(|b| b.trim_start_matches('.').eq_ignore_ascii_case(ext))
+        })
+}
+
+fn expand_pattern(pattern: &str, mode: MatchMode) -> String {
+    // In auto mode a pattern with a slash is treated as a path; anything else
+    // is a filename pattern and gets **/ prepended for recursive matching
+    if mode != MatchMode::Auto || pattern.contains('/') {
         pattern.to_string()
     } else {
         format!("**/{pattern}")
     }
 }
 
+/// A size limit as written in the config, parsed to bytes once when it is
+/// read so that every later use agrees on the value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeThreshold {
+    raw: String,
+    bytes: u64,
+}
+
+impl SizeThreshold {
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// The size as written, e.g. `"10MB"`.
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+}
+
+impl FromStr for SizeThreshold {
+    type Err = SizelintError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(Self {
+            raw: s.to_string(),
+            bytes: parse_size_string(s)?,
+        })
+    }
+}
+
+impl fmt::Display for SizeThreshold {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl Serialize for SizeThreshold {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.raw)
+    }
+}
+
+impl<'de> Deserialize<'de> for SizeThreshold {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        raw.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 pub fn parse_size_string(size_str: &str) -> Result<u64> {
     let size_str = size_str.trim().to_uppercase();
 
@@ -513,19 +1779,25 @@ pub fn parse_size_string(size_str: &str) -> Result<u64> {
         ));
     }
 
-    let (number_part, unit_part) = if size_str.ends_with("TB") {
-        (&size_str[..size_str.len() - 2], "TB")
-    } else if size_str.ends_with("GB") {
-        (&size_str[..size_str.len() - 2], "GB")
-    } else if size_str.ends_with("MB") {
-        (&size_str[..size_str.len() - 2], "MB")
-    } else if size_str.ends_with("KB") {
-        (&size_str[..size_str.len() - 2], "KB")
-    } else if size_str.ends_with("B") {
-        (&size_str[..size_str.len() - 1], "B")
-    } else {
-        (size_str.as_str(), "B")
-    };
+    if let Some((percent, base)) = size_str.split_once("% OF ") {
+        return percent_of(&size_str, percent, parse_size_string(base)?);
+    }
+    if size_str.ends_with('%') {
+        return Err(SizelintError::invalid_size_format(
+            size_str.to_string(),
+            "Percentage sizes need a base, e.g. \"1% of 10GB\" or a quota".to_string(),
+        ));
+    }
+
+    // Longest matching suffix, so "10KB" is not read as "10K" bytes
+    let (number_part, multiplier) = UNIT_MULTIPLIERS
+        .iter()
+        .filter(|(unit, _)| size_str.ends_with(unit))
+        .max_by_key(|(unit, _)| unit.len())
+        .map_or((size_str.as_str(), 1), |(unit, multiplier)| {
+            (&size_str[..size_str.len() - unit.len()], *multiplier)
+        });
+    let number_part = number_part.trim_end();
 
     let number: f64 = number_part.parse().map_err(|_| {
         SizelintError::invalid_size_format(
@@ -541,23 +1813,36 @@ pub fn parse_size_string(size_str: &str) -> Result<u64> {
         ));
     }
 
-    let multiplier = match unit_part {
-        "B" => 1,
-        "KB" => BYTES_PER_KB,
-        "MB" => BYTES_PER_MB,
-        "GB" => BYTES_PER_GB,
-        "TB" => BYTES_PER_TB,
-        _ => {
-            return Err(SizelintError::invalid_size_format(
-                size_str.to_string(),
-                format!("Unknown size unit: {unit_part}"),
-            ));
-        }
-    };
-
     Ok((number * multiplier as f64) as u64)
 }
 
+/// Like [`parse_size_string`], but a bare percentage such as `"1%"` is
+/// taken of `quota`.
+pub fn parse_size_with_quota(size_str: &str, quota: Option<u64>) -> Result<u64> {
+    match (size_str.trim().strip_suffix('%'), quota) {
+        (Some(percent), Some(quota)) => percent_of(size_str, percent, quota),
+        _ => parse_size_string(size_str),
+    }
+}
+
+fn percent_of(size_str: &str, percent: &str, base: u64) -> Result<u64> {
+    let percent: f64 = percent.trim().parse().map_err(|_| {
+        SizelintError::invalid_size_format(
+            size_str.to_string(),
+            format!("Invalid percentage: {percent}"),
+        )
+    })?;
+
+    if !(0.0..=100.0).contains(&percent) {
+        return Err(SizelintError::invalid_size_format(
+            size_str.to_string(),
+            "Percentage must be between 0 and 100".to_string(),
+        ));
+    }
+
+    Ok((base as f64 * percent / 100.0) as u64)
+}
+
 pub fn format_size(size: u64) -> String {
     let mut size_f = size as f64;
     let mut unit_index = 0;
@@ -574,6 +1859,32 @@ pub fn format_size(size: u64) -> String {
     }
 }
 
+/// `size` in both unit families, e.g. `10.0 MiB (10.5 MB)`, for readers
+/// who would otherwise guess whether "MB" means 1000² or 1024² bytes.
+pub fn format_size_dual(size: u64) -> String {
+    let scale = |step: f64, units: &[&str]| {
+        let mut size_f = size as f64;
+        let mut unit_index = 0;
+        while size_f >= step && unit_index < units.len() - 1 {
+            size_f /= step;
+            unit_index += 1;
+        }
+        if unit_index == 0 {
+            format!("{size} B")
+        } else {
+            format!("{size_f:.1} {}", units[unit_index])
+        }
+    };
+    if size < 1_000 {
+        return format!("{size} B");
+    }
+    format!(
+        "{} ({})",
+        scale(SIZE_THRESHOLD, BINARY_UNITS),
+        scale(1_000.0, DECIMAL_UNITS)
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -592,6 +1903,1059 @@ mod tests {
         assert_eq!(parse_size_string("  2MB  ").unwrap(), 2 * 1024 * 1024);
     }
 
+    #[test]
+    fn test_parse_size_string_unit_words() {
+        assert_eq!(
+            parse_size_string("10 megabytes").unwrap(),
+            parse_size_string("10MB").unwrap()
+        );
+        assert_eq!(
+            parse_size_string("1 gibibyte").unwrap(),
+            parse_size_string("1GiB").unwrap()
+        );
+        assert_eq!(parse_size_string("1GiB").unwrap(), 1024 * 1024 * 1024);
+        assert_eq!(parse_size_string("1 Kilobyte").unwrap(), 1024);
+        assert_eq!(parse_size_string("512 bytes").unwrap(), 512);
+        assert_eq!(parse_size_string("2 TB").unwrap(), 2 * BYTES_PER_TB);
+        assert!(parse_size_string("10 megabits").is_err());
+    }
+
+    #[test]
+    fn test_invalid_threshold_fails_when_parsed() {
+        let err = "10XB".parse::<SizeThreshold>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid size format '10XB': Invalid size number: 10X"
+        );
+
+        let err = toml::from_str::<RuleDefinition>(r#"max_size = "10XB""#).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Invalid size format '10XB': Invalid size number: 10X")
+        );
+
+        let threshold: SizeThreshold = "1.5MB".parse().unwrap();
+        assert_eq!(threshold.as_str(), "1.5MB");
+        assert_eq!(threshold.bytes(), 1_572_864);
+    }
+
+    #[test]
+    fn test_parse_percentage_sizes() {
+        let ten_gb = 10 * 1024 * 1024 * 1024;
+        assert_eq!(parse_size_string("1% of 10GB").unwrap(), ten_gb / 100);
+        assert_eq!(parse_size_with_quota("50%", Some(2048)).unwrap(), 1024);
+        assert_eq!(
+            parse_size_with_quota("2MB", Some(2048)).unwrap(),
+            2 * 1024 * 1024
+        );
+        assert!(parse_size_string("1%").is_err());
+        assert!(parse_size_with_quota("150%", Some(2048)).is_err());
+    }
+
+    fn rule(name: &str, priority: i32) -> ConfigurableRule {
+        ConfigurableRule::new(
+            name.to_string(),
+            RuleDefinition {
+                enabled: true,
+                priority,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_equal_priority_ties_broken_by_name() {
+        let path = Path::new("src/main.rs");
+
+        let mut engine = RuleEngine::new();
+        engine.add_rule(rule("beta", 50));
+        engine.add_rule(rule("alpha", 50));
+        assert_eq!(engine.best_rule_for(path).unwrap().name(), "alpha");
+
+        let mut engine = RuleEngine::new();
+        engine.add_rule(rule("alpha", 50));
+        engine.add_rule(rule("beta", 50));
+        assert_eq!(engine.best_rule_for(path).unwrap().name(), "alpha");
+
+        engine.add_rule(rule("gamma", 60));
+        assert_eq!(engine.best_rule_for(path).unwrap().name(), "gamma");
+    }
+
+    #[test]
+    fn test_match_mode_controls_include_interpretation() {
+        let root = Path::new("/repo");
+        let include_foo = |match_mode| {
+            ConfigurableRule::new(
+                "foo".to_string(),
+                RuleDefinition {
+                    enabled: true,
+                    includes: vec!["foo.txt".to_string()],
+                    match_mode,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .with_root(root)
+        };
+
+        let basename = include_foo(MatchMode::Basename);
+        assert!(!basename.should_skip_file(&root.join("foo.txt")));
+        assert!(!basename.should_skip_file(&root.join("a/b/foo.txt")));
+
+        let path = include_foo(MatchMode::Path);
+        assert!(!path.should_skip_file(&root.join("foo.txt")));
+        assert!(path.should_skip_file(&root.join("a/b/foo.txt")));
+
+        let auto = include_foo(MatchMode::Auto);
+        assert!(!auto.should_skip_file(&root.join("a/b/foo.txt")));
+    }
+
+    #[test]
+    fn test_rule_overlap_is_reported_once_per_path() {
+        let include = |name: &str, priority: i32| {
+            ConfigurableRule::new(
+                name.to_string(),
+                RuleDefinition {
+                    enabled: true,
+                    priority,
+                    includes: vec!["*.png".to_string()],
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+
+        let mut engine = RuleEngine::new().with_rule_overlap_warnings(true);
+        engine.add_rule(rule("default", 0));
+        engine.add_rule(include("images", 80));
+        engine.add_rule(include("lfs", 50));
+
+        let tmp = tempfile::tempdir().unwrap();
+        let png = tmp.path().join("logo.png");
+        let txt = tmp.path().join("notes.txt");
+        std::fs::write(&png, "png").unwrap();
+        std::fs::write(&txt, "txt").unwrap();
+
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(Level::WARN)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            engine.check_file(&png).unwrap();
+            engine.check_file(&png).unwrap();
+            engine.check_file(&txt).unwrap();
+        });
+
+        let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let expected = format!("{}: rule 'images' applied, shadowing 'lfs'", png.display());
+        assert_eq!(output.matches(&expected).count(), 1, "in: {output}");
+
+        assert_eq!(
+            engine.rule_overlaps(),
+            vec![RuleOverlap {
+                path: png,
+                chosen: "images".to_string(),
+                shadowed: vec!["lfs".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_binary_extensions_skip_content_sniffing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let png = tmp.path().join("logo.png");
+        std::fs::write(&png, "plain text, not an image").unwrap();
+
+        assert!(!is_binary_file(&png, &[]));
+        assert!(is_binary_file(&png, &["png".to_string()]));
+        assert!(is_binary_file(&png, &[".PNG".to_string()]));
+
+        // Listed extensions are never read, so the file need not exist
+        assert!(is_binary_file(
+            &tmp.path().join("missing.png"),
+            &["png".to_string()]
+        ));
+
+        let bin = tmp.path().join("data.dat");
+        std::fs::write(&bin, [0x89, 0x00, 0x01]).unwrap();
+        assert!(is_binary_file(&bin, &[]));
+    }
+
+    #[test]
+    fn test_text_only_rule_skips_binary_extensions() {
+        let text_rule = ConfigurableRule::new(
+            "text".to_string(),
+            RuleDefinition {
+                enabled: true,
+                priority: 100,
+                text_only: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let mut engine = RuleEngine::new().with_binary_extensions(vec!["png".to_string()]);
+        engine.add_rule(rule("default", 0));
+        engine.add_rule(text_rule);
+
+        let path = Path::new("assets/logo.png");
+        assert_eq!(engine.best_rule_for(path).unwrap().name(), "default");
+        assert_eq!(
+            engine
+                .best_rule_for(Path::new("src/main.rs"))
+                .unwrap()
+                .name(),
+            "text"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_executable_only_rule_matches_executables() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let script = tmp.path().join("build");
+        let data = tmp.path().join("data.txt");
+        std::fs::write(&script, "#!/bin/sh\n").unwrap();
+        std::fs::write(&data, "data").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::set_permissions(&data, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let mut engine = RuleEngine::new();
+        engine.add_rule(rule("default", 0));
+        engine.add_rule(
+            ConfigurableRule::new(
+                "no-executables".to_string(),
+                RuleDefinition {
+                    enabled: true,
+                    priority: 100,
+                    executable_only: true,
+                    error_on_match: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+        );
+
+        let violations = engine.check_file(&script).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule_name, "no-executables");
+        assert!(engine.check_file(&data).unwrap().is_empty());
+        assert!(!is_executable(Path::new("no/such/file")));
+    }
+
+    #[test]
+    fn test_unmatched_rules_reported() {
+        let with_includes = |name: &str, pattern: &str| {
+            ConfigurableRule::new(
+                name.to_string(),
+                RuleDefinition {
+                    enabled: true,
+                    priority: 50,
+                    includes: vec![pattern.to_string()],
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+
+        let mut engine = RuleEngine::new();
+        engine.add_rule(with_includes("typo", "*.nonexistent"));
+        engine.add_rule(with_includes("sources", "*.rs"));
+
+        let tmp = tempfile::tempdir().unwrap();
+        let file = tmp.path().join("main.rs");
+        std::fs::write(&file, "fn main() {}").unwrap();
+        engine.check_files(&[file]).unwrap();
+
+        assert_eq!(engine.unmatched_rules(), vec!["typo"]);
+    }
+
+    #[test]
+    fn test_run_stats_count_under_parallel_checks() {
+        let sized = |name: &str, pattern: &str, max: Option<&str>, warn: Option<&str>| {
+            ConfigurableRule::new(
+                name.to_string(),
+                RuleDefinition {
+                    enabled: true,
+                    priority: 50,
+                    includes: vec![pattern.to_string()],
+                    max_size: max.map(|s| s.parse().unwrap()),
+                    warn_size: warn.map(|s| s.parse().unwrap()),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+
+        let mut engine = RuleEngine::new();
+        engine.add_rule(sized("binaries", "*.bin", Some("100B"), None));
+        engine.add_rule(sized("text", "*.txt", None, Some("100B")));
+        engine.add_rule(sized("unused", "*.nonexistent", Some("1B"), None));
+
+        let tmp = tempfile::tempdir().unwrap();
+        let mut paths = Vec::new();
+        for i in 0..200 {
+            let ext = if i % 2 == 0 { "bin" } else { "txt" };
+            let size = if i % 4 < 2 { 200 } else { 10 };
+            let path = tmp.path().join(format!("file{i}.{ext}"));
+            std::fs::write(&path, vec![0u8; size]).unwrap();
+            paths.push(path);
+        }
+
+        let (violations, stats) = engine.check_files_with_stats(&paths).unwrap();
+
+        let binaries = stats.rule("binaries").unwrap();
+        assert_eq!(binaries.matched, 100);
+        assert_eq!(binaries.errors, 50);
+        assert_eq!(binaries.warnings, 0);
+        let text = stats.rule("text").unwrap();
+        assert_eq!(text.matched, 100);
+        assert_eq!(text.errors, 0);
+        assert_eq!(text.warnings, 50);
+        assert_eq!(stats.rule("unused"), Some(RuleStats::default()));
+        assert!(stats.rule("missing").is_none());
+
+        let total: usize = stats
+            .by_rule()
+            .values()
+            .map(|s| s.errors + s.warnings)
+            .sum();
+        assert_eq!(total, violations.len());
+    }
+
+    #[test]
+    fn test_custom_rules_run_alongside_configured_rules() {
+        struct NoTodoFiles;
+
+        impl Rule for NoTodoFiles {
+            fn name(&self) -> &str {
+                "no_todo_files"
+            }
+
+            fn check(&self, path: &Path) -> Result<Vec<Violation>> {
+                let is_todo = path.file_name().is_some_and(|n| n == "TODO");
+                Ok(is_todo
+                    .then(|| {
+                        Violation::new(
+                            path.to_path_buf(),
+                            self.name().to_string(),
+                            "TODO files belong in the issue tracker".to_string(),
+                            Severity::Warning,
+                        )
+                    })
+                    .into_iter()
+                    .collect())
+            }
+        }
+
+        let tmp = tempfile::tempdir().unwrap();
+        let todo = tmp.path().join("TODO");
+        let readme = tmp.path().join("README");
+        std::fs::write(&todo, "- ship it").unwrap();
+        std::fs::write(&readme, "hello").unwrap();
+
+        let mut engine = RuleEngine::new();
+        engine.add_rule(rule("default", 100));
+        engine.add_custom_rule(Box::new(NoTodoFiles));
+
+        let violations = engine.check_files(&[todo.clone(), readme]).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, todo);
+        assert_eq!(violations[0].rule_name, "no_todo_files");
+    }
+
+    #[test]
+    fn test_files_deleted_after_discovery_are_skipped() {
+        let tmp = tempfile::tempdir().unwrap();
+        let big = tmp.path().join("big.bin");
+        let vanished = tmp.path().join("vanished.bin");
+        std::fs::write(&big, vec![0u8; 200]).unwrap();
+        std::fs::write(&vanished, vec![0u8; 200]).unwrap();
+        let discovered = vec![big.clone(), vanished.clone()];
+        std::fs::remove_file(&vanished).unwrap();
+
+        let mut engine = RuleEngine::new();
+        engine.add_rule(
+            ConfigurableRule::new(
+                "default".to_string(),
+                RuleDefinition {
+                    enabled: true,
+                    max_size: Some("100B".parse().unwrap()),
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+        );
+        let violations = engine.check_files(&discovered).unwrap();
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, big);
+    }
+
+    #[test]
+    fn test_profiling_reports_slowest_rule_first() {
+        struct Sleepy;
+
+        impl Rule for Sleepy {
+            fn name(&self) -> &str {
+                "sleepy"
+            }
+
+            fn check(&self, _path: &Path) -> Result<Vec<Violation>> {
+                std::thread::sleep(Duration::from_millis(20));
+                Ok(vec![])
+            }
+        }
+
+        let tmp = tempfile::tempdir().unwrap();
+        let files: Vec<PathBuf> = (0..3)
+            .map(|i| {
+                let path = tmp.path().join(format!("{i}.txt"));
+                std::fs::write(&path, "x").unwrap();
+                path
+            })
+            .collect();
+
+        let engine = |profile| {
+            let mut engine = RuleEngine::new().with_profiling(profile);
+            engine.add_rule(rule("default", 100));
+            engine.add_custom_rule(Box::new(Sleepy));
+            engine.check_files(&files).unwrap();
+            engine
+        };
+
+        let profiled = engine(true);
+        let slowest = profiled.slowest_rules(5);
+        assert_eq!(slowest[0].0, "sleepy");
+        assert!(slowest[0].1 >= Duration::from_millis(60));
+        assert_eq!(profiled.slowest_rules(1).len(), 1);
+
+        assert!(engine(false).slowest_rules(5).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_report_names_link_and_target() {
+        let tmp = tempfile::tempdir().unwrap();
+        let target = tmp.path().join("large.bin");
+        let link = tmp.path().join("link.bin");
+        std::fs::write(&target, vec![0u8; 200]).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        let target = std::fs::canonicalize(&target).unwrap();
+
+        let engine = |report| {
+            let mut engine = RuleEngine::new().with_symlink_report(report);
+            engine.add_rule(
+                ConfigurableRule::new(
+                    "default".to_string(),
+                    RuleDefinition {
+                        enabled: true,
+                        max_size: Some("100B".parse().unwrap()),
+                        ..Default::default()
+                    },
+                )
+                .unwrap(),
+            );
+            engine
+        };
+
+        let both = &engine(SymlinkReport::Both).check_file(&link).unwrap()[0];
+        assert_eq!(both.path, link);
+        assert!(both.message.contains(&link.display().to_string()));
+        assert!(both.message.contains(&target.display().to_string()));
+
+        let on_target = &engine(SymlinkReport::Target).check_file(&link).unwrap()[0];
+        assert_eq!(on_target.path, target);
+    }
+
+    #[test]
+    fn test_pass_records_name_size_and_rule() {
+        let tmp = tempfile::tempdir().unwrap();
+        let small = tmp.path().join("small.txt");
+        let big = tmp.path().join("big.bin");
+        std::fs::write(&small, vec![b'a'; 10]).unwrap();
+        std::fs::write(&big, vec![0u8; 500]).unwrap();
+
+        let mut engine = RuleEngine::new().with_pass_records(true);
+        engine.add_rule(
+            ConfigurableRule::new(
+                "default".to_string(),
+                RuleDefinition {
+                    enabled: true,
+                    max_size: Some("100B".parse().unwrap()),
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+        );
+
+        let violations = engine.check_files(&[small.clone(), big]).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            engine.largest_passed(5),
+            vec![PassedFile {
+                path: small,
+                size: 10,
+                rule: "default".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_check_sized_files_never_stats() {
+        let mut engine = RuleEngine::new();
+        engine.add_rule(
+            ConfigurableRule::new(
+                "default".to_string(),
+                RuleDefinition {
+                    enabled: true,
+                    max_size: Some("100B".parse().unwrap()),
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+        );
+
+        let missing = PathBuf::from("/nonexistent/sizelint/big.bin");
+        let files = vec![
+            (missing.clone(), 500),
+            (PathBuf::from("/nonexistent/sizelint/small.txt"), 10),
+        ];
+        let violations = engine.check_sized_files(&files).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, missing);
+        assert_eq!(violations[0].sort_key, 500);
+    }
+
+    #[test]
+    fn test_check_sized_files_ignores_same_named_file_in_cwd() {
+        let mut engine = RuleEngine::new();
+        for (name, text_only, executable_only, priority) in
+            [("text", true, false, 1), ("exec", false, true, 10)]
+        {
+            engine.add_rule(
+                ConfigurableRule::new(
+                    name.to_string(),
+                    RuleDefinition {
+                        enabled: true,
+                        max_size: Some("100B".parse().unwrap()),
+                        text_only,
+                        executable_only,
+                        priority,
+                        ..Default::default()
+                    },
+                )
+                .unwrap(),
+            );
+        }
+
+        // The test binary, relative to the CWD, is an executable binary
+        // file on disk; as a repo-relative path its content is unknown
+        let exe = std::env::current_exe().unwrap();
+        let cwd = std::env::current_dir().unwrap();
+        let relative = exe
+            .strip_prefix(&cwd)
+            .map_or(exe.clone(), Path::to_path_buf);
+        assert!(is_binary_file(&relative, &[]));
+        #[cfg(unix)]
+        assert_eq!(engine.best_rule_for(&relative).unwrap().name(), "exec");
+
+        let violations = engine.check_sized_files(&[(relative, 500)]).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule_name, "text");
+    }
+
+    #[test]
+    fn test_severity_specific_messages() {
+        let tmp = tempfile::tempdir().unwrap();
+        let big = tmp.path().join("big.bin");
+        let medium = tmp.path().join("medium.bin");
+        std::fs::write(&big, vec![0u8; 200]).unwrap();
+        std::fs::write(&medium, vec![0u8; 50]).unwrap();
+
+        let rule = ConfigurableRule::new(
+            "limits".to_string(),
+            RuleDefinition {
+                enabled: true,
+                max_size: Some("100B".parse().unwrap()),
+                warn_size: Some("10B".parse().unwrap()),
+                error_message: Some("Hard limit exceeded - must fix".to_string()),
+                warn_message: Some("Approaching limit".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let error = &rule.check(&big).unwrap()[0];
+        assert_eq!(error.severity, Severity::Error);
+        assert_eq!(error.message, "Hard limit exceeded - must fix");
+
+        let warning = &rule.check_blob(&medium, 50).unwrap()[0];
+        assert_eq!(warning.severity, Severity::Warning);
+        assert_eq!(warning.message, "Approaching limit");
+    }
+
+    #[test]
+    fn test_scoped_limits_depend_on_location() {
+        let tmp = tempfile::tempdir().unwrap();
+        let write = |rel: &str| {
+            let path = tmp.path().join(rel);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, vec![0u8; 4096]).unwrap();
+            path
+        };
+        let src = write("src/model.bin");
+        let test = write("test/fixture.bin");
+        let other = write("assets/image.bin");
+
+        let rule = ConfigurableRule::new(
+            "binaries".to_string(),
+            RuleDefinition {
+                enabled: true,
+                includes: vec!["*.bin".to_string()],
+                max_size: Some("2KB".parse().unwrap()),
+                scoped_limits: vec![
+                    crate::config::ScopedLimit {
+                        path: "src/**".to_string(),
+                        max: "1KB".parse().unwrap(),
+                    },
+                    crate::config::ScopedLimit {
+                        path: "test/**".to_string(),
+                        max: "10KB".parse().unwrap(),
+                    },
+                ],
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .with_root(tmp.path());
+
+        let src_violations = rule.check(&src).unwrap();
+        assert_eq!(src_violations.len(), 1);
+        assert_eq!(src_violations[0].limit, Some(1024));
+        assert!(rule.check(&test).unwrap().is_empty());
+        assert_eq!(rule.check(&other).unwrap()[0].limit, Some(2048));
+    }
+
+    #[test]
+    fn test_boundary_decides_file_exactly_at_limit() {
+        let tmp = tempfile::tempdir().unwrap();
+        let exact = tmp.path().join("exact.bin");
+        std::fs::write(&exact, vec![0u8; 100]).unwrap();
+
+        let rule = |boundary| {
+            ConfigurableRule::new(
+                "limits".to_string(),
+                RuleDefinition {
+                    enabled: true,
+                    max_size: Some("100B".parse().unwrap()),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .with_boundary(boundary)
+        };
+
+        assert!(rule(Boundary::Inclusive).check(&exact).unwrap().is_empty());
+
+        let violations = rule(Boundary::Exclusive).check(&exact).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].severity, Severity::Error);
+        assert_eq!(violations[0].expected_value.as_deref(), Some("< 100 B"));
+    }
+
+    #[test]
+    fn test_error_grace_downgrades_marginal_overages() {
+        let tmp = tempfile::tempdir().unwrap();
+        let marginal = tmp.path().join("marginal.bin");
+        let over = tmp.path().join("over.bin");
+        std::fs::write(&marginal, vec![0u8; 103]).unwrap();
+        std::fs::write(&over, vec![0u8; 108]).unwrap();
+
+        let rule = ConfigurableRule::new(
+            "limits".to_string(),
+            RuleDefinition {
+                enabled: true,
+                max_size: Some("100B".parse().unwrap()),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .with_error_grace(Some(5.0));
+
+        let violations = rule.check(&marginal).unwrap();
+        assert_eq!(violations[0].severity, Severity::Warning);
+        assert!(violations[0].message.contains("within the 5% error grace"));
+        assert_eq!(rule.check(&over).unwrap()[0].severity, Severity::Error);
+        assert_eq!(
+            rule.check_blob(&marginal, 103).unwrap()[0].severity,
+            Severity::Warning
+        );
+    }
+
+    #[test]
+    fn test_normalize_crlf_measures_text_without_carriage_returns() {
+        let tmp = tempfile::tempdir().unwrap();
+        let text = tmp.path().join("notes.txt");
+        let binary = tmp.path().join("data.bin");
+        // 60 lines of "abc\r\n": 300 bytes on disk, 240 with LF endings
+        std::fs::write(&text, "abc\r\n".repeat(60)).unwrap();
+        let mut bytes = b"\0abc\r\n".repeat(50);
+        bytes.truncate(300);
+        std::fs::write(&binary, bytes).unwrap();
+
+        let engine = |normalize| {
+            let mut engine = RuleEngine::new().with_normalize_crlf(normalize);
+            engine.add_rule(
+                ConfigurableRule::new(
+                    "default".to_string(),
+                    RuleDefinition {
+                        enabled: true,
+                        max_size: Some("250B".parse().unwrap()),
+                        ..Default::default()
+                    },
+                )
+                .unwrap(),
+            );
+            engine
+        };
+
+        assert_eq!(engine(false).check_file(&text).unwrap().len(), 1);
+        assert!(engine(true).check_file(&text).unwrap().is_empty());
+        assert_eq!(engine(true).normalized_size(&text), Some(240));
+
+        // A CRLF split across two chunks still counts once
+        let mut straddling = vec![b'a'; CRLF_CHUNK_LEN - 1];
+        straddling.extend_from_slice(b"\r\nb\r");
+        std::fs::write(&text, &straddling).unwrap();
+        assert_eq!(
+            engine(true).normalized_size(&text),
+            Some(straddling.len() as u64 - 1)
+        );
+
+        assert_eq!(engine(true).normalized_size(&binary), None);
+        assert_eq!(engine(true).check_file(&binary).unwrap().len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fifo_is_never_read_by_content_rules() {
+        let tmp = tempfile::tempdir().unwrap();
+        let fifo = tmp.path().join("pipe.tar");
+        let status = std::process::Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let mut engine = RuleEngine::new().with_normalize_crlf(true);
+        engine.add_rule(
+            ConfigurableRule::new(
+                "archives".to_string(),
+                RuleDefinition {
+                    enabled: true,
+                    text_only: true,
+                    inspect_archives: true,
+                    max_size: Some("1B".parse().unwrap()),
+                    max_extracted_size: Some("1B".parse().unwrap()),
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+        );
+
+        // Opening the FIFO would block without a writer, so fail instead of hanging
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(engine.check_file(&fifo).map(|v| v.len()));
+        });
+        let checked = rx
+            .recv_timeout(std::time::Duration::from_secs(10))
+            .expect("checking a FIFO blocked");
+        assert_eq!(checked.unwrap(), 0);
+    }
+
+    #[test]
+    fn test_require_extension_flags_large_extensionless_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dump = tmp.path().join("dump");
+        let blob = tmp.path().join("blob.bin");
+        let note = tmp.path().join("NOTES");
+        std::fs::write(&dump, vec![0u8; 4096]).unwrap();
+        std::fs::write(&blob, vec![0u8; 4096]).unwrap();
+        std::fs::write(&note, "small").unwrap();
+
+        let rule = ConfigurableRule::new(
+            "assets".to_string(),
+            RuleDefinition {
+                enabled: true,
+                require_extension: true,
+                extensionless_min_size: Some("1KB".parse().unwrap()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let violations = rule.check(&dump).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].severity, Severity::Error);
+        assert_eq!(
+            violations[0].message,
+            "File without an extension exceeds 1.0 KB"
+        );
+        assert!(rule.check(&blob).unwrap().is_empty());
+        assert!(rule.check(&note).unwrap().is_empty());
+        assert_eq!(rule.check_blob(&dump, 4096).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_rule_index_picks_same_rule_as_full_scan() {
+        let mut engine = RuleEngine::new();
+        for i in 0..300 {
+            let (includes, match_mode) = match i % 4 {
+                0 => (vec![format!("*.ext{i}")], MatchMode::Auto),
+                1 => (vec![format!("dir{}/**", i % 7)], MatchMode::Auto),
+                2 => (vec![format!("file{}.*", i % 11)], MatchMode::Basename),
+                _ => (
+                    vec![format!("dir{}/*.ext{}", i % 5, i % 9)],
+                    MatchMode::Path,
+                ),
+            };
+            engine.add_rule(
+                ConfigurableRule::new(
+                    format!("rule{i:03}"),
+                    RuleDefinition {
+                        enabled: i % 13 != 0,
+                        priority: i % 6,
+                        includes,
+                        excludes: if i % 10 == 0 {
+                            vec!["*.ext0".to_string()]
+                        } else {
+                            vec![]
+                        },
+                        match_mode,
+                        max_size: Some("1KB".parse().unwrap()),
+                        ..Default::default()
+                    },
+                )
+                .unwrap(),
+            );
+        }
+        engine.add_rule(
+            ConfigurableRule::new(
+                "default".to_string(),
+                RuleDefinition {
+                    enabled: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+        );
+        let engine = engine.with_root(Path::new("/repo"));
+
+        for d in 0..8 {
+            for f in 0..12 {
+                for e in 0..20 {
+                    let path = PathBuf::from(format!("/repo/dir{d}/file{f}.ext{e}"));
+                    let full_scan = engine
+                        .rules
+                        .iter()
+                        .filter(|r| engine.applies_to(r, &path, true))
+                        .max_by(|a, b| RuleEngine::rule_order(a, b))
+                        .map(|r| r.name.as_str());
+                    assert_eq!(
+                        engine.best_rule_for(&path).map(|r| r.name.as_str()),
+                        full_scan,
+                        "{}",
+                        path.display()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_require_tracked_flags_untracked_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let tracked_file = tmp.path().join("config/tracked.toml");
+        let untracked_file = tmp.path().join("config/local.toml");
+        std::fs::create_dir_all(tmp.path().join("config")).unwrap();
+        std::fs::write(&tracked_file, "a = 1").unwrap();
+        std::fs::write(&untracked_file, "b = 2").unwrap();
+        let files = vec![tracked_file.clone(), untracked_file.clone()];
+
+        let engine = |tracked| {
+            let mut engine = RuleEngine::new().with_tracked_files(tracked);
+            engine.add_rule(
+                ConfigurableRule::new(
+                    "config_tracked".to_string(),
+                    RuleDefinition {
+                        enabled: true,
+                        includes: vec!["**/config/*".to_string()],
+                        require_tracked: true,
+                        ..Default::default()
+                    },
+                )
+                .unwrap(),
+            );
+            engine
+        };
+
+        let violations = engine(Some(HashSet::from([tracked_file])))
+            .check_files(&files)
+            .unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, untracked_file);
+        assert_eq!(violations[0].actual_value.as_deref(), Some("untracked"));
+
+        // Outside a repository there is no tracked set and nothing is flagged
+        assert!(engine(None).check_files(&files).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_partial_results_visible_to_other_handles() {
+        let partial = PartialResults::default();
+        let handler_view = partial.clone();
+        let engine = RuleEngine::history_max("100B")
+            .unwrap()
+            .with_partial_results(partial);
+
+        let blobs: Vec<_> = [("/repo/big.bin", 500), ("/repo/small.txt", 10)]
+            .into_iter()
+            .map(|(path, size)| crate::git::HistoryBlob {
+                path: path.to_string(),
+                size,
+                commit: "abc123".to_string(),
+            })
+            .collect();
+        assert!(handler_view.snapshot().is_empty());
+        engine.check_history_blobs(&blobs).unwrap();
+
+        let seen = handler_view.snapshot();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].path, PathBuf::from("/repo/big.bin"));
+        assert_eq!(seen[0].commit.as_deref(), Some("abc123"));
+    }
+
+    #[test]
+    fn test_history_blobs_ties_keep_first_blob() {
+        let engine = RuleEngine::history_max("100B").unwrap();
+        let blobs: Vec<_> = (0..64)
+            .map(|i| crate::git::HistoryBlob {
+                path: "/repo/big.bin".to_string(),
+                size: 500,
+                commit: format!("commit{i}"),
+            })
+            .collect();
+
+        let violations = engine.check_history_blobs(&blobs).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].commit.as_deref(), Some("commit0"));
+    }
+
+    #[test]
+    fn test_max_violations_and_fail_fast_cap_results() {
+        let tmp = tempfile::tempdir().unwrap();
+        let files: Vec<PathBuf> = (0..50)
+            .map(|i| {
+                let path = tmp.path().join(format!("file_{i}.bin"));
+                std::fs::write(&path, vec![0u8; 64]).unwrap();
+                path
+            })
+            .collect();
+
+        let limited = |max| {
+            let mut engine = RuleEngine::new().with_max_violations(max);
+            engine.add_rule(
+                ConfigurableRule::new(
+                    "default".to_string(),
+                    RuleDefinition {
+                        enabled: true,
+                        max_size: Some("10B".parse().unwrap()),
+                        ..Default::default()
+                    },
+                )
+                .unwrap(),
+            );
+            engine
+        };
+
+        let engine = limited(Some(5));
+        assert_eq!(engine.check_files(&files).unwrap().len(), 5);
+        assert!(engine.stopped_early());
+
+        let engine = limited(None);
+        assert_eq!(engine.check_files(&files).unwrap().len(), 50);
+        assert!(!engine.stopped_early());
+
+        let engine = limited(None).with_fail_fast(true);
+        let violations = engine.check_files(&files).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].severity, Severity::Error);
+        assert!(engine.stopped_early());
+    }
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(std::sync::Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_check_file_emits_trace_line() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("big.bin");
+        std::fs::write(&path, vec![0u8; 2048]).unwrap();
+
+        let mut engine = RuleEngine::new();
+        engine.add_rule(
+            ConfigurableRule::new(
+                "default".to_string(),
+                RuleDefinition {
+                    enabled: true,
+                    max_size: Some("1KB".parse().unwrap()),
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+        );
+
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(Level::TRACE)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            engine.check_file(&path).unwrap();
+        });
+
+        let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let expected = format!(
+            "{} -> default (2.0 KB vs 1.0 KB) = violation",
+            path.display()
+        );
+        assert!(
+            output.contains(&expected),
+            "missing trace line in: {output}"
+        );
+    }
+
     #[test]
     fn test_format_size() {
         assert_eq!(format_size(100), "100 B");
@@ -600,4 +2964,36 @@ mod tests {
         assert_eq!(format_size(1536 * 1024), "1.5 MB");
         assert_eq!(format_size(1024 * 1024 * 1024), "1.0 GB");
     }
+
+    #[test]
+    fn test_size_revisions_flags_churny_files() {
+        let blob = |path: &str, size: u64| crate::git::HistoryBlob {
+            path: path.to_string(),
+            size,
+            commit: "abc123".to_string(),
+        };
+        let mut revisions = SizeRevisions::new(1024);
+        revisions.record(&[blob("model.bin", 2048), blob("model.bin", 3072)]);
+        revisions.record(&[
+            blob("model.bin", 4096),
+            blob("model.bin", 2048),
+            blob("notes.txt", 10),
+            blob("notes.txt", 20),
+            blob("notes.txt", 30),
+        ]);
+
+        let violations = revisions.check(2);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, PathBuf::from("model.bin"));
+        assert_eq!(violations[0].actual_value.as_deref(), Some("3 sizes"));
+        assert!(revisions.check(3).is_empty());
+    }
+
+    #[test]
+    fn test_format_size_dual() {
+        assert_eq!(format_size_dual(100), "100 B");
+        assert_eq!(format_size_dual(1_010), "1010 B (1.0 kB)");
+        assert_eq!(format_size_dual(10 * 1024 * 1024), "10.0 MiB (10.5 MB)");
+        assert_eq!(format_size_dual(2_000_000_000), "1.9 GiB (2.0 GB)");
+    }
 }
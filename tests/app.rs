@@ -0,0 +1,261 @@
+use clap::Parser;
+use sizelint::app::App;
+use sizelint::baseline::{BaselineEntry, DEFAULT_BASELINE, read_baseline};
+use sizelint::cli::Cli;
+use sizelint::config::Config;
+use sizelint::error::Result;
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+use tempfile::TempDir;
+
+/// Run sizelint in-process with `args`, without the per-user config.
+fn try_run(args: &[&str]) -> Result<ExitCode> {
+    let mut argv = vec!["sizelint", "--no-global-config"];
+    argv.extend_from_slice(args);
+    App::new(Cli::parse_from(argv))?.run()
+}
+
+fn run(args: &[&str]) -> ExitCode {
+    try_run(args).unwrap()
+}
+
+/// Quietly check `root` against the config at `config`, with `extra` flags.
+fn check(config: &Path, root: &Path, extra: &[&str]) -> ExitCode {
+    let mut args = vec!["check", "--quiet", "--config", config.to_str().unwrap()];
+    args.extend_from_slice(extra);
+    args.push(root.to_str().unwrap());
+    run(&args)
+}
+
+/// A temporary directory with a `sizelint.toml` path inside it.
+fn project() -> (TempDir, std::path::PathBuf) {
+    let tmp = tempfile::tempdir().unwrap();
+    let config = tmp.path().join("sizelint.toml");
+    (tmp, config)
+}
+
+#[test]
+fn test_config_validate_exit_code() {
+    let (_tmp, config) = project();
+    let validate = |toml: &str| {
+        fs::write(&config, toml).unwrap();
+        run(&["config", "validate", "--config", config.to_str().unwrap()])
+    };
+
+    assert_eq!(validate("max_file_size = \"5MB\"\n"), ExitCode::SUCCESS);
+    assert_eq!(
+        validate("max_file_size = \"1MB\"\nwarn_file_size = \"2MB\"\n"),
+        ExitCode::FAILURE
+    );
+}
+
+#[test]
+fn test_warn_is_error_fails_only_for_escalated_rule() {
+    let (tmp, config) = project();
+    fs::write(tmp.path().join("model.bin"), vec![0u8; 2048]).unwrap();
+    fs::write(tmp.path().join("notes.txt"), vec![b'a'; 2048]).unwrap();
+    let check_escalated = |escalate: bool| {
+        fs::write(
+            &config,
+            format!(
+                r#"
+[rules.binaries]
+includes = ["*.bin"]
+warn_size = "1KB"
+warn_is_error = {escalate}
+
+[rules.text]
+includes = ["*.txt"]
+warn_size = "1KB"
+"#
+            ),
+        )
+        .unwrap();
+        check(&config, tmp.path(), &[])
+    };
+
+    assert_eq!(check_escalated(false), ExitCode::SUCCESS);
+    assert_eq!(check_escalated(true), ExitCode::FAILURE);
+}
+
+#[test]
+fn test_baseline_auto_records_then_enforces() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (_config_dir, config) = project();
+    fs::write(&config, "max_file_size = \"1KB\"\n").unwrap();
+    let check_auto = || check(&config, tmp.path(), &["--baseline-auto"]);
+
+    fs::write(tmp.path().join("legacy.bin"), vec![0u8; 2048]).unwrap();
+    assert_eq!(check_auto(), ExitCode::SUCCESS);
+    let baseline = read_baseline(&tmp.path().join(DEFAULT_BASELINE)).unwrap();
+    assert_eq!(
+        baseline,
+        vec![BaselineEntry {
+            path: "legacy.bin".to_string(),
+            rule: "default".to_string(),
+        }]
+    );
+
+    assert_eq!(check_auto(), ExitCode::SUCCESS);
+    fs::write(tmp.path().join("new.bin"), vec![0u8; 2048]).unwrap();
+    assert_eq!(check_auto(), ExitCode::FAILURE);
+}
+
+#[test]
+fn test_rules_add_appends_a_loadable_rule() {
+    let (_tmp, config) = project();
+    fs::write(&config, "# team limits\nmax_file_size = \"10MB\"\n").unwrap();
+    let add = |args: &[&str]| {
+        let mut argv = vec!["--config", config.to_str().unwrap(), "rules", "add"];
+        argv.extend_from_slice(args);
+        try_run(&argv)
+    };
+
+    let added = add(&[
+        "images",
+        "--max",
+        "5MB",
+        "--include",
+        "*.png",
+        "--include",
+        "*.jpg",
+        "--priority",
+        "200",
+    ]);
+    assert_eq!(added.unwrap(), ExitCode::SUCCESS);
+
+    let content = fs::read_to_string(&config).unwrap();
+    assert!(content.starts_with("# team limits\n"));
+    let loaded = Config::load_from_file(&config).unwrap();
+    let rule = &loaded.rules.unwrap().rules["images"];
+    assert_eq!(rule.max_size.as_ref().unwrap().bytes(), 5 * 1024 * 1024);
+    assert_eq!(rule.includes, vec!["*.png", "*.jpg"]);
+    assert_eq!(rule.priority, 200);
+    assert!(rule.enabled);
+
+    // Invalid input and duplicate names leave the file alone
+    assert!(add(&["bad", "--max", "5 parsecs"]).is_err());
+    assert!(add(&["bad", "--include", "[oops"]).is_err());
+    assert_eq!(add(&["images"]).unwrap(), ExitCode::FAILURE);
+    assert_eq!(fs::read_to_string(&config).unwrap(), content);
+}
+
+#[test]
+fn test_active_config_file_is_not_discovered() {
+    let (tmp, config) = project();
+    fs::write(tmp.path().join("Cargo.toml"), "[package]").unwrap();
+    // The config is the only file over the limit
+    let padding = format!("# {}\n", "x".repeat(2048));
+    let check_with = |toml: &str| {
+        fs::write(&config, format!("{padding}max_file_size = \"1KB\"\n{toml}")).unwrap();
+        check(&config, tmp.path(), &[])
+    };
+
+    assert_eq!(check_with(""), ExitCode::SUCCESS);
+    assert_eq!(check_with("check_self = true\n"), ExitCode::FAILURE);
+}
+
+#[test]
+fn test_global_includes_restrict_default_rule() {
+    let (tmp, config) = project();
+    fs::create_dir_all(tmp.path().join("assets")).unwrap();
+    fs::create_dir_all(tmp.path().join("src")).unwrap();
+    fs::write(tmp.path().join("src/big.rs"), vec![b'a'; 2048]).unwrap();
+    fs::write(
+        &config,
+        "max_file_size = \"1KB\"\nincludes = [\"assets/**\"]\n",
+    )
+    .unwrap();
+
+    assert_eq!(check(&config, tmp.path(), &[]), ExitCode::SUCCESS);
+    fs::write(tmp.path().join("assets/big.png"), vec![0u8; 2048]).unwrap();
+    assert_eq!(check(&config, tmp.path(), &[]), ExitCode::FAILURE);
+}
+
+#[test]
+fn test_sidecar_limit_overrides_rule_max() {
+    let (tmp, config) = project();
+    fs::write(tmp.path().join("foo.bin"), vec![0u8; 2048]).unwrap();
+    fs::write(tmp.path().join("bar.bin"), vec![0u8; 2048]).unwrap();
+    fs::write(tmp.path().join("foo.bin.sizelimit"), "1KB\n").unwrap();
+    let check_with = |toml: &str| {
+        fs::write(&config, toml).unwrap();
+        check(&config, tmp.path(), &[])
+    };
+
+    assert_eq!(check_with("max_file_size = \"1MB\"\n"), ExitCode::SUCCESS);
+    // Only foo.bin has a sidecar; bar.bin keeps the 1MB limit
+    assert_eq!(
+        check_with("max_file_size = \"1MB\"\nuse_sidecar_limits = true\n"),
+        ExitCode::FAILURE
+    );
+    fs::remove_file(tmp.path().join("foo.bin.sizelimit")).unwrap();
+    assert_eq!(
+        check_with("max_file_size = \"1MB\"\nuse_sidecar_limits = true\n"),
+        ExitCode::SUCCESS
+    );
+}
+
+#[test]
+fn test_group_runs_only_tagged_rules() {
+    let (_tmp, config) = project();
+    fs::write(
+        &config,
+        r#"
+[rules.images]
+includes = ["*.png"]
+max_size = "1KB"
+tags = ["assets"]
+
+[rules.videos]
+includes = ["*.mp4"]
+max_size = "1KB"
+tags = ["assets", "ci"]
+
+[rules.sources]
+includes = ["*.rs"]
+max_size = "1KB"
+"#,
+    )
+    .unwrap();
+    let data = tempfile::tempdir().unwrap();
+    let fails = |file: &str, groups: &[&str]| {
+        fs::write(data.path().join(file), vec![0u8; 2048]).unwrap();
+        let args: Vec<&str> = groups.iter().flat_map(|group| ["--group", group]).collect();
+        let code = check(&config, data.path(), &args);
+        fs::remove_file(data.path().join(file)).unwrap();
+        code == ExitCode::FAILURE
+    };
+
+    assert!(fails("big.png", &[]));
+    assert!(fails("big.rs", &[]));
+    // Untagged rules, the default rule included, only run without a group
+    assert!(fails("big.png", &["assets"]));
+    assert!(!fails("big.rs", &["assets"]));
+    assert!(!fails("big.png", &["ci"]));
+    assert!(fails("big.mp4", &["ci"]));
+    assert!(fails("big.png", &["ci", "assets"]));
+    assert!(!fails("big.mp4", &["docs"]));
+}
+
+#[test]
+fn test_default_rule_excludes_leave_paths_to_explicit_rules() {
+    let (tmp, config) = project();
+    fs::create_dir_all(tmp.path().join("fixtures")).unwrap();
+    fs::write(tmp.path().join("fixtures/big.bin"), vec![0u8; 2048]).unwrap();
+    let check_with = |rules: &str| {
+        fs::write(
+            &config,
+            format!("max_file_size = \"1KB\"\ndefault_rule_excludes = [\"fixtures/**\"]\n{rules}"),
+        )
+        .unwrap();
+        check(&config, tmp.path(), &[])
+    };
+
+    assert_eq!(check_with(""), ExitCode::SUCCESS);
+    assert_eq!(
+        check_with("[rules.fixtures]\nincludes = [\"*.bin\"]\nmax_size = \"1KB\"\n"),
+        ExitCode::FAILURE
+    );
+}
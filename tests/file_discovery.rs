@@ -1,4 +1,4 @@
-use sizelint::discovery::FileDiscovery;
+use sizelint::discovery::{COMMON_EXCLUDES, FileDiscovery, modified_since, parse_modified_since};
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -195,6 +195,28 @@ fn test_discovery_ignores_gitignore_when_disabled() {
     assert!(files_contain_name(&files, "result"));
 }
 
+#[test]
+fn test_discovery_common_excludes() {
+    let tmpdir = utils::tmp_mkdir();
+    let tree_root = tmpdir.path();
+
+    utils::Git::new(tree_root.to_path_buf()).create();
+
+    utils::write_file(tree_root.join("index.js"), "app");
+    utils::mkdir(tree_root.join("node_modules/pkg"));
+    utils::write_file(tree_root.join("node_modules/pkg/bundle.js"), "vendored");
+
+    let discovery = FileDiscovery::new(tree_root, &[]).unwrap();
+    let files = discovery.discover_files(true).unwrap();
+    assert!(files_contain_name(&files, "bundle.js"));
+
+    let common: Vec<String> = COMMON_EXCLUDES.iter().map(|p| p.to_string()).collect();
+    let discovery = FileDiscovery::new(tree_root, &common).unwrap();
+    let files = discovery.discover_files(true).unwrap();
+    assert!(!files_contain_name(&files, "bundle.js"));
+    assert!(files_contain_name(&files, "index.js"));
+}
+
 #[test]
 fn test_discovery_git_exclude() {
     let tmpdir = utils::tmp_mkdir();
@@ -423,3 +445,36 @@ fn test_discovery_config_excludes_with_glob_subdirectory() {
     assert!(files_contain_name(&files, "small.txt"));
     assert!(files_contain_name(&files, "main.rs"));
 }
+
+#[test]
+fn test_modified_since_skips_old_files() {
+    use std::time::{Duration, SystemTime};
+
+    let tmpdir = utils::tmp_mkdir();
+    let tree_root = tmpdir.path();
+
+    utils::write_file(tree_root.join("old.bin"), "old");
+    utils::write_file(tree_root.join("recent.bin"), "recent");
+    let now = SystemTime::now();
+    File::options()
+        .write(true)
+        .open(tree_root.join("old.bin"))
+        .unwrap()
+        .set_modified(now - Duration::from_secs(2 * 3600))
+        .unwrap();
+
+    let discovery = FileDiscovery::new(tree_root, &[]).unwrap();
+    let files = discovery.discover_files(true).unwrap();
+    let cutoff = parse_modified_since("1h", now).unwrap();
+    assert_eq!(cutoff, now - Duration::from_secs(3600));
+    let files = modified_since(files, cutoff);
+
+    assert!(!files_contain_name(&files, "old.bin"));
+    assert!(files_contain_name(&files, "recent.bin"));
+
+    assert_eq!(
+        parse_modified_since("1970-01-02", now).unwrap(),
+        SystemTime::UNIX_EPOCH + Duration::from_secs(86_400)
+    );
+    assert!(parse_modified_since("1 hour", now).is_err());
+}
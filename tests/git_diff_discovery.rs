@@ -53,6 +53,17 @@ impl TestGitRepo {
     }
 }
 
+/// The sizelint binary, isolated from the per-user config of whoever runs
+/// the tests.
+fn sizelint() -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_sizelint"));
+    command.env(
+        "XDG_CONFIG_HOME",
+        Path::new(env!("CARGO_TARGET_TMPDIR")).join("no-user-config"),
+    );
+    command
+}
+
 fn file_names(files: &[PathBuf]) -> Vec<String> {
     let mut names: Vec<String> = files
         .iter()
@@ -112,6 +123,33 @@ fn test_git_diff_empty_range_returns_zero_files() {
     assert!(files.is_empty());
 }
 
+#[test]
+fn test_base_is_head_on_default_branch_tip() {
+    use sizelint::git::GitRepo;
+
+    let repo = TestGitRepo::new();
+    let base = repo.default_branch();
+    repo.write_file("last.rs", "fn last() {}");
+    TestGitRepo::git(&repo.root, &["add", "."]);
+    TestGitRepo::git(&repo.root, &["commit", "-m", "last commit"]);
+
+    let git = GitRepo::discover(&repo.root).unwrap();
+    assert!(git.base_is_head(&base).unwrap());
+    assert!(!git.base_is_head("HEAD~1..HEAD").unwrap());
+
+    // The last-commit fallback sees what the empty merge-base range hides
+    let discovery = FileDiscovery::new(&repo.root, &[]).unwrap();
+    assert!(discovery.discover_git_diff_files(&base).unwrap().is_empty());
+    let files = discovery.discover_git_diff_files("HEAD~1..HEAD").unwrap();
+    assert_eq!(file_names(&files), vec!["last.rs"]);
+
+    TestGitRepo::git(&repo.root, &["checkout", "-b", "feature"]);
+    repo.write_file("feature.rs", "fn feature() {}");
+    TestGitRepo::git(&repo.root, &["add", "."]);
+    TestGitRepo::git(&repo.root, &["commit", "-m", "feature"]);
+    assert!(!git.base_is_head(&base).unwrap());
+}
+
 #[test]
 fn test_git_diff_config_excludes_filter_results() {
     let repo = TestGitRepo::new();
@@ -175,6 +213,162 @@ fn test_history_blob_config_excludes_with_relative_path() {
     assert!(!has_dat);
 }
 
+#[test]
+fn test_git_diff_added_only_skips_modified_files() {
+    use sizelint::git::DiffFilter;
+
+    let repo = TestGitRepo::new();
+    let base = repo.default_branch();
+
+    TestGitRepo::git(&repo.root, &["checkout", "-b", "feature"]);
+    repo.write_file("init.txt", "init, but modified");
+    repo.write_file("added.rs", "fn added() {}");
+    TestGitRepo::git(&repo.root, &["add", "."]);
+    TestGitRepo::git(&repo.root, &["commit", "-m", "add one, modify one"]);
+
+    let discovery = FileDiscovery::new(&repo.root, &[]).unwrap();
+    let names = file_names(&discovery.discover_git_diff_files(&base).unwrap());
+    assert_eq!(names, vec!["added.rs", "init.txt"]);
+
+    let discovery = FileDiscovery::new(&repo.root, &[])
+        .unwrap()
+        .with_diff_filter(DiffFilter::Added);
+    let names = file_names(&discovery.discover_git_diff_files(&base).unwrap());
+    assert_eq!(names, vec!["added.rs"]);
+
+    let blobs = discovery.discover_history_blobs(&base).unwrap();
+    assert!(blobs.iter().all(|b| !b.path.ends_with("init.txt")));
+}
+
+#[test]
+fn test_tree_sizes_by_dir_aggregates_top_level() {
+    use sizelint::git::GitRepo;
+
+    let repo = TestGitRepo::new();
+    repo.write_file("src/main.rs", &"a".repeat(100));
+    repo.write_file("src/nested/lib.rs", &"b".repeat(50));
+    repo.write_file("docs/guide.md", &"c".repeat(30));
+    TestGitRepo::git(&repo.root, &["add", "."]);
+    TestGitRepo::git(&repo.root, &["commit", "-m", "add tree"]);
+
+    let sizes = GitRepo::discover(&repo.root)
+        .unwrap()
+        .tree_sizes_by_dir()
+        .unwrap();
+
+    assert_eq!(sizes.get("src"), Some(&150));
+    assert_eq!(sizes.get("docs"), Some(&30));
+    // init.txt sits at the repository root
+    assert_eq!(sizes.get("."), Some(&4));
+    assert_eq!(sizes.len(), 3);
+}
+
+#[test]
+fn test_growth_flags_large_increase_only() {
+    use sizelint::git::GitRepo;
+    use sizelint::rules::check_growth;
+
+    const MB: usize = 1024 * 1024;
+
+    let repo = TestGitRepo::new();
+    let base = repo.default_branch();
+    write_large_file(&repo, "static.bin", 5 * MB);
+    write_large_file(&repo, "growing.bin", MB);
+    TestGitRepo::git(&repo.root, &["add", "."]);
+    TestGitRepo::git(&repo.root, &["commit", "-m", "base files"]);
+
+    TestGitRepo::git(&repo.root, &["checkout", "-b", "feature"]);
+    write_large_file(&repo, "static.bin", 5 * MB + 10);
+    write_large_file(&repo, "growing.bin", 4 * MB);
+    write_large_file(&repo, "new.bin", 3 * MB);
+    TestGitRepo::git(&repo.root, &["add", "."]);
+    TestGitRepo::git(&repo.root, &["commit", "-m", "grow"]);
+
+    let range = format!("{base}..HEAD");
+    let discovery = FileDiscovery::new(&repo.root, &[]).unwrap();
+    let files = discovery.discover_git_diff_files(&range).unwrap();
+
+    let git = GitRepo::discover(&repo.root).unwrap();
+    let base_rev = git.range_base(&range).unwrap();
+    let base_sizes = git.blob_sizes_at(&base_rev, &files).unwrap();
+    assert_eq!(base_sizes.len(), 2);
+
+    let violations = check_growth(&files, &base_sizes, 2 * MB as u64).unwrap();
+    let names = file_names(
+        &violations
+            .iter()
+            .map(|v| v.path.clone())
+            .collect::<Vec<_>>(),
+    );
+    assert_eq!(names, vec!["growing.bin", "new.bin"]);
+}
+
+#[test]
+fn test_filter_tracked_handles_huge_path_lists() {
+    use sizelint::git::GitRepo;
+
+    let repo = TestGitRepo::new();
+    repo.write_file("src/tracked.rs", "fn tracked() {}");
+    TestGitRepo::git(&repo.root, &["add", "."]);
+    TestGitRepo::git(&repo.root, &["commit", "-m", "tracked"]);
+    repo.write_file("untracked.rs", "fn untracked() {}");
+
+    // Far more path bytes than fit into a single command line
+    let mut paths: Vec<PathBuf> = (0..20_000)
+        .map(|i| repo.root.join(format!("generated/module_{i:05}/file.rs")))
+        .collect();
+    paths.push(repo.root.join("untracked.rs"));
+    paths.push(repo.root.join("src/tracked.rs"));
+    paths.push(repo.root.join("init.txt"));
+
+    let git = GitRepo::discover(&repo.root).unwrap();
+    let tracked = git.filter_tracked(&paths).unwrap();
+
+    assert_eq!(file_names(&tracked), vec!["init.txt", "tracked.rs"]);
+}
+
+#[test]
+fn test_gitattributes_size_override() {
+    use sizelint::config::RuleDefinition;
+    use sizelint::git::{GitRepo, MAX_SIZE_ATTRIBUTE};
+    use sizelint::rules::{ConfigurableRule, RuleEngine, parse_size_string};
+
+    let repo = TestGitRepo::new();
+    repo.write_file(".gitattributes", "data/** sizelint-max=5MB\n");
+    write_large_file(&repo, "data/big.bin", 3 * 1024 * 1024);
+    write_large_file(&repo, "other/big.bin", 3 * 1024 * 1024);
+
+    let files = vec![
+        repo.root.join("data/big.bin"),
+        repo.root.join("other/big.bin"),
+    ];
+    let git = GitRepo::discover(&repo.root).unwrap();
+    let attrs = git.check_attr(MAX_SIZE_ATTRIBUTE, &files).unwrap();
+    assert_eq!(attrs.len(), 1);
+    assert_eq!(attrs[&repo.root.join("data/big.bin")], "5MB");
+
+    let overrides = attrs
+        .into_iter()
+        .map(|(path, value)| (path, parse_size_string(&value).unwrap()))
+        .collect();
+    let mut engine = RuleEngine::new().with_size_overrides(overrides);
+    engine.add_rule(
+        ConfigurableRule::new(
+            "default".to_string(),
+            RuleDefinition {
+                enabled: true,
+                max_size: Some("1MB".parse().unwrap()),
+                ..Default::default()
+            },
+        )
+        .unwrap(),
+    );
+
+    let violations = engine.check_files(&files).unwrap();
+    assert_eq!(violations.len(), 1);
+    assert!(violations[0].path.ends_with("other/big.bin"));
+}
+
 #[test]
 fn test_git_diff_error_when_not_in_git_repo() {
     let tmp = tempfile::tempdir().unwrap();
@@ -189,6 +383,37 @@ fn test_git_diff_error_when_not_in_git_repo() {
 
 // History blob tests
 
+#[test]
+fn test_gitattributes_size_override_with_relative_paths() {
+    let repo = TestGitRepo::new();
+    repo.write_file(".gitattributes", "data/** sizelint-max=5MB\n");
+    repo.write_file(
+        "sizelint.toml",
+        "max_file_size = \"1MB\"\nuse_gitattributes = true\n",
+    );
+    write_large_file(&repo, "data/big.bin", 3 * 1024 * 1024);
+    std::fs::create_dir_all(repo.root.join("other")).unwrap();
+
+    for (cwd, path) in [
+        (repo.root.clone(), "data"),
+        (repo.root.join("other"), "../data"),
+    ] {
+        let output = sizelint()
+            .args(["check", "--config"])
+            .arg(repo.root.join("sizelint.toml"))
+            .arg(path)
+            .current_dir(&cwd)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "check {path} from {}: {}",
+            cwd.display(),
+            String::from_utf8_lossy(&output.stdout)
+        );
+    }
+}
+
 fn write_large_file(repo: &TestGitRepo, name: &str, size: usize) {
     let content = "x".repeat(size);
     repo.write_file(name, &content);
@@ -231,7 +456,7 @@ fn test_history_blob_small_file_no_violation() {
             enabled: true,
             description: "test".to_string(),
             priority: 100,
-            max_size: Some("1MB".to_string()),
+            max_size: Some("1MB".parse().unwrap()),
             ..Default::default()
         },
     )
@@ -346,7 +571,7 @@ fn test_history_blob_temporarily_too_large_then_shrunk() {
             enabled: true,
             description: "test".to_string(),
             priority: 100,
-            max_size: Some("500B".to_string()),
+            max_size: Some("500B".parse().unwrap()),
             ..Default::default()
         },
     )
@@ -390,7 +615,7 @@ fn test_history_blob_grew_then_deleted() {
             enabled: true,
             description: "test".to_string(),
             priority: 100,
-            max_size: Some("1KB".to_string()),
+            max_size: Some("1KB".parse().unwrap()),
             ..Default::default()
         },
     )
@@ -444,7 +669,7 @@ fn test_history_blob_dedup_keeps_largest() {
             enabled: true,
             description: "test".to_string(),
             priority: 100,
-            max_size: Some("500B".to_string()),
+            max_size: Some("500B".parse().unwrap()),
             ..Default::default()
         },
     )
@@ -489,7 +714,7 @@ fn test_cross_phase_dedup_keeps_larger_history_blob() {
             enabled: true,
             description: "test".to_string(),
             priority: 100,
-            max_size: Some("500B".to_string()),
+            max_size: Some("500B".parse().unwrap()),
             ..Default::default()
         },
     )
@@ -554,7 +779,7 @@ fn test_cross_phase_dedup_no_duplicate_when_unchanged() {
             enabled: true,
             description: "test".to_string(),
             priority: 100,
-            max_size: Some("500B".to_string()),
+            max_size: Some("500B".parse().unwrap()),
             ..Default::default()
         },
     )
@@ -590,3 +815,617 @@ fn test_cross_phase_dedup_no_duplicate_when_unchanged() {
     assert_eq!(violations.len(), 1);
     assert_eq!(violations[0].sort_key, 800);
 }
+
+#[test]
+fn test_history_max_flags_oversized_blob_anywhere_in_history() {
+    use sizelint::rules::RuleEngine;
+
+    let repo = TestGitRepo::new();
+
+    write_large_file(&repo, "dump.bin", 60 * 1024);
+    TestGitRepo::git(&repo.root, &["add", "."]);
+    TestGitRepo::git(&repo.root, &["commit", "-m", "add dump"]);
+    let commit = TestGitRepo::git(&repo.root, &["rev-parse", "--short=12", "HEAD"]);
+
+    std::fs::remove_file(repo.root.join("dump.bin")).unwrap();
+    TestGitRepo::git(&repo.root, &["add", "."]);
+    TestGitRepo::git(&repo.root, &["commit", "-m", "remove dump"]);
+
+    let discovery = FileDiscovery::new(&repo.root, &[]).unwrap();
+    let blobs = discovery.discover_all_history_blobs().unwrap();
+
+    let engine = RuleEngine::history_max("50KB").unwrap();
+    let violations = engine.check_history_blobs(&blobs).unwrap();
+    assert_eq!(violations.len(), 1);
+    assert!(violations[0].path.ends_with("dump.bin"));
+    assert_eq!(violations[0].rule_name, "history_max");
+    assert_eq!(violations[0].commit.as_deref(), Some(commit.as_str()));
+
+    let engine = RuleEngine::history_max("100KB").unwrap();
+    assert!(engine.check_history_blobs(&blobs).unwrap().is_empty());
+}
+
+#[test]
+fn test_history_window_limits_scanned_commits() {
+    use sizelint::git::CommitWindow;
+
+    let repo = TestGitRepo::new();
+    let commit_on = |name: &str, date: &str| {
+        write_large_file(&repo, name, 1024);
+        TestGitRepo::git(&repo.root, &["add", "."]);
+        let output = Command::new("git")
+            .args(["commit", "-m", name])
+            .env("GIT_AUTHOR_DATE", date)
+            .env("GIT_COMMITTER_DATE", date)
+            .current_dir(&repo.root)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+    };
+    commit_on("december.bin", "2023-12-15T12:00:00Z");
+    commit_on("february.bin", "2024-02-15T12:00:00Z");
+    commit_on("may.bin", "2024-05-15T12:00:00Z");
+
+    let blobs_within = |since: &str, until: &str| {
+        FileDiscovery::new(&repo.root, &[])
+            .unwrap()
+            .with_commit_window(CommitWindow {
+                since: Some(since.to_string()),
+                until: Some(until.to_string()),
+            })
+            .discover_all_history_blobs()
+    };
+
+    let blobs = blobs_within("2024-01-01", "2024-03-31").unwrap();
+    let names: Vec<_> = blobs.iter().map(|b| b.path.as_str()).collect();
+    assert_eq!(names.len(), 1, "{names:?}");
+    assert!(names[0].ends_with("february.bin"));
+
+    let err = blobs_within("2024-03-31", "2024-01-01").unwrap_err();
+    assert!(err.to_string().contains("after it ends"), "{err}");
+}
+
+#[test]
+fn test_tree_blobs_at_ref_ignore_working_directory() {
+    let repo = TestGitRepo::new();
+
+    write_large_file(&repo, "data.bin", 2048);
+    TestGitRepo::git(&repo.root, &["add", "."]);
+    TestGitRepo::git(&repo.root, &["commit", "-m", "add large"]);
+
+    write_large_file(&repo, "data.bin", 10);
+    TestGitRepo::git(&repo.root, &["add", "."]);
+    TestGitRepo::git(&repo.root, &["commit", "-m", "shrink"]);
+
+    // Uncommitted changes must not affect the tree sizes
+    write_large_file(&repo, "data.bin", 4096);
+
+    let discovery = FileDiscovery::new(&repo.root, &[]).unwrap();
+    let size_at = |rev: &str| {
+        discovery
+            .discover_tree_blobs(rev)
+            .unwrap()
+            .into_iter()
+            .find(|b| b.path.ends_with("data.bin"))
+            .map(|b| b.size)
+    };
+
+    assert_eq!(size_at("HEAD~1"), Some(2048));
+    assert_eq!(size_at("HEAD"), Some(10));
+    assert_eq!(size_at("HEAD~2"), None);
+}
+
+#[test]
+fn test_working_tree_include_untracked() {
+    let repo = TestGitRepo::new();
+    repo.write_file("init.txt", "init, but modified");
+    write_large_file(&repo, "huge.bin", 4096);
+    repo.write_file(".gitignore", "ignored.bin\n");
+    write_large_file(&repo, "ignored.bin", 4096);
+
+    let discovery = FileDiscovery::new(&repo.root, &[]).unwrap();
+    let names = file_names(&discovery.discover_working_tree_files().unwrap());
+    assert_eq!(names, vec!["init.txt"]);
+
+    let discovery = FileDiscovery::new(&repo.root, &[])
+        .unwrap()
+        .with_untracked(true);
+    let names = file_names(&discovery.discover_working_tree_files().unwrap());
+    assert_eq!(names, vec![".gitignore", "huge.bin", "init.txt"]);
+}
+
+#[test]
+fn test_tree_growth_against_base() {
+    use sizelint::git::GitRepo;
+    use sizelint::rules::check_tree_growth;
+
+    let repo = TestGitRepo::new();
+    let base = repo.default_branch();
+
+    TestGitRepo::git(&repo.root, &["checkout", "-b", "feature"]);
+    write_large_file(&repo, "assets/big.bin", 8192);
+    TestGitRepo::git(&repo.root, &["add", "."]);
+    TestGitRepo::git(&repo.root, &["commit", "-m", "add big"]);
+
+    let git = GitRepo::discover(&repo.root).unwrap();
+    let base_total = git.tree_size(&git.range_base(&base).unwrap()).unwrap();
+    let head_total = git.tree_size("HEAD").unwrap();
+    assert_eq!(head_total - base_total, 8192);
+
+    let violation = check_tree_growth(base_total, head_total, 4096).unwrap();
+    assert_eq!(violation.rule_name, "max_tree_growth");
+    assert_eq!(violation.actual_value.as_deref(), Some("+8.0 KB"));
+    assert!(check_tree_growth(base_total, head_total, 8192).is_none());
+}
+
+#[test]
+fn test_gitdir_size_tracks_committed_objects() {
+    use sizelint::git::GitRepo;
+    use sizelint::rules::check_gitdir_size;
+
+    let repo = TestGitRepo::new();
+    let git = GitRepo::discover(&repo.root).unwrap();
+    let git_dir = git.git_dir().unwrap();
+    assert!(git_dir.ends_with(".git"));
+    let before = sizelint::fs::dir_size(&git_dir).unwrap();
+
+    // Pseudo-random bytes so the object does not compress away
+    let mut state: u32 = 0x2545_f491;
+    let noise: Vec<u8> = (0..64 * 1024)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect();
+    std::fs::write(repo.root.join("noise.bin"), noise).unwrap();
+    TestGitRepo::git(&repo.root, &["add", "."]);
+    TestGitRepo::git(&repo.root, &["commit", "-m", "add noise"]);
+
+    let after = sizelint::fs::dir_size(&git_dir).unwrap();
+    assert!(after >= before + 64 * 1024, "{before} -> {after}");
+
+    assert!(check_gitdir_size(&git_dir, after, after - 1).is_some());
+    assert!(check_gitdir_size(&git_dir, after, after).is_none());
+}
+
+#[test]
+fn test_config_git_runs_diff_discovery() {
+    use clap::Parser;
+    use sizelint::{App, Cli};
+    use std::process::ExitCode;
+
+    let repo = TestGitRepo::new();
+    let base = repo.default_branch();
+    write_large_file(&repo, "old.bin", 2048);
+    TestGitRepo::git(&repo.root, &["add", "."]);
+    TestGitRepo::git(&repo.root, &["commit", "-m", "add old.bin"]);
+
+    TestGitRepo::git(&repo.root, &["checkout", "-b", "feature"]);
+    repo.write_file("new.txt", "small");
+    TestGitRepo::git(&repo.root, &["add", "."]);
+    TestGitRepo::git(&repo.root, &["commit", "-m", "add new.txt"]);
+    write_large_file(&repo, "old.bin", 4096);
+
+    let config_dir = tempfile::tempdir().unwrap();
+    let check = |git: Option<&str>, extra: &[&str]| {
+        let config = config_dir.path().join("sizelint.toml");
+        let mut toml = "max_file_size = \"1KB\"\n".to_string();
+        if let Some(git) = git {
+            toml.push_str(&format!("git = \"{git}\"\n"));
+        }
+        std::fs::write(&config, toml).unwrap();
+        let mut args = vec![
+            "sizelint",
+            "--no-global-config",
+            "check",
+            "--quiet",
+            "--config",
+            config.to_str().unwrap(),
+        ];
+        args.extend_from_slice(extra);
+        args.push(repo.root.to_str().unwrap());
+        App::new(Cli::parse_from(args)).unwrap().run().unwrap()
+    };
+
+    // Only new.txt changed since the base; old.bin predates it
+    assert_eq!(check(Some(&base), &[]), ExitCode::SUCCESS);
+    assert_eq!(check(None, &[]), ExitCode::FAILURE);
+    // An explicit CLI mode wins over the configured range
+    assert_eq!(check(Some(&base), &["--working-tree"]), ExitCode::FAILURE);
+}
+
+#[test]
+fn test_repeated_git_ranges_are_labeled_and_deduplicated() {
+    let repo = TestGitRepo::new();
+    TestGitRepo::git(&repo.root, &["tag", "v0"]);
+    write_large_file(&repo, "first.bin", 2048);
+    TestGitRepo::git(&repo.root, &["add", "."]);
+    TestGitRepo::git(&repo.root, &["commit", "-m", "add first.bin"]);
+    TestGitRepo::git(&repo.root, &["tag", "v1"]);
+    write_large_file(&repo, "second.bin", 4096);
+    TestGitRepo::git(&repo.root, &["add", "."]);
+    TestGitRepo::git(&repo.root, &["commit", "-m", "add second.bin"]);
+
+    let config_dir = tempfile::tempdir().unwrap();
+    let config = config_dir.path().join("sizelint.toml");
+    std::fs::write(&config, "max_file_size = \"1KB\"\n").unwrap();
+
+    // first.bin is in both ranges, second.bin only in the second
+    let output = sizelint()
+        .args(["check", "--format", "json", "--config"])
+        .arg(&config)
+        .args(["--git", "v0..v1", "--git", "v0..HEAD"])
+        .arg(&repo.root)
+        .current_dir(&repo.root)
+        .output()
+        .unwrap();
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    let reported: Vec<(&str, &str)> = summary["violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| (v["path"].as_str().unwrap(), v["range"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        reported,
+        vec![("first.bin", "v0..v1"), ("second.bin", "v0..HEAD")]
+    );
+}
+
+#[test]
+fn test_staged_with_path_checks_only_staged_files_within_it() {
+    let repo = TestGitRepo::new();
+    write_large_file(&repo, "src/committed.bin", 2048);
+    TestGitRepo::git(&repo.root, &["add", "."]);
+    TestGitRepo::git(&repo.root, &["commit", "-m", "add committed.bin"]);
+    write_large_file(&repo, "src/staged.bin", 2048);
+    write_large_file(&repo, "docs/staged.bin", 2048);
+    TestGitRepo::git(&repo.root, &["add", "."]);
+
+    let config_dir = tempfile::tempdir().unwrap();
+    let config = config_dir.path().join("sizelint.toml");
+    std::fs::write(&config, "max_file_size = \"1KB\"\n").unwrap();
+
+    let output = sizelint()
+        .args(["check", "--format", "json", "--config"])
+        .arg(&config)
+        .args(["--staged", "src"])
+        .current_dir(&repo.root)
+        .output()
+        .unwrap();
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    let reported: Vec<&str> = summary["violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(reported, vec!["src/staged.bin"]);
+}
+
+#[test]
+fn test_git_dir_and_work_tree_locate_linked_worktree() {
+    let repo = TestGitRepo::new();
+    let outside = tempfile::tempdir().unwrap();
+    let worktree = outside.path().join("wt");
+    TestGitRepo::git(
+        &repo.root,
+        &["worktree", "add", "-b", "wt", worktree.to_str().unwrap()],
+    );
+    std::fs::write(worktree.join("big.bin"), "x".repeat(2048)).unwrap();
+    TestGitRepo::git(&worktree, &["add", "big.bin"]);
+
+    let config = outside.path().join("sizelint.toml");
+    std::fs::write(&config, "max_file_size = \"1KB\"\n").unwrap();
+    let git_dir = TestGitRepo::git(&worktree, &["rev-parse", "--absolute-git-dir"]);
+
+    // Run from a directory outside any repository
+    let cwd = tempfile::tempdir().unwrap();
+    let output = sizelint()
+        .args(["--git-dir", &git_dir, "--work-tree"])
+        .arg(&worktree)
+        .args(["check", "--staged", "--format", "json", "--config"])
+        .arg(&config)
+        .current_dir(cwd.path())
+        .output()
+        .unwrap();
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    let reported: Vec<PathBuf> = summary["violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| PathBuf::from(v["path"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        reported,
+        vec![std::fs::canonicalize(&worktree).unwrap().join("big.bin")]
+    );
+}
+
+#[test]
+fn test_print_discovery_reports_mode_root_and_git() {
+    let repo = TestGitRepo::new();
+    write_large_file(&repo, "staged.bin", 16);
+    TestGitRepo::git(&repo.root, &["add", "."]);
+
+    let output = sizelint()
+        .args(["check", "--staged", "--print-discovery"])
+        .current_dir(&repo.root)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    let root = std::fs::canonicalize(&repo.root).unwrap();
+    assert!(
+        stderr.contains(&format!("mode=staged root={} git=yes", root.display())),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_in_archive_checks_zip_members() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut zip =
+        zip::ZipWriter::new(std::fs::File::create(tmp.path().join("release.zip")).unwrap());
+    let options = zip::write::SimpleFileOptions::default();
+    zip.add_directory("assets/", options).unwrap();
+    zip.start_file("assets/video.mp4", options).unwrap();
+    std::io::Write::write_all(&mut zip, &[0u8; 4096]).unwrap();
+    zip.start_file("README.md", options).unwrap();
+    std::io::Write::write_all(&mut zip, b"release notes").unwrap();
+    zip.finish().unwrap();
+    std::fs::write(
+        tmp.path().join("sizelint.toml"),
+        "max_file_size = \"1KB\"\n",
+    )
+    .unwrap();
+
+    let output = sizelint()
+        .args(["check", "--format", "json", "--in-archive", "release.zip"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(summary["total_files_checked"], 2);
+    let reported: Vec<&str> = summary["violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(reported, vec!["release.zip!assets/video.mp4"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_commits_checks_only_the_listed_commits() {
+    let repo = TestGitRepo::new();
+    let mut hashes = Vec::new();
+    for name in ["first.bin", "middle.bin", "last.bin"] {
+        write_large_file(&repo, name, 2048);
+        TestGitRepo::git(&repo.root, &["add", "."]);
+        TestGitRepo::git(&repo.root, &["commit", "-m", name]);
+        hashes.push(TestGitRepo::git(&repo.root, &["rev-parse", "HEAD"]));
+    }
+
+    let config_dir = tempfile::tempdir().unwrap();
+    let config = config_dir.path().join("sizelint.toml");
+    std::fs::write(&config, "max_file_size = \"1KB\"\n").unwrap();
+
+    let output = sizelint()
+        .args(["check", "--format", "json", "--config"])
+        .arg(&config)
+        .arg("--commits")
+        .arg(format!("{},{}", hashes[0], &hashes[2][..8]))
+        .current_dir(&repo.root)
+        .output()
+        .unwrap();
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    let mut reported: Vec<&str> = summary["violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v["path"].as_str().unwrap())
+        .collect();
+    reported.sort();
+    assert_eq!(reported, vec!["first.bin", "last.bin"]);
+}
+
+#[test]
+fn test_global_config_sits_beneath_repo_config() {
+    let config_home = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(config_home.path().join("sizelint")).unwrap();
+    std::fs::write(
+        config_home.path().join("sizelint/config.toml"),
+        "max_file_size = \"1KB\"\n",
+    )
+    .unwrap();
+    let work = tempfile::tempdir().unwrap();
+    std::fs::write(work.path().join("big.bin"), vec![0u8; 2048]).unwrap();
+    let check = |args: &[&str]| {
+        sizelint()
+            .args(args)
+            .arg("check")
+            .env("XDG_CONFIG_HOME", config_home.path())
+            .current_dir(work.path())
+            .output()
+            .unwrap()
+            .status
+            .code()
+    };
+
+    assert_eq!(check(&[]), Some(1));
+    assert_eq!(check(&["--no-global-config"]), Some(0));
+
+    std::fs::write(
+        work.path().join("sizelint.toml"),
+        "max_file_size = \"10KB\"\n",
+    )
+    .unwrap();
+    assert_eq!(check(&[]), Some(0));
+
+    // Settings the repository config leaves out keep their per-user values
+    std::fs::write(
+        config_home.path().join("sizelint/config.toml"),
+        "warn_file_size = \"1KB\"\nfail_on_warn = true\n",
+    )
+    .unwrap();
+    assert_eq!(check(&[]), Some(1));
+    assert_eq!(check(&["--no-global-config"]), Some(0));
+
+    // A percentage in the repository config resolves against the per-user quota
+    std::fs::write(
+        config_home.path().join("sizelint/config.toml"),
+        "quota = \"100KB\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        work.path().join("sizelint.toml"),
+        "max_file_size = \"1%\"\n",
+    )
+    .unwrap();
+    assert_eq!(check(&[]), Some(1));
+    std::fs::write(
+        work.path().join("sizelint.toml"),
+        "max_file_size = \"3%\"\n",
+    )
+    .unwrap();
+    assert_eq!(check(&[]), Some(0));
+}
+
+#[test]
+fn test_errors_in_global_and_repo_config_are_reported_together() {
+    let config_home = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(config_home.path().join("sizelint")).unwrap();
+    std::fs::write(
+        config_home.path().join("sizelint/config.toml"),
+        "max_fiel_size = \"1KB\"\n",
+    )
+    .unwrap();
+    let work = tempfile::tempdir().unwrap();
+    std::fs::write(
+        work.path().join("sizelint.toml"),
+        "warn_fiel_size = \"1KB\"\n",
+    )
+    .unwrap();
+
+    let output = sizelint()
+        .arg("check")
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("NO_COLOR", "1")
+        .current_dir(work.path())
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success());
+    assert!(stderr.contains("max_fiel_size"), "{stderr}");
+    assert!(stderr.contains("warn_fiel_size"), "{stderr}");
+}
+
+#[test]
+fn test_trace_config_shows_only_the_layers_merged() {
+    let work = tempfile::tempdir().unwrap();
+    let config = work.path().join("custom.toml");
+    std::fs::write(&config, "quota = \"100KB\"\nwarn_file_size = \"1%\"\n").unwrap();
+    let trace = |key: &str| {
+        let output = sizelint()
+            .args(["--no-global-config", "--trace-config", key, "--config"])
+            .arg(&config)
+            .arg("check")
+            .current_dir(work.path())
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        stderr
+            .lines()
+            .find(|line| line.starts_with(&format!("{key}:")))
+            .unwrap_or_else(|| panic!("no trace for {key} in: {stderr}"))
+            .to_string()
+    };
+
+    // An explicit config is not merged over the defaults
+    assert_eq!(trace("max_file_size"), "max_file_size: unset (final)");
+    // Percentages show as the size they resolved to
+    assert_eq!(
+        trace("warn_file_size"),
+        format!("warn_file_size: {}=1024B (final)", config.display())
+    );
+}
+
+#[test]
+fn test_max_size_revisions_flags_rewritten_file() {
+    let repo = TestGitRepo::new();
+    TestGitRepo::git(&repo.root, &["tag", "base"]);
+    for size in [2048, 3072, 4096] {
+        write_large_file(&repo, "model.bin", size);
+        TestGitRepo::git(&repo.root, &["add", "."]);
+        TestGitRepo::git(&repo.root, &["commit", "-m", "rewrite model.bin"]);
+    }
+
+    let config_dir = tempfile::tempdir().unwrap();
+    let config = config_dir.path().join("sizelint.toml");
+    std::fs::write(
+        &config,
+        "warn_file_size = \"1KB\"\nmax_file_size = \"1MB\"\nmax_size_revisions = 2\n",
+    )
+    .unwrap();
+
+    let output = sizelint()
+        .args(["check", "--format", "json", "--config"])
+        .arg(&config)
+        .args(["--git", "base..HEAD"])
+        .current_dir(&repo.root)
+        .output()
+        .unwrap();
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    let churn: Vec<&serde_json::Value> = summary["violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|v| v["rule_name"] == "max_size_revisions")
+        .collect();
+    assert_eq!(churn.len(), 1);
+    assert_eq!(churn[0]["path"], "model.bin");
+    assert_eq!(churn[0]["actual_value"], "3 sizes");
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_repository_without_commits() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    TestGitRepo::git(root, &["init", "-b", "main"]);
+    std::fs::write(root.join("sizelint.toml"), "max_file_size = \"1KB\"\n").unwrap();
+    std::fs::write(root.join("big.bin"), vec![0u8; 2048]).unwrap();
+    TestGitRepo::git(root, &["add", "big.bin"]);
+    let check = |args: &[&str]| {
+        sizelint()
+            .args(["--no-global-config", "check"])
+            .args(args)
+            .current_dir(root)
+            .output()
+            .unwrap()
+    };
+
+    // Staged files are diffed against the empty tree
+    let staged = check(&["--staged", "--format", "json"]);
+    assert_eq!(staged.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&staged.stdout).unwrap();
+    assert_eq!(report["violations"][0]["path"], "big.bin");
+
+    for args in [&["--git", "main"][..], &["--history-max", "1KB"]] {
+        let output = check(args);
+        assert_eq!(output.status.code(), Some(0), "{args:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("no commits yet"), "{args:?}: {stderr}");
+    }
+}